@@ -1,31 +1,101 @@
-use std::collections::HashMap;
+use std::sync::Arc;
 
+use chrono::{DateTime, Duration, Utc};
 use reqwest::{Client, Method};
+use serde::Deserialize;
 use url::Url;
 
+use crate::clock::ServerClock;
 use crate::errors::BinanceError;
-use crate::request::{make_request, sign_request};
-use crate::types::account::Account;
-use crate::types::general::Error;
+use crate::rate_limit::RateLimiter;
+use crate::signer::{HmacSigner, Signer};
+use crate::types::account::{Account, AccountTrade};
 
 use super::get_base_url;
+use super::rest::{Endpoint, Params, RestClient};
+
+const ACCOUNT: Endpoint = Endpoint::signed(Method::GET, "account", 20);
+const CREATE_LISTEN_KEY: Endpoint = Endpoint::api_key(Method::POST, "userDataStream", 2);
+const KEEPALIVE_LISTEN_KEY: Endpoint = Endpoint::api_key(Method::PUT, "userDataStream", 2);
+const CLOSE_LISTEN_KEY: Endpoint = Endpoint::api_key(Method::DELETE, "userDataStream", 2);
+const MY_TRADES: Endpoint = Endpoint::signed(Method::GET, "myTrades", 20);
+
+/// The maximum number of trades returned by `myTrades`.
+const MY_TRADES_LIMIT: u32 = 1000;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListenKey {
+    listen_key: String,
+}
 
 #[derive(Debug, Clone)]
 pub struct BinanceAccountClient {
-    client: Client,
-    api_key: String,
-    secret: String,
-    base_url: Url,
+    rest: RestClient,
 }
 
 impl BinanceAccountClient {
     pub fn new(client: Client, api_key: String, secret: String, testnet: bool) -> Self {
-        return Self {
-            client,
-            api_key,
-            secret,
-            base_url: get_base_url(testnet),
-        };
+        return Self::with_base_url(client, api_key, secret, None, get_base_url(testnet));
+    }
+
+    /// Creates a client that sends requests to `base_url`.
+    ///
+    /// `recv_window` is used for requests that do not specify their own.
+    pub fn with_base_url(
+        client: Client,
+        api_key: String,
+        secret: String,
+        recv_window: Option<u32>,
+        base_url: Url,
+    ) -> Self {
+        let mut rest = RestClient::new(client, base_url)
+            .with_credentials(api_key, Arc::new(HmacSigner::new(secret)));
+        rest.set_recv_window(recv_window);
+        rest.use_server_clock();
+        return Self { rest };
+    }
+
+    /// Shares `rate_limiter` with this client instead of its own one.
+    ///
+    /// The server time requests of its own clock are counted by
+    /// `rate_limiter` as well.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rest.set_rate_limiter(rate_limiter);
+        return self;
+    }
+
+    pub const fn rate_limiter(&self) -> &RateLimiter {
+        return self.rest.rate_limiter();
+    }
+
+    /// Signs requests with `signer` (e.g. an Ed25519 or RSA key) instead of
+    /// the HMAC secret passed to the constructor.
+    #[must_use]
+    pub fn with_signer(mut self, signer: Arc<dyn Signer>) -> Self {
+        self.rest.set_signer(signer);
+        return self;
+    }
+
+    /// Shares `clock` with this client instead of its own one.
+    #[must_use]
+    pub fn with_clock(mut self, clock: ServerClock) -> Self {
+        self.rest.set_clock(clock);
+        return self;
+    }
+
+    /// The server clock used to stamp signed requests.
+    pub fn clock(&self) -> Option<&ServerClock> {
+        return self.rest.clock();
+    }
+
+    pub const fn base_url(&self) -> &Url {
+        return self.rest.base_url();
+    }
+
+    pub fn api_key(&self) -> &str {
+        return self.rest.api_key();
     }
 
     pub async fn get_account(
@@ -33,41 +103,143 @@ impl BinanceAccountClient {
         omit_zero_balances: Option<bool>,
         recv_window: Option<i64>,
     ) -> Result<Account, BinanceError> {
-        let url = self.base_url.join("account")?;
-        let method = Method::GET;
-        let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+        let params = Params::new()
+            .with_opt("omitZeroBalances", omit_zero_balances)
+            .with_opt("recvWindow", recv_window);
+        return self.rest.send(&ACCOUNT, &params).await;
+    }
 
-        let mut params = HashMap::new();
-        params.insert("timestamp", timestamp);
+    /// Creates a listen key for the user data stream, valid for 60 minutes
+    /// unless kept alive. Returns the existing key if one is open.
+    pub async fn create_listen_key(&self) -> Result<String, BinanceError> {
+        let response: ListenKey = self.rest.send(&CREATE_LISTEN_KEY, &Params::new()).await?;
+        return Ok(response.listen_key);
+    }
 
-        if let Some(omit) = omit_zero_balances {
-            params.insert("omitZeroBalances", omit.to_string());
-        }
+    /// Extends the validity of `listen_key` to 60 minutes from now.
+    pub async fn keepalive_listen_key(&self, listen_key: &str) -> Result<(), BinanceError> {
+        let params = Params::new().with("listenKey", listen_key);
+        self.rest
+            .send::<serde_json::Value>(&KEEPALIVE_LISTEN_KEY, &params)
+            .await?;
+        return Ok(());
+    }
 
-        if let Some(window) = recv_window {
-            params.insert("recvWindow", window.to_string());
-        }
+    /// Closes the user data stream of `listen_key`.
+    pub async fn close_listen_key(&self, listen_key: &str) -> Result<(), BinanceError> {
+        let params = Params::new().with("listenKey", listen_key);
+        self.rest
+            .send::<serde_json::Value>(&CLOSE_LISTEN_KEY, &params)
+            .await?;
+        return Ok(());
+    }
+
+    /// Returns the trades of the account on `symbol`, optionally only those of
+    /// `order_id`, from trade id `from_id`, or within a window of at most 24
+    /// hours between `start_time` and `end_time`.
+    pub async fn get_my_trades(
+        &self,
+        symbol: &str,
+        order_id: Option<u64>,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+        from_id: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<AccountTrade>, BinanceError> {
+        // Querying the trades of a single order is lighter.
+        let endpoint = if order_id.is_some() {
+            MY_TRADES.with_weight(5)
+        } else {
+            MY_TRADES
+        };
+        let params = Params::new()
+            .with("symbol", symbol)
+            .with_opt("orderId", order_id)
+            .with_opt("startTime", start_time.map(|t| t.timestamp_millis()))
+            .with_opt("endTime", end_time.map(|t| t.timestamp_millis()))
+            .with_opt("fromId", from_id)
+            .with_opt("limit", limit);
+        return self.rest.send(&endpoint, &params).await;
+    }
 
-        let request = make_request(&self.client, method, &url, &params)?;
-        let signed_request = sign_request(request, &self.api_key, &self.secret).unwrap();
+    /// Returns every trade on `symbol` between `start_time` (inclusive) and
+    /// `end_time` (exclusive).
+    ///
+    /// The range is queried in 24 hour windows, the longest `myTrades`
+    /// accepts. Once a window holds more trades than fit in one response, the
+    /// remaining trades are fetched by id with `fromId`.
+    pub async fn get_historical_my_trades(
+        &self,
+        symbol: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<AccountTrade>, BinanceError> {
+        let mut all_trades = Vec::new();
+        let mut window_start = start_time;
 
-        let response = self.client.execute(signed_request).await?;
+        while window_start < end_time {
+            let window_end = (window_start + Duration::days(1)).min(end_time);
+            let trades = self
+                .get_my_trades(
+                    symbol,
+                    None,
+                    Some(window_start),
+                    Some(window_end - Duration::milliseconds(1)),
+                    None,
+                    Some(MY_TRADES_LIMIT),
+                )
+                .await?;
+            let fetched = trades.len();
+            all_trades.extend(trades);
 
-        if !response.status().is_success() {
-            let status_code: u16 = response.status().as_u16();
-            let error: Error = response.json().await?;
-            return Err(BinanceError::Api(status_code, error));
+            if fetched >= MY_TRADES_LIMIT as usize {
+                self.follow_my_trades(symbol, end_time, &mut all_trades)
+                    .await?;
+                break;
+            }
+            window_start = window_end;
         }
 
-        let account: Account = response.json().await?;
+        return Ok(all_trades);
+    }
+
+    /// Appends the trades following the last one of `trades` until `end_time`.
+    async fn follow_my_trades(
+        &self,
+        symbol: &str,
+        end_time: DateTime<Utc>,
+        trades: &mut Vec<AccountTrade>,
+    ) -> Result<(), BinanceError> {
+        while let Some(last) = trades.last() {
+            let page = self
+                .get_my_trades(
+                    symbol,
+                    None,
+                    None,
+                    None,
+                    Some(last.id + 1),
+                    Some(MY_TRADES_LIMIT),
+                )
+                .await?;
+            let fetched = page.len();
+            let before = trades.len();
+            trades.extend(page.into_iter().take_while(|t| t.time < end_time));
 
-        Ok(account)
+            if fetched < MY_TRADES_LIMIT as usize || trades.len() - before < fetched {
+                break;
+            }
+        }
+        return Ok(());
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::env;
+    use rust_decimal::Decimal;
+
+    use crate::mock::{MOCK_API_KEY, MockServer};
+    use crate::signer::Ed25519Signer;
+    use crate::types::general::{BinanceErrorCode, RateLimitType};
 
     use super::*;
 
@@ -78,10 +250,9 @@ mod tests {
 
         let client =
             BinanceAccountClient::new(Client::new(), api_key.to_string(), secret.to_string(), true);
-        assert_eq!(client.api_key, api_key);
-        assert_eq!(client.secret, secret);
+        assert_eq!(client.api_key(), api_key);
         assert_eq!(
-            client.base_url.as_str(),
+            client.base_url().as_str(),
             "https://testnet.binance.vision/api/v3/"
         );
 
@@ -91,20 +262,137 @@ mod tests {
             secret.to_string(),
             false,
         );
-        assert_eq!(client.api_key, api_key);
-        assert_eq!(client.secret, secret);
-        assert_eq!(client.base_url.as_str(), "https://api.binance.com/api/v3/");
+        assert_eq!(client.api_key(), api_key);
+        assert_eq!(
+            client.base_url().as_str(),
+            "https://api.binance.com/api/v3/"
+        );
     }
 
     #[tokio::test]
-    async fn test_get_account() {
-        let api_key = env::var("BINANCE_TEST_API_KEY").unwrap();
-        let secret = env::var("BINANCE_TEST_SECRET").unwrap();
+    async fn test_clock_shares_rate_limiter() {
+        let server = MockServer::start().await;
+        let rate_limiter = RateLimiter::default();
+        let client = BinanceAccountClient::with_base_url(
+            Client::new(),
+            server.api_key().to_string(),
+            server.secret().to_string(),
+            None,
+            server.api_url(),
+        )
+        .with_rate_limiter(rate_limiter.clone());
 
-        let client =
-            BinanceAccountClient::new(Client::new(), api_key.clone(), secret.clone(), true);
+        client.get_account(None, None).await.unwrap();
+        // The server time request of the clock is counted too.
+        assert_eq!(server.requests()[0].path, "time");
+        let raw_requests = rate_limiter
+            .usage()
+            .into_iter()
+            .find(|usage| usage.rate_limit_type == RateLimitType::RawRequests)
+            .unwrap();
+        assert_eq!(raw_requests.used, 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_account() {
+        let server = MockServer::start().await;
+        let client = BinanceAccountClient::with_base_url(
+            Client::new(),
+            server.api_key().to_string(),
+            server.secret().to_string(),
+            None,
+            server.api_url(),
+        );
 
         let account = client.get_account(Some(true), None).await.unwrap();
         assert!(!account.balances.is_empty());
+        assert_eq!(account.commission_rates.maker, Decimal::new(1, 3));
+
+        server.set_balance("BTC", Decimal::new(5, 1), Decimal::new(25, 2));
+        let account = client.get_account(None, None).await.unwrap();
+        assert_eq!(account.free("BTC"), Decimal::new(5, 1));
+        assert_eq!(account.total("BTC"), Decimal::new(75, 2));
+        assert_eq!(account.total("XRP"), Decimal::ZERO);
+        assert!(account.balance("BNB").unwrap().is_zero());
+        let assets: Vec<_> = account.non_zero_balances().map(|b| &b.asset).collect();
+        assert_eq!(assets, ["USDT", "BTC"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_account_ed25519() {
+        let server =
+            MockServer::with_signer(MOCK_API_KEY, Ed25519Signer::from_bytes(&[7; 32])).await;
+        let client = BinanceAccountClient::with_base_url(
+            Client::new(),
+            server.api_key().to_string(),
+            String::new(),
+            None,
+            server.api_url(),
+        );
+
+        let error = client.get_account(None, None).await.unwrap_err();
+        assert_eq!(error.error_code(), Some(BinanceErrorCode::InvalidSignature));
+
+        let client = client.with_signer(server.signer());
+        client.get_account(None, None).await.unwrap();
+        let request = server.requests().pop().unwrap();
+        assert_eq!(request.param("signature").unwrap().len(), 88);
+    }
+
+    #[tokio::test]
+    async fn test_resync_on_invalid_timestamp() {
+        let server = MockServer::start().await;
+        let client = server.client().account;
+        client.get_account(None, None).await.unwrap();
+
+        // The local clock drifts after the first sync: the first attempt is
+        // rejected with -1021, then the clock is synced and the request retried.
+        server.set_time_offset(-60_000);
+        client.get_account(None, None).await.unwrap();
+        assert!((client.clock().unwrap().offset() + 60_000).abs() < 1_000);
+
+        let paths = server
+            .requests()
+            .into_iter()
+            .map(|request| request.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["time", "account", "account", "time", "account"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_my_trades() {
+        let server = MockServer::start().await;
+        let client = server.client().account;
+        let now = DateTime::from_timestamp_millis(Utc::now().timestamp_millis()).unwrap();
+        let start = now - Duration::days(3);
+
+        // The second daily window holds more trades than fit in one response.
+        for (day, count) in [(0, 300), (1, 1500), (2, 200)] {
+            for i in 0..count {
+                let time = start + Duration::days(day) + Duration::seconds(i * 86_400 / count);
+                server.add_trade(
+                    "BTCUSDT",
+                    Decimal::from(85_000),
+                    Decimal::ONE,
+                    time.timestamp_millis(),
+                );
+            }
+        }
+
+        let trades = client
+            .get_historical_my_trades("BTCUSDT", start, now)
+            .await
+            .unwrap();
+        assert_eq!(trades.len(), 2000);
+        assert!(trades.windows(2).all(|pair| pair[0].id < pair[1].id));
+        assert!(trades.iter().all(|t| t.time >= start && t.time < now));
+        assert_eq!(trades[0].commission_asset, "BNB");
+
+        let recent = client
+            .get_my_trades("BTCUSDT", None, None, None, None, Some(10))
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 10);
+        assert_eq!(recent.last().unwrap().id, trades.last().unwrap().id);
     }
 }
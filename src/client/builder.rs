@@ -0,0 +1,356 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Client;
+use url::Url;
+
+use crate::clock::ServerClock;
+use crate::errors::BinanceError;
+use crate::rate_limit::RateLimiter;
+use crate::signer::{HmacSigner, Signer};
+use crate::symbol_registry::SymbolRegistry;
+use crate::websocket::BinanceWebSocket;
+use crate::ws_api::BinanceWsApiClient;
+
+use super::{
+    MAINNET_STREAM_URL, MAINNET_URL, MAINNET_WS_API_URL, TESTNET_STREAM_URL, TESTNET_URL,
+    TESTNET_WS_API_URL, account::BinanceAccountClient, general::BinanceGeneralClient, get_api_url,
+    get_ws_api_url, market::BinanceMarketClient, root::BinanceClient,
+    trading::BinanceTradingClient,
+};
+
+/// Builder for [`BinanceClient`] that allows every endpoint host to be overridden.
+///
+/// URLs are root URLs (e.g. `https://api1.binance.com` or `http://127.0.0.1:8080`);
+/// the `/api/v3/` and `/ws/` paths are appended by the clients themselves.
+#[derive(Debug, Default, Clone)]
+pub struct BinanceClientBuilder {
+    api_key: String,
+    secret: String,
+    testnet: bool,
+    base_url: Option<String>,
+    market_data_url: Option<String>,
+    stream_url: Option<String>,
+    ws_api_url: Option<String>,
+    http_client: Option<Client>,
+    recv_window: Option<u32>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    rate_limiter: Option<RateLimiter>,
+    time_sync_interval: Option<Duration>,
+    symbol_refresh_interval: Option<Duration>,
+    signer: Option<Arc<dyn Signer>>,
+}
+
+impl BinanceClientBuilder {
+    #[must_use]
+    pub fn new(api_key: String, secret: String) -> Self {
+        return Self {
+            api_key,
+            secret,
+            ..Default::default()
+        };
+    }
+
+    /// Signs requests with `signer` instead of an HMAC key made from the secret,
+    /// e.g. with an [`Ed25519Signer`](crate::signer::Ed25519Signer).
+    #[must_use]
+    pub fn signer(mut self, signer: impl Signer + 'static) -> Self {
+        self.signer = Some(Arc::new(signer));
+        return self;
+    }
+
+    /// Uses the testnet hosts for every URL that is not set explicitly.
+    #[must_use]
+    pub const fn testnet(mut self, testnet: bool) -> Self {
+        self.testnet = testnet;
+        return self;
+    }
+
+    /// Sets the REST API root URL used by all sub-clients.
+    #[must_use]
+    pub fn base_url(mut self, url: &str) -> Self {
+        self.base_url = Some(url.to_string());
+        return self;
+    }
+
+    /// Sets the root URL used for public market data only
+    /// (e.g. `https://data-api.binance.vision`). Defaults to the REST base URL.
+    #[must_use]
+    pub fn market_data_url(mut self, url: &str) -> Self {
+        self.market_data_url = Some(url.to_string());
+        return self;
+    }
+
+    /// Sets the WebSocket market data stream root URL.
+    #[must_use]
+    pub fn stream_url(mut self, url: &str) -> Self {
+        self.stream_url = Some(url.to_string());
+        return self;
+    }
+
+    /// Sets the WebSocket API root URL.
+    #[must_use]
+    pub fn ws_api_url(mut self, url: &str) -> Self {
+        self.ws_api_url = Some(url.to_string());
+        return self;
+    }
+
+    /// Uses an existing HTTP client instead of building a new one.
+    ///
+    /// The timeout and user agent settings of this builder are ignored when a
+    /// client is provided, since they are part of the client itself.
+    #[must_use]
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.http_client = Some(client);
+        return self;
+    }
+
+    /// Sets the default `recvWindow` in milliseconds for signed requests.
+    #[must_use]
+    pub const fn recv_window(mut self, recv_window: u32) -> Self {
+        self.recv_window = Some(recv_window);
+        return self;
+    }
+
+    /// Sets the total timeout of a single HTTP request.
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        return self;
+    }
+
+    /// Sets the timeout for establishing an HTTP connection.
+    #[must_use]
+    pub const fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        return self;
+    }
+
+    #[must_use]
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        return self;
+    }
+
+    /// Shares an existing rate limiter, e.g. between clients that use the same IP
+    /// address. By default every built client gets its own limiter.
+    #[must_use]
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        return self;
+    }
+
+    /// Sets how often the server clock offset used for signed requests is
+    /// measured again. Defaults to 5 minutes.
+    #[must_use]
+    pub const fn time_sync_interval(mut self, interval: Duration) -> Self {
+        self.time_sync_interval = Some(interval);
+        return self;
+    }
+
+    /// Sets how often the exchange information of the symbol registry is
+    /// fetched again. Defaults to 30 minutes.
+    #[must_use]
+    pub const fn symbol_refresh_interval(mut self, interval: Duration) -> Self {
+        self.symbol_refresh_interval = Some(interval);
+        return self;
+    }
+
+    /// Builds the client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the URLs cannot be parsed or the HTTP client
+    /// cannot be built.
+    pub fn build(self) -> Result<BinanceClient, BinanceError> {
+        let base_url = match &self.base_url {
+            Some(url) => parse_root_url(url)?,
+            None => Url::parse(if self.testnet {
+                TESTNET_URL
+            } else {
+                MAINNET_URL
+            })?,
+        };
+        let market_data_url = match &self.market_data_url {
+            Some(url) => parse_root_url(url)?,
+            None => base_url.clone(),
+        };
+        let stream_url = match &self.stream_url {
+            Some(url) => parse_root_url(url)?,
+            None => Url::parse(if self.testnet {
+                TESTNET_STREAM_URL
+            } else {
+                MAINNET_STREAM_URL
+            })?,
+        };
+
+        let ws_api_url = match &self.ws_api_url {
+            Some(url) => parse_root_url(url)?,
+            None => Url::parse(if self.testnet {
+                TESTNET_WS_API_URL
+            } else {
+                MAINNET_WS_API_URL
+            })?,
+        };
+
+        let client = if let Some(client) = self.http_client {
+            client
+        } else {
+            let mut builder = Client::builder();
+            if let Some(timeout) = self.timeout {
+                builder = builder.timeout(timeout);
+            }
+            if let Some(timeout) = self.connect_timeout {
+                builder = builder.connect_timeout(timeout);
+            }
+            if let Some(user_agent) = &self.user_agent {
+                builder = builder.user_agent(user_agent);
+            }
+            builder.build()?
+        };
+
+        let rate_limiter = self.rate_limiter.unwrap_or_default();
+        let signer = self
+            .signer
+            .unwrap_or_else(|| Arc::new(HmacSigner::new(self.secret.clone())));
+        let api_url = get_api_url(&base_url)?;
+        let general = BinanceGeneralClient::with_base_url(client.clone(), api_url.clone())
+            .with_rate_limiter(rate_limiter.clone());
+        let mut clock = ServerClock::new(general.clone());
+        if let Some(interval) = self.time_sync_interval {
+            clock = clock.with_sync_interval(interval);
+        }
+        let mut symbols = SymbolRegistry::new(general.clone());
+        if let Some(interval) = self.symbol_refresh_interval {
+            symbols = symbols.with_refresh_interval(interval);
+        }
+
+        return Ok(BinanceClient {
+            general,
+            market: BinanceMarketClient::with_base_url(
+                client.clone(),
+                get_api_url(&market_data_url)?,
+            )
+            .with_rate_limiter(rate_limiter.clone()),
+            trading: BinanceTradingClient::with_base_url(
+                client.clone(),
+                self.api_key.clone(),
+                self.secret.clone(),
+                self.recv_window,
+                api_url.clone(),
+            )
+            .with_rate_limiter(rate_limiter.clone())
+            .with_clock(clock.clone())
+            .with_signer(signer.clone())
+            .with_symbol_registry(symbols.clone()),
+            account: BinanceAccountClient::with_base_url(
+                client,
+                self.api_key.clone(),
+                self.secret,
+                self.recv_window,
+                api_url,
+            )
+            .with_rate_limiter(rate_limiter)
+            .with_clock(clock.clone())
+            .with_signer(signer.clone()),
+            websocket: BinanceWebSocket::with_base_url(stream_url),
+            ws_api: BinanceWsApiClient::new(get_ws_api_url(&ws_api_url)?, self.api_key, signer)
+                .with_clock(clock.clone()),
+            clock,
+            symbols,
+        });
+    }
+}
+
+/// Parses a root URL, making sure it ends with a slash so that relative paths
+/// are appended to it instead of replacing its last segment.
+fn parse_root_url(url: &str) -> Result<Url, url::ParseError> {
+    if url.ends_with('/') {
+        return Url::parse(url);
+    }
+    return Url::parse(&format!("{url}/"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_urls() {
+        let client = BinanceClientBuilder::default().build().unwrap();
+        assert_eq!(
+            client.general.base_url().as_str(),
+            "https://api.binance.com/api/v3/"
+        );
+        assert_eq!(
+            client.websocket.base_url().as_str(),
+            "wss://stream.binance.com/"
+        );
+        assert_eq!(
+            client.ws_api.url().as_str(),
+            "wss://ws-api.binance.com/ws-api/v3"
+        );
+
+        let client = BinanceClientBuilder::default()
+            .testnet(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.trading.base_url().as_str(),
+            "https://testnet.binance.vision/api/v3/"
+        );
+        assert_eq!(
+            client.websocket.base_url().as_str(),
+            "wss://stream.testnet.binance.vision/"
+        );
+    }
+
+    #[test]
+    fn test_custom_urls() {
+        let client = BinanceClientBuilder::new("key".to_string(), "secret".to_string())
+            .base_url("http://127.0.0.1:8080")
+            .market_data_url("https://data-api.binance.vision")
+            .stream_url("ws://127.0.0.1:8081/proxy")
+            .ws_api_url("ws://127.0.0.1:8082")
+            .recv_window(5000)
+            .timeout(Duration::from_secs(10))
+            .user_agent("rust_binance-test")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            client.general.base_url().as_str(),
+            "http://127.0.0.1:8080/api/v3/"
+        );
+        assert_eq!(
+            client.trading.base_url().as_str(),
+            "http://127.0.0.1:8080/api/v3/"
+        );
+        assert_eq!(
+            client.account.base_url().as_str(),
+            "http://127.0.0.1:8080/api/v3/"
+        );
+        assert_eq!(
+            client.market.base_url().as_str(),
+            "https://data-api.binance.vision/api/v3/"
+        );
+        assert_eq!(
+            client.websocket.base_url().as_str(),
+            "ws://127.0.0.1:8081/proxy/"
+        );
+        assert_eq!(
+            client.ws_api.url().as_str(),
+            "ws://127.0.0.1:8082/ws-api/v3"
+        );
+    }
+
+    #[test]
+    fn test_invalid_url() {
+        let result = BinanceClientBuilder::default()
+            .base_url("not a url")
+            .build();
+        assert!(matches!(result, Err(BinanceError::Url(_))));
+    }
+}
@@ -1,96 +1,335 @@
-use reqwest::Client;
+use reqwest::{Client, Method};
 use url::Url;
 
 use crate::errors::BinanceError;
-use crate::types::general::{ExchangeInfo, Timestamp};
+use crate::rate_limit::RateLimiter;
+use crate::types::general::{ExchangeInfo, ExchangeInfoQuery, Timestamp};
 
 use super::get_base_url;
+use super::rest::{Endpoint, Params, RestClient};
+
+const PING: Endpoint = Endpoint::public(Method::GET, "ping", 1);
+const TIME: Endpoint = Endpoint::public(Method::GET, "time", 1);
+pub const EXCHANGE_INFO: Endpoint = Endpoint::public(Method::GET, "exchangeInfo", 20);
+
 #[derive(Debug, Clone)]
 pub struct BinanceGeneralClient {
-    client: Client,
-    base_url: Url,
+    rest: RestClient,
 }
 
 impl BinanceGeneralClient {
     pub fn new(client: Client, testnet: bool) -> Self {
+        return Self::with_base_url(client, get_base_url(testnet));
+    }
+
+    /// Creates a client that sends requests to `base_url` (e.g. `https://api1.binance.com/api/v3/`).
+    pub fn with_base_url(client: Client, base_url: Url) -> Self {
         return Self {
-            client,
-            base_url: get_base_url(testnet),
+            rest: RestClient::new(client, base_url),
         };
     }
 
+    /// Shares `rate_limiter` with this client instead of its own one.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rest.set_rate_limiter(rate_limiter);
+        return self;
+    }
+
+    pub const fn rate_limiter(&self) -> &RateLimiter {
+        return self.rest.rate_limiter();
+    }
+
+    pub const fn base_url(&self) -> &Url {
+        return self.rest.base_url();
+    }
+
     pub async fn ping(&self) -> Result<(), BinanceError> {
-        let url = self.base_url.join("ping")?;
-        self.client.get(url).send().await?.error_for_status()?;
+        self.rest
+            .send_public::<serde_json::Value>(&PING, &Params::new())
+            .await?;
         Ok(())
     }
 
     pub async fn get_time(&self) -> Result<Timestamp, BinanceError> {
-        let url = self.base_url.join("time")?;
-        let resp = self
-            .client
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Timestamp>()
-            .await?;
-        return Ok(resp);
+        return self.rest.send_public(&TIME, &Params::new()).await;
     }
 
+    /// Returns the exchange information of `symbols`, or of every symbol if
+    /// it is empty.
     pub async fn get_exchange_info(&self, symbols: &[&str]) -> Result<ExchangeInfo, BinanceError> {
-        let url = self.base_url.join("exchangeInfo")?;
-
-        // ["BTCUSDT", "ETHUSDT"]
-        let symbols_query = format!("[\"{}\"]", symbols.join("\",\""));
+        let query = ExchangeInfoQuery {
+            symbols: symbols.iter().map(ToString::to_string).collect(),
+            ..Default::default()
+        };
+        return self.get_exchange_info_with(&query).await;
+    }
 
-        let resp = self
-            .client
-            .get(url)
-            .query(&[("symbols", symbols_query)])
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<ExchangeInfo>()
-            .await?;
+    /// Returns the exchange information of the symbols selected by `query`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BinanceError::NotAllowed`] without sending the request if
+    /// `symbols` is combined with `permissions` or `symbol_status`.
+    pub async fn get_exchange_info_with(
+        &self,
+        query: &ExchangeInfoQuery,
+    ) -> Result<ExchangeInfo, BinanceError> {
+        let mut params = Params::new();
+        if !query.symbols.is_empty() {
+            if !query.permissions.is_empty() || query.symbol_status.is_some() {
+                return Err(BinanceError::NotAllowed(
+                    "permissions and symbolStatus cannot be combined with symbols".to_string(),
+                ));
+            }
+            params = params.with_list("symbols", &query.symbols);
+        }
+        if !query.permissions.is_empty() {
+            params = params.with_list("permissions", &query.permissions);
+        }
+        let params = params
+            .with_opt("showPermissionSets", query.show_permission_sets)
+            .with_opt("symbolStatus", query.symbol_status);
+        let resp: ExchangeInfo = self.rest.send_public(&EXCHANGE_INFO, &params).await?;
+        self.rest.rate_limiter().set_limits(&resp.rate_limits);
         return Ok(resp);
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use reqwest::Method;
+
+    use rust_decimal::Decimal;
+    use serde_json::json;
+
+    use crate::mock::{MockResponse, MockServer};
+    use crate::rate_limit::RateLimitMode;
+    use crate::types::general::{
+        BinanceErrorCode, ExchangeFilter, MaxNumOrderAmendsFilter, MaxNumOrderListsFilter,
+        MaxNumOrdersFilter, PriceFilter, RateLimitType, SymbolFilter, SymbolStatus,
+    };
+    use crate::types::trading::OrderType;
+
     use super::*;
 
     #[test]
     fn test_new_client() {
         let client = BinanceGeneralClient::new(Client::new(), true);
         assert_eq!(
-            client.base_url.as_str(),
+            client.base_url().as_str(),
             "https://testnet.binance.vision/api/v3/"
         );
 
         let client = BinanceGeneralClient::new(Client::new(), false);
-        assert_eq!(client.base_url.as_str(), "https://api.binance.com/api/v3/");
+        assert_eq!(
+            client.base_url().as_str(),
+            "https://api.binance.com/api/v3/"
+        );
     }
 
     #[tokio::test]
     async fn test_ping() {
-        let client = BinanceGeneralClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceGeneralClient::with_base_url(Client::new(), server.api_url());
         let result = client.ping().await;
         result.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_ping_rate_limited() {
+        let server = MockServer::start().await;
+        // A zero Retry-After keeps the rate limiter from delaying the next request.
+        server.push_response(Method::GET, "ping", MockResponse::rate_limited(0));
+        server.push_response(Method::GET, "ping", MockResponse::banned(0));
+        server.push_response(
+            Method::GET,
+            "ping",
+            MockResponse {
+                status: 503,
+                headers: Vec::new(),
+                body: "Service Unavailable".to_string(),
+            },
+        );
+        let client = BinanceGeneralClient::with_base_url(Client::new(), server.api_url());
+
+        let error = client.ping().await.unwrap_err();
+        assert_eq!(error.error_code(), Some(BinanceErrorCode::TooManyRequests));
+        assert!(error.is_rate_limited());
+        assert!(error.is_retryable());
+
+        let error = client.ping().await.unwrap_err();
+        assert_eq!(error.status(), Some(418));
+        assert!(error.is_rate_limited());
+
+        let error = client.ping().await.unwrap_err();
+        assert!(matches!(error, BinanceError::Status(503, _)));
+        assert!(error.is_unknown_execution_status());
+        assert!(!error.is_retryable());
+
+        client.ping().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter() {
+        let server = MockServer::start().await;
+        let client = BinanceGeneralClient::with_base_url(Client::new(), server.api_url())
+            .with_rate_limiter(RateLimiter::new(RateLimitMode::Reject));
+
+        client.get_exchange_info(&["BTCUSDT"]).await.unwrap();
+        let usage = client.rate_limiter().usage();
+        assert_eq!(usage.len(), 4);
+        assert_eq!(usage[0].limit, 6000);
+        assert!(usage[0].used >= 20);
+
+        server.push_response(
+            Method::GET,
+            "ping",
+            MockResponse::json(&serde_json::json!({})).with_header("X-MBX-USED-WEIGHT-1M", "6000"),
+        );
+        client.ping().await.unwrap();
+        let error = client.ping().await.unwrap_err();
+        assert!(matches!(error, BinanceError::RateLimited(_)));
+
+        let client = BinanceGeneralClient::with_base_url(Client::new(), server.api_url())
+            .with_rate_limiter(RateLimiter::new(RateLimitMode::Reject));
+        server.push_response(Method::GET, "ping", MockResponse::rate_limited(60));
+        assert_eq!(client.ping().await.unwrap_err().status(), Some(429));
+        let error = client.ping().await.unwrap_err();
+        assert!(matches!(error, BinanceError::RateLimited(wait) if wait.as_secs() >= 59));
+        assert_eq!(server.requests().len(), 3);
+    }
+
     #[tokio::test]
     async fn test_get_time() {
-        let client = BinanceGeneralClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceGeneralClient::with_base_url(Client::new(), server.api_url());
         let result = client.get_time().await;
         assert!(result.unwrap().server_time > 0);
     }
 
     #[tokio::test]
     async fn test_get_exchange_info() {
-        let client = BinanceGeneralClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceGeneralClient::with_base_url(Client::new(), server.api_url());
         let result = client.get_exchange_info(&["BTCUSDT", "ETHUSDT"]).await;
-        assert!(!result.unwrap().symbols.is_empty());
+        let info = result.unwrap();
+        assert!(!info.symbols.is_empty());
+
+        let symbol = &info.symbols[0];
+        assert_eq!(symbol.status, SymbolStatus::Trading);
+        assert!(symbol.order_types.contains(&OrderType::LimitMaker));
+        assert!(
+            symbol
+                .filters
+                .contains(&SymbolFilter::PriceFilter(PriceFilter {
+                    min_price: Decimal::new(85, 1),
+                    max_price: Decimal::from(8_500_000),
+                    tick_size: Decimal::new(85, 1),
+                }))
+        );
+        assert!(
+            symbol
+                .filters
+                .iter()
+                .any(|filter| matches!(filter, SymbolFilter::Notional(n) if n.apply_min_to_market))
+        );
+        assert!(
+            symbol
+                .filters
+                .contains(&SymbolFilter::MaxNumOrderLists(MaxNumOrderListsFilter {
+                    max_num_order_lists: 20
+                }))
+        );
+        assert!(symbol.filters.contains(&SymbolFilter::MaxNumOrderAmends(
+            MaxNumOrderAmendsFilter {
+                max_num_order_amends: 10
+            }
+        )));
+        assert!(!symbol.filters.contains(&SymbolFilter::Unknown));
+    }
+
+    #[tokio::test]
+    async fn test_exchange_info_query() {
+        let server = MockServer::start().await;
+        server.set_symbol_status("ETHBTC", "BREAK");
+        let client = BinanceGeneralClient::with_base_url(Client::new(), server.api_url());
+
+        let info = client.get_exchange_info(&[]).await.unwrap();
+        assert_eq!(info.symbols.len(), 4);
+        assert_eq!(info.symbols[0].permission_sets, [["SPOT"]]);
+
+        let query = ExchangeInfoQuery {
+            permissions: vec!["SPOT".to_string()],
+            show_permission_sets: Some(false),
+            symbol_status: Some(SymbolStatus::Break),
+            ..Default::default()
+        };
+        let info = client.get_exchange_info_with(&query).await.unwrap();
+        assert_eq!(info.symbols.len(), 1);
+        assert_eq!(info.symbols[0].symbol, "ETHBTC");
+        assert!(info.symbols[0].permission_sets.is_empty());
+        let request = server.requests().pop().unwrap();
+        assert_eq!(request.param("permissions").as_deref(), Some(r#"["SPOT"]"#));
+        assert_eq!(request.param("symbolStatus").as_deref(), Some("BREAK"));
+
+        let query = ExchangeInfoQuery {
+            permissions: vec!["MARGIN".to_string()],
+            ..Default::default()
+        };
+        let info = client.get_exchange_info_with(&query).await.unwrap();
+        assert!(info.symbols.is_empty());
+
+        let query = ExchangeInfoQuery {
+            symbols: vec!["BTCUSDT".to_string()],
+            symbol_status: Some(SymbolStatus::Trading),
+            ..Default::default()
+        };
+        let error = client.get_exchange_info_with(&query).await.unwrap_err();
+        assert!(matches!(error, BinanceError::NotAllowed(_)));
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_exchange_info_unknown_entries() {
+        let server = MockServer::start().await;
+        let client = BinanceGeneralClient::with_base_url(Client::new(), server.api_url())
+            .with_rate_limiter(RateLimiter::new(RateLimitMode::Reject));
+        let url = server.api_url().join("exchangeInfo").unwrap();
+        let mut info: serde_json::Value = Client::new()
+            .get(url)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        info["rateLimits"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "rateLimitType": "CONNECTIONS", "interval": "MINUTE", "intervalNum": 1, "limit": 300 }));
+        info["exchangeFilters"] = json!([
+            { "filterType": "EXCHANGE_MAX_NUM_ORDERS", "maxNumOrders": 1000 },
+            { "filterType": "EXCHANGE_MAX_NUM_ORDER_LISTS", "maxNumOrderLists": 20 },
+            { "filterType": "EXCHANGE_MAX_NUM_NEW_FILTER", "maxNumNewFilter": 1 }
+        ]);
+        server.push_response(Method::GET, "exchangeInfo", MockResponse::json(&info));
+
+        let info = client.get_exchange_info(&[]).await.unwrap();
+        assert_eq!(info.rate_limits[4].rate_limit_type, RateLimitType::Unknown);
+        assert_eq!(
+            info.exchange_filters,
+            [
+                ExchangeFilter::ExchangeMaxNumOrders(MaxNumOrdersFilter {
+                    max_num_orders: 1000
+                }),
+                ExchangeFilter::ExchangeMaxNumOrderLists(MaxNumOrderListsFilter {
+                    max_num_order_lists: 20
+                }),
+                ExchangeFilter::Unknown
+            ]
+        );
+        // Unknown limits are not tracked.
+        assert_eq!(client.rate_limiter().usage().len(), 4);
     }
 }
@@ -1,51 +1,79 @@
 use chrono::{DateTime, Duration, Utc};
-use reqwest::Client;
+use reqwest::{Client, Method};
 use url::Url;
 
 use crate::errors::BinanceError;
+use crate::rate_limit::{
+    RateLimiter, depth_weight, ticker_24hr_weight, ticker_price_weight, ticker_window_weight,
+};
 use crate::types::market::{
     AveragePrice, CompressedTrade, Kline, KlineInterval, OrderBook, Ticker, Ticker24, Ticker24Mini,
     TickerBook, TickerMini, TickerPrice, Trade, WindowSize,
 };
 
 use super::get_base_url;
+use super::rest::{Endpoint, Params, RestClient};
+
+const DEPTH: Endpoint = Endpoint::public(Method::GET, "depth", 5);
+const TRADES: Endpoint = Endpoint::public(Method::GET, "trades", 25);
+const HISTORICAL_TRADES: Endpoint = Endpoint::public(Method::GET, "historicalTrades", 25);
+const AGG_TRADES: Endpoint = Endpoint::public(Method::GET, "aggTrades", 4);
+const KLINES: Endpoint = Endpoint::public(Method::GET, "klines", 2);
+const UI_KLINES: Endpoint = Endpoint::public(Method::GET, "uiKlines", 2);
+const AVG_PRICE: Endpoint = Endpoint::public(Method::GET, "avgPrice", 2);
+const TICKER_24HR: Endpoint = Endpoint::public(Method::GET, "ticker/24hr", 2);
+const TICKER_TRADING_DAY: Endpoint = Endpoint::public(Method::GET, "ticker/tradingDay", 4);
+const TICKER_PRICE: Endpoint = Endpoint::public(Method::GET, "ticker/price", 2);
+const TICKER_BOOK: Endpoint = Endpoint::public(Method::GET, "ticker/bookTicker", 2);
+const TICKER: Endpoint = Endpoint::public(Method::GET, "ticker", 4);
 
 #[derive(Debug, Clone)]
 pub struct BinanceMarketClient {
-    client: Client,
-    base_url: Url,
+    rest: RestClient,
 }
 
 impl BinanceMarketClient {
     pub fn new(client: Client, testnet: bool) -> Self {
+        return Self::with_base_url(client, get_base_url(testnet));
+    }
+
+    /// Creates a client that sends requests to `base_url`.
+    ///
+    /// Market data endpoints are public, so `base_url` may also point to a
+    /// market-data-only host such as `https://data-api.binance.vision/api/v3/`.
+    pub fn with_base_url(client: Client, base_url: Url) -> Self {
         return Self {
-            client,
-            base_url: get_base_url(testnet),
+            rest: RestClient::new(client, base_url),
         };
     }
 
+    /// Shares `rate_limiter` with this client instead of its own one.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rest.set_rate_limiter(rate_limiter);
+        return self;
+    }
+
+    pub const fn rate_limiter(&self) -> &RateLimiter {
+        return self.rest.rate_limiter();
+    }
+
+    pub const fn base_url(&self) -> &Url {
+        return self.rest.base_url();
+    }
+
     pub async fn get_depth(
         &self,
         symbol: &str,
         limit: Option<u32>,
     ) -> Result<OrderBook, BinanceError> {
-        let url = self.base_url.join("depth")?;
-        let mut query = vec![("symbol", symbol.to_string())];
-
-        if let Some(l) = limit {
-            query.push(("limit", l.to_string()));
-        }
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<OrderBook>()
-            .await?;
-        return Ok(resp);
+        let params = Params::new()
+            .with("symbol", symbol)
+            .with_opt("limit", limit);
+        return self
+            .rest
+            .send_public(&DEPTH.with_weight(depth_weight(limit)), &params)
+            .await;
     }
 
     pub async fn get_recent_trades(
@@ -53,23 +81,10 @@ impl BinanceMarketClient {
         symbol: &str,
         limit: Option<u32>,
     ) -> Result<Vec<Trade>, BinanceError> {
-        let url = self.base_url.join("trades")?;
-        let mut query = vec![("symbol", symbol.to_string())];
-
-        if let Some(l) = limit {
-            query.push(("limit", l.to_string()));
-        }
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<Trade>>()
-            .await?;
-        return Ok(resp);
+        let params = Params::new()
+            .with("symbol", symbol)
+            .with_opt("limit", limit);
+        return self.rest.send_public(&TRADES, &params).await;
     }
 
     pub async fn get_historical_trades(
@@ -78,26 +93,11 @@ impl BinanceMarketClient {
         limit: Option<u32>,
         from_id: Option<u32>,
     ) -> Result<Vec<Trade>, BinanceError> {
-        let url = self.base_url.join("historicalTrades")?;
-        let mut query = vec![("symbol", symbol.to_string())];
-
-        if let Some(l) = limit {
-            query.push(("limit", l.to_string()));
-        }
-        if let Some(id) = from_id {
-            query.push(("fromId", id.to_string()));
-        }
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<Trade>>()
-            .await?;
-        return Ok(resp);
+        let params = Params::new()
+            .with("symbol", symbol)
+            .with_opt("limit", limit)
+            .with_opt("fromId", from_id);
+        return self.rest.send_public(&HISTORICAL_TRADES, &params).await;
     }
 
     pub async fn get_compressed_trades(
@@ -108,32 +108,13 @@ impl BinanceMarketClient {
         start_time: Option<DateTime<Utc>>,
         end_time: Option<DateTime<Utc>>,
     ) -> Result<Vec<CompressedTrade>, BinanceError> {
-        let url = self.base_url.join("aggTrades")?;
-        let mut query = vec![("symbol", symbol.to_string())];
-
-        if let Some(l) = limit {
-            query.push(("limit", l.to_string()));
-        }
-        if let Some(id) = from_id {
-            query.push(("fromId", id.to_string()));
-        }
-        if let Some(start) = start_time {
-            query.push(("startTime", start.timestamp_millis().to_string()));
-        }
-        if let Some(end) = end_time {
-            query.push(("endTime", end.timestamp_millis().to_string()));
-        }
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<CompressedTrade>>()
-            .await?;
-        return Ok(resp);
+        let params = Params::new()
+            .with("symbol", symbol)
+            .with_opt("limit", limit)
+            .with_opt("fromId", from_id)
+            .with_opt("startTime", start_time.map(|t| t.timestamp_millis()))
+            .with_opt("endTime", end_time.map(|t| t.timestamp_millis()));
+        return self.rest.send_public(&AGG_TRADES, &params).await;
     }
 
     pub async fn get_klines(
@@ -145,36 +126,8 @@ impl BinanceMarketClient {
         end_time: Option<DateTime<Utc>>,
         timezone: Option<String>,
     ) -> Result<Vec<Kline>, BinanceError> {
-        let url = self.base_url.join("klines")?;
-        let mut query = vec![
-            ("symbol", symbol.to_string()),
-            ("interval", interval.to_string()),
-        ];
-
-        if let Some(l) = limit {
-            query.push(("limit", l.to_string()));
-        }
-        if let Some(start) = start_time {
-            query.push(("startTime", start.timestamp_millis().to_string()));
-        }
-        if let Some(end) = end_time {
-            query.push(("endTime", end.timestamp_millis().to_string()));
-        }
-        if let Some(tz) = timezone {
-            query.push(("timezone", tz));
-        }
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<Kline>>()
-            .await?;
-
-        return Ok(resp);
+        let params = kline_params(symbol, &interval, limit, start_time, end_time, timezone);
+        return self.rest.send_public(&KLINES, &params).await;
     }
 
     pub async fn get_ui_klines(
@@ -186,179 +139,61 @@ impl BinanceMarketClient {
         end_time: Option<DateTime<Utc>>,
         timezone: Option<String>,
     ) -> Result<Vec<Kline>, BinanceError> {
-        let url = self.base_url.join("uiKlines")?;
-        let mut query = vec![
-            ("symbol", symbol.to_string()),
-            ("interval", interval.to_string()),
-        ];
-
-        if let Some(l) = limit {
-            query.push(("limit", l.to_string()));
-        }
-        if let Some(start) = start_time {
-            query.push(("startTime", start.timestamp_millis().to_string()));
-        }
-        if let Some(end) = end_time {
-            query.push(("endTime", end.timestamp_millis().to_string()));
-        }
-        if let Some(tz) = timezone {
-            query.push(("timezone", tz));
-        }
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<Kline>>()
-            .await?;
-
-        return Ok(resp);
+        let params = kline_params(symbol, &interval, limit, start_time, end_time, timezone);
+        return self.rest.send_public(&UI_KLINES, &params).await;
     }
 
     pub async fn get_average_price(&self, symbol: &str) -> Result<AveragePrice, BinanceError> {
-        let url = self.base_url.join("avgPrice")?;
-        let query = vec![("symbol", symbol.to_string())];
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<AveragePrice>()
-            .await?;
-
-        return Ok(resp);
+        let params = Params::new().with("symbol", symbol);
+        return self.rest.send_public(&AVG_PRICE, &params).await;
     }
 
     pub async fn get_ticker_24hr(&self, symbols: &[&str]) -> Result<Vec<Ticker24>, BinanceError> {
-        let url = self.base_url.join("ticker/24hr")?;
-
-        let symbols_query = format!("[\"{}\"]", symbols.join("\",\""));
-        let query = vec![("symbols", symbols_query)];
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<Ticker24>>()
-            .await?;
-
-        return Ok(resp);
+        let endpoint = TICKER_24HR.with_weight(ticker_24hr_weight(symbols.len()));
+        let params = Params::new().with_symbols(symbols);
+        return self.rest.send_public(&endpoint, &params).await;
     }
 
     pub async fn get_ticker_24hr_mini(
         &self,
         symbols: &[&str],
     ) -> Result<Vec<Ticker24Mini>, BinanceError> {
-        let url = self.base_url.join("ticker/24hr")?;
-
-        let symbols_query = format!("[\"{}\"]", symbols.join("\",\""));
-        let query = vec![("symbols", symbols_query), ("type", "MINI".to_string())];
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<Ticker24Mini>>()
-            .await?;
-
-        return Ok(resp);
+        let endpoint = TICKER_24HR.with_weight(ticker_24hr_weight(symbols.len()));
+        let params = Params::new().with_symbols(symbols).with("type", "MINI");
+        return self.rest.send_public(&endpoint, &params).await;
     }
 
     pub async fn get_ticker_trading_day(
         &self,
         symbols: &[&str],
     ) -> Result<Vec<Ticker>, BinanceError> {
-        let url = self.base_url.join("ticker/tradingDay")?;
-
-        let symbols_query = format!("[\"{}\"]", symbols.join("\",\""));
-        let query = vec![("symbols", symbols_query)];
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<Ticker>>()
-            .await?;
-
-        return Ok(resp);
+        let endpoint = TICKER_TRADING_DAY.with_weight(ticker_window_weight(symbols.len()));
+        let params = Params::new().with_symbols(symbols);
+        return self.rest.send_public(&endpoint, &params).await;
     }
 
     pub async fn get_ticker_trading_day_mini(
         &self,
         symbols: &[&str],
     ) -> Result<Vec<TickerMini>, BinanceError> {
-        let url = self.base_url.join("ticker/tradingDay")?;
-
-        let symbols_query = format!("[\"{}\"]", symbols.join("\",\""));
-        let query = vec![("symbols", symbols_query), ("type", "MINI".to_string())];
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<TickerMini>>()
-            .await?;
-
-        return Ok(resp);
+        let endpoint = TICKER_TRADING_DAY.with_weight(ticker_window_weight(symbols.len()));
+        let params = Params::new().with_symbols(symbols).with("type", "MINI");
+        return self.rest.send_public(&endpoint, &params).await;
     }
 
     pub async fn get_ticker_price(
         &self,
         symbols: &[&str],
     ) -> Result<Vec<TickerPrice>, BinanceError> {
-        let url = self.base_url.join("ticker/price")?;
-
-        let symbols_query = format!("[\"{}\"]", symbols.join("\",\""));
-        let query = vec![("symbols", symbols_query)];
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<TickerPrice>>()
-            .await?;
-
-        return Ok(resp);
+        let endpoint = TICKER_PRICE.with_weight(ticker_price_weight(symbols.len()));
+        let params = Params::new().with_symbols(symbols);
+        return self.rest.send_public(&endpoint, &params).await;
     }
 
     pub async fn get_ticker_book(&self, symbols: &[&str]) -> Result<Vec<TickerBook>, BinanceError> {
-        let url = self.base_url.join("ticker/bookTicker")?;
-
-        let symbols_query = format!("[\"{}\"]", symbols.join("\",\""));
-        let query = vec![("symbols", symbols_query)];
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<TickerBook>>()
-            .await?;
-
-        return Ok(resp);
+        let endpoint = TICKER_BOOK.with_weight(ticker_price_weight(symbols.len()));
+        let params = Params::new().with_symbols(symbols);
+        return self.rest.send_public(&endpoint, &params).await;
     }
 
     pub async fn get_rolling_window_price_change(
@@ -366,25 +201,11 @@ impl BinanceMarketClient {
         symbols: &[&str],
         window_size: WindowSize,
     ) -> Result<Vec<Ticker>, BinanceError> {
-        let url = self.base_url.join("ticker")?;
-
-        let symbols_query = format!("[\"{}\"]", symbols.join("\",\""));
-        let query = vec![
-            ("symbols", symbols_query),
-            ("windowSize", window_size.to_string()),
-        ];
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<Ticker>>()
-            .await?;
-
-        return Ok(resp);
+        let endpoint = TICKER.with_weight(ticker_window_weight(symbols.len()));
+        let params = Params::new()
+            .with_symbols(symbols)
+            .with("windowSize", &window_size);
+        return self.rest.send_public(&endpoint, &params).await;
     }
 
     pub async fn get_rolling_window_price_change_mini(
@@ -392,26 +213,12 @@ impl BinanceMarketClient {
         symbols: &[&str],
         window_size: WindowSize,
     ) -> Result<Vec<TickerMini>, BinanceError> {
-        let url = self.base_url.join("ticker")?;
-
-        let symbols_query = format!("[\"{}\"]", symbols.join("\",\""));
-        let query = vec![
-            ("symbols", symbols_query),
-            ("windowSize", window_size.to_string()),
-            ("type", "MINI".to_string()),
-        ];
-
-        let resp = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<TickerMini>>()
-            .await?;
-
-        return Ok(resp);
+        let endpoint = TICKER.with_weight(ticker_window_weight(symbols.len()));
+        let params = Params::new()
+            .with_symbols(symbols)
+            .with("windowSize", &window_size)
+            .with("type", "MINI");
+        return self.rest.send_public(&endpoint, &params).await;
     }
 
     pub async fn get_historical_klines(
@@ -468,27 +275,51 @@ impl BinanceMarketClient {
     }
 }
 
+fn kline_params(
+    symbol: &str,
+    interval: &KlineInterval,
+    limit: Option<u32>,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+    timezone: Option<String>,
+) -> Params {
+    return Params::new()
+        .with("symbol", symbol)
+        .with("interval", interval)
+        .with_opt("limit", limit)
+        .with_opt("startTime", start_time.map(|t| t.timestamp_millis()))
+        .with_opt("endTime", end_time.map(|t| t.timestamp_millis()))
+        .with_opt("timezone", timezone);
+}
+
 #[cfg(test)]
 mod tests {
     use rust_decimal::Decimal;
 
+    use crate::mock::MockServer;
+    use crate::types::general::BinanceErrorCode;
+
     use super::*;
 
     #[test]
     fn test_new_client() {
         let client = BinanceMarketClient::new(Client::new(), true);
         assert_eq!(
-            client.base_url.as_str(),
+            client.base_url().as_str(),
             "https://testnet.binance.vision/api/v3/"
         );
 
         let client = BinanceMarketClient::new(Client::new(), false);
-        assert_eq!(client.base_url.as_str(), "https://api.binance.com/api/v3/");
+        assert_eq!(
+            client.base_url().as_str(),
+            "https://api.binance.com/api/v3/"
+        );
     }
 
     #[tokio::test]
     async fn test_get_depth() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client.get_depth("BTCUSDT", Some(5)).await;
         let depth = result.unwrap();
         assert!(!depth.bids.is_empty());
@@ -500,9 +331,21 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_get_depth_invalid_symbol() {
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
+
+        let error = client.get_depth("UNKNOWN", None).await.unwrap_err();
+        assert_eq!(error.status(), Some(400));
+        assert_eq!(error.error_code(), Some(BinanceErrorCode::BadSymbol));
+        assert!(!error.is_retryable());
+    }
+
     #[tokio::test]
     async fn test_get_recent_trades() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client.get_recent_trades("BTCUSDT", Some(5)).await;
         let trades = result.unwrap();
         assert!(!trades.is_empty());
@@ -511,7 +354,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_historical_trades() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client.get_historical_trades("BTCUSDT", Some(5), None).await;
         let trades = result.unwrap();
         assert!(!trades.is_empty());
@@ -520,7 +364,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_historical_trades_with_from_id() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client
             .get_historical_trades("BTCUSDT", Some(5), Some(1))
             .await;
@@ -531,7 +376,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_compressed_trades() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client
             .get_compressed_trades("BTCUSDT", Some(5), None, None, None)
             .await;
@@ -542,7 +388,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_klines() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client
             .get_klines("BTCUSDT", KlineInterval::OneDay, Some(5), None, None, None)
             .await;
@@ -553,7 +400,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_ui_klines() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client
             .get_ui_klines("BTCUSDT", KlineInterval::OneDay, Some(5), None, None, None)
             .await;
@@ -564,7 +412,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_average_price() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client.get_average_price("BTCUSDT").await;
         let average_price = result.unwrap();
         assert!(average_price.price > Decimal::from_str_exact("0.0").unwrap());
@@ -572,7 +421,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_ticker_24hr() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client.get_ticker_24hr(&["BTCUSDT"]).await;
         let ticker = result.unwrap();
         assert!(ticker[0].open_price > Decimal::from_str_exact("0.0").unwrap());
@@ -580,7 +430,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_ticker_24hr_mini() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client.get_ticker_24hr_mini(&["BTCUSDT"]).await;
         let ticker = result.unwrap();
         assert!(ticker[0].open_price > Decimal::from_str_exact("0.0").unwrap());
@@ -588,7 +439,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_ticker_trading_day() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client.get_ticker_trading_day(&["BTCUSDT"]).await;
         let ticker = result.unwrap();
         assert!(ticker[0].open_price > Decimal::from_str_exact("0.0").unwrap());
@@ -596,7 +448,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_ticker_trading_day_mini() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client.get_ticker_trading_day_mini(&["BTCUSDT"]).await;
         let ticker = result.unwrap();
         assert!(ticker[0].open_price > Decimal::from_str_exact("0.0").unwrap());
@@ -604,18 +457,25 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_ticker_price() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
 
         let result = client.get_ticker_price(&["BTCUSDT", "ETHUSDT"]).await;
         let ticker = result.unwrap();
         assert!(ticker[0].price > Decimal::from_str_exact("0.0").unwrap());
         assert!(ticker[1].price > Decimal::from_str_exact("0.0").unwrap());
         assert_eq!(ticker.len(), 2);
+
+        // Without symbols, every symbol is returned.
+        let ticker = client.get_ticker_price(&[]).await.unwrap();
+        assert_eq!(ticker.len(), 4);
+        assert_eq!(server.requests().pop().unwrap().param("symbols"), None);
     }
 
     #[tokio::test]
     async fn test_get_ticker_book() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client.get_ticker_book(&["BTCUSDT", "ETHUSDT"]).await;
         let ticker = result.unwrap();
         assert!(ticker[0].ask_price > Decimal::from_str_exact("0.0").unwrap());
@@ -625,7 +485,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_rolling_window_price_change() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         let result = client
             .get_rolling_window_price_change(&["BTCUSDT", "ETHUSDT"], WindowSize::Days(1))
             .await;
@@ -637,7 +498,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_rolling_window_price_change_mini() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
 
         let result = client
             .get_rolling_window_price_change_mini(&["BTCUSDT", "ETHUSDT"], WindowSize::Days(1))
@@ -651,7 +513,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_historical_klines() {
-        let client = BinanceMarketClient::new(Client::new(), true);
+        let server = MockServer::start().await;
+        let client = BinanceMarketClient::with_base_url(Client::new(), server.api_url());
         // Пример: получить 5 дневных свечей за последние 5 дней
         let now = chrono::Utc::now();
         let start_time = now - chrono::Duration::days(5);
@@ -1,24 +1,52 @@
 mod account;
+mod builder;
 mod general;
 mod market;
+mod rest;
 mod root;
 mod trading;
 
 use url::Url;
 
+pub use account::BinanceAccountClient;
+pub use builder::BinanceClientBuilder;
+pub use general::BinanceGeneralClient;
+pub use market::BinanceMarketClient;
 pub use root::BinanceClient;
 
 /// The API version to use for the Binance API.
 pub const API_VERSION: &str = "v3";
 
+/// The root URL of the Binance mainnet REST API.
+pub const MAINNET_URL: &str = "https://api.binance.com/";
+
+/// The root URL of the Binance testnet REST API.
+pub const TESTNET_URL: &str = "https://testnet.binance.vision/";
+
+/// The root URL of the Binance mainnet market data streams.
+pub const MAINNET_STREAM_URL: &str = "wss://stream.binance.com/";
+
+/// The root URL of the Binance testnet market data streams.
+pub const TESTNET_STREAM_URL: &str = "wss://stream.testnet.binance.vision/";
+
+/// The root URL of the Binance mainnet WebSocket API.
+pub const MAINNET_WS_API_URL: &str = "wss://ws-api.binance.com/";
+
+/// The root URL of the Binance testnet WebSocket API.
+pub const TESTNET_WS_API_URL: &str = "wss://ws-api.testnet.binance.vision/";
+
 /// Returns the base URL for the Binance API depending on whether the testnet is used or not.
 fn get_base_url(testnet: bool) -> Url {
-    if testnet {
-        return format!("https://testnet.binance.vision/api/{API_VERSION}/")
-            .parse()
-            .unwrap();
-    }
-    return format!("https://api.binance.com/api/{API_VERSION}/")
-        .parse()
-        .unwrap();
+    let root = if testnet { TESTNET_URL } else { MAINNET_URL };
+    return get_api_url(&root.parse().unwrap()).unwrap();
+}
+
+/// Returns the versioned REST API URL (`<root>/api/v3/`) for a root URL.
+fn get_api_url(root: &Url) -> Result<Url, url::ParseError> {
+    return root.join(&format!("api/{API_VERSION}/"));
+}
+
+/// Returns the versioned WebSocket API URL (`<root>/ws-api/v3`) for a root URL.
+fn get_ws_api_url(root: &Url) -> Result<Url, url::ParseError> {
+    return root.join(&format!("ws-api/{API_VERSION}"));
 }
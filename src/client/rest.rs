@@ -0,0 +1,452 @@
+use std::sync::{Arc, OnceLock};
+
+use reqwest::{Client, Method, Request};
+use serde::{Serialize, Serializer, de::DeserializeOwned};
+use url::Url;
+
+use crate::clock::ServerClock;
+use crate::errors::BinanceError;
+use crate::rate_limit::RateLimiter;
+use crate::request::{API_KEY_HEADER, parse_response};
+use crate::signer::{HmacSigner, Signer};
+use crate::types::general::BinanceErrorCode;
+
+use super::BinanceGeneralClient;
+
+/// How a request is authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Security {
+    /// Public endpoint.
+    None,
+    /// Requires the API key header only.
+    ApiKey,
+    /// Requires the API key header, a `timestamp` and a `signature`.
+    Signed,
+}
+
+/// Description of a REST endpoint: its method, path relative to `/api/v3/`,
+/// security type and request weight.
+#[derive(Debug, Clone)]
+pub struct Endpoint {
+    pub method: Method,
+    pub path: &'static str,
+    pub security: Security,
+    pub weight: u32,
+    /// Whether the endpoint counts towards the order rate limits.
+    pub is_order: bool,
+}
+
+impl Endpoint {
+    pub const fn public(method: Method, path: &'static str, weight: u32) -> Self {
+        return Self {
+            method,
+            path,
+            security: Security::None,
+            weight,
+            is_order: false,
+        };
+    }
+
+    pub const fn api_key(method: Method, path: &'static str, weight: u32) -> Self {
+        return Self {
+            method,
+            path,
+            security: Security::ApiKey,
+            weight,
+            is_order: false,
+        };
+    }
+
+    pub const fn signed(method: Method, path: &'static str, weight: u32) -> Self {
+        return Self {
+            method,
+            path,
+            security: Security::Signed,
+            weight,
+            is_order: false,
+        };
+    }
+
+    /// Marks the endpoint as placing orders.
+    pub const fn order(mut self) -> Self {
+        self.is_order = true;
+        return self;
+    }
+
+    /// Overrides the weight for endpoints whose weight depends on the parameters.
+    pub const fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        return self;
+    }
+}
+
+/// Ordered request parameters; optional values are skipped.
+#[derive(Debug, Default, Clone)]
+pub struct Params(Vec<(&'static str, String)>);
+
+impl Params {
+    pub const fn new() -> Self {
+        return Self(Vec::new());
+    }
+
+    #[must_use]
+    pub fn with(mut self, name: &'static str, value: &(impl ToString + ?Sized)) -> Self {
+        self.0.push((name, value.to_string()));
+        return self;
+    }
+
+    #[must_use]
+    pub fn with_opt(self, name: &'static str, value: Option<impl ToString>) -> Self {
+        return match value {
+            Some(value) => self.with(name, &value),
+            None => self,
+        };
+    }
+
+    /// Adds a list of symbols as the JSON array expected by `symbols` parameters.
+    /// An empty list is left out, which selects every symbol.
+    #[must_use]
+    pub fn with_symbols(self, symbols: &[&str]) -> Self {
+        if symbols.is_empty() {
+            return self;
+        }
+        return self.with_list("symbols", symbols);
+    }
+
+    /// Adds a list of values as a JSON array, e.g. for `permissions`.
+    #[must_use]
+    pub fn with_list(self, name: &'static str, values: &[impl AsRef<str>]) -> Self {
+        let values: Vec<&str> = values.iter().map(AsRef::as_ref).collect();
+        return self.with(name, &format!("[\"{}\"]", values.join("\",\"")));
+    }
+}
+
+impl Serialize for Params {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.collect_map(self.0.iter().map(|(name, value)| (name, value)));
+    }
+}
+
+/// A service of a client, such as its server clock, either shared with the
+/// client or built from its [`RestClient`] on first use, so that it follows
+/// the final configuration of the client. Clones share a derived service once
+/// it is built.
+#[derive(Debug, Clone)]
+pub enum Service<T> {
+    Shared(T),
+    Derived(Arc<OnceLock<T>>),
+}
+
+impl<T> Service<T> {
+    pub fn derived() -> Self {
+        return Self::Derived(Arc::default());
+    }
+
+    pub fn get_or_init(&self, init: impl FnOnce() -> T) -> &T {
+        return match self {
+            Self::Shared(service) => service,
+            Self::Derived(service) => service.get_or_init(init),
+        };
+    }
+
+    /// Forgets a derived service, so that it is built again from the current
+    /// configuration.
+    pub fn reset(&mut self) {
+        if let Self::Derived(_) = self {
+            *self = Self::derived();
+        }
+    }
+}
+
+/// Executes requests against the REST API: builds the query string, adds the
+/// API key header, timestamp and signature as required by the endpoint, applies
+/// the rate limiter and decodes the response or the Binance error payload.
+#[derive(Debug, Clone)]
+pub struct RestClient {
+    client: Client,
+    base_url: Url,
+    api_key: String,
+    signer: Arc<dyn Signer>,
+    recv_window: Option<u32>,
+    rate_limiter: RateLimiter,
+    /// Without a clock, signed requests use the local time.
+    clock: Option<Service<ServerClock>>,
+}
+
+impl RestClient {
+    pub fn new(client: Client, base_url: Url) -> Self {
+        return Self {
+            client,
+            base_url,
+            api_key: String::new(),
+            signer: Arc::new(HmacSigner::new(String::new())),
+            recv_window: None,
+            rate_limiter: RateLimiter::default(),
+            clock: None,
+        };
+    }
+
+    pub fn with_credentials(mut self, api_key: String, signer: Arc<dyn Signer>) -> Self {
+        self.api_key = api_key;
+        self.signer = signer;
+        return self;
+    }
+
+    pub const fn base_url(&self) -> &Url {
+        return &self.base_url;
+    }
+
+    /// A client for the public endpoints of the same API that shares the rate
+    /// limiter of this one, e.g. for the server clock.
+    pub fn general_client(&self) -> BinanceGeneralClient {
+        return BinanceGeneralClient::with_base_url(self.client.clone(), self.base_url.clone())
+            .with_rate_limiter(self.rate_limiter.clone());
+    }
+
+    pub fn api_key(&self) -> &str {
+        return &self.api_key;
+    }
+
+    pub const fn rate_limiter(&self) -> &RateLimiter {
+        return &self.rate_limiter;
+    }
+
+    pub fn clock(&self) -> Option<&ServerClock> {
+        return self
+            .clock
+            .as_ref()
+            .map(|clock| clock.get_or_init(|| ServerClock::new(self.general_client())));
+    }
+
+    pub fn set_signer(&mut self, signer: Arc<dyn Signer>) {
+        self.signer = signer;
+    }
+
+    pub const fn set_recv_window(&mut self, recv_window: Option<u32>) {
+        self.recv_window = recv_window;
+    }
+
+    /// Sets the rate limiter, which a server clock built by this client
+    /// follows as well.
+    pub fn set_rate_limiter(&mut self, rate_limiter: RateLimiter) {
+        self.rate_limiter = rate_limiter;
+        if let Some(clock) = &mut self.clock {
+            clock.reset();
+        }
+    }
+
+    /// Stamps signed requests with a server clock built on first use.
+    pub fn use_server_clock(&mut self) {
+        self.clock = Some(Service::derived());
+    }
+
+    pub fn set_clock(&mut self, clock: ServerClock) {
+        self.clock = Some(Service::Shared(clock));
+    }
+
+    /// Sends a request to a public or API key endpoint and decodes the response as `T`.
+    ///
+    /// Unlike [`Self::send`], this never touches the server clock, which itself
+    /// uses it to fetch the server time.
+    pub async fn send_public<T: DeserializeOwned>(
+        &self,
+        endpoint: &Endpoint,
+        params: &(impl Serialize + Sync),
+    ) -> Result<T, BinanceError> {
+        debug_assert!(endpoint.security != Security::Signed);
+        let query = serde_qs::to_string(params)?;
+        let request = self.request(endpoint, &query)?;
+        return self.execute(endpoint, request).await;
+    }
+
+    /// Sends a request to `endpoint` with `params` and decodes the response as `T`.
+    ///
+    /// Signed requests are stamped with the server clock. If Binance rejects
+    /// the timestamp (-1021), the clock is synced and the request sent once more.
+    pub async fn send<T: DeserializeOwned>(
+        &self,
+        endpoint: &Endpoint,
+        params: &(impl Serialize + Sync),
+    ) -> Result<T, BinanceError> {
+        if endpoint.security != Security::Signed {
+            return self.send_public(endpoint, params).await;
+        }
+
+        let mut query = serde_qs::to_string(params)?;
+        if let Some(recv_window) = self.recv_window
+            && !has_param(&query, "recvWindow")
+        {
+            append_param(&mut query, "recvWindow", &recv_window.to_string());
+        }
+
+        let Some(clock) = self.clock() else {
+            let timestamp = chrono::Utc::now().timestamp_millis();
+            let request = self.request(endpoint, &self.signed_query(&query, timestamp))?;
+            return self.execute(endpoint, request).await;
+        };
+
+        let timestamp = clock.timestamp().await;
+        let request = self.request(endpoint, &self.signed_query(&query, timestamp))?;
+        let result = self.execute(endpoint, request).await;
+        let Err(error) = &result else {
+            return result;
+        };
+        if error.error_code() != Some(BinanceErrorCode::InvalidTimestamp) {
+            return result;
+        }
+
+        log::warn!("Timestamp rejected by the server, syncing clock and retrying");
+        clock.sync().await?;
+        let request = self.request(endpoint, &self.signed_query(&query, clock.now()))?;
+        return self.execute(endpoint, request).await;
+    }
+
+    /// Appends the timestamp and the signature of the resulting query.
+    fn signed_query(&self, query: &str, timestamp: i64) -> String {
+        let mut query = query.to_string();
+        append_param(&mut query, "timestamp", &timestamp.to_string());
+        let signature = self.signer.sign(&query);
+        query.push_str("&signature=");
+        query.extend(url::form_urlencoded::byte_serialize(signature.as_bytes()));
+        return query;
+    }
+
+    fn request(&self, endpoint: &Endpoint, query: &str) -> Result<Request, BinanceError> {
+        let mut url = self.base_url.join(endpoint.path)?;
+        if !query.is_empty() {
+            url.set_query(Some(query));
+        }
+
+        let mut request = self.client.request(endpoint.method.clone(), url);
+        if endpoint.security != Security::None {
+            request = request.header(API_KEY_HEADER, &self.api_key);
+        }
+        return Ok(request.build()?);
+    }
+
+    async fn execute<T: DeserializeOwned>(
+        &self,
+        endpoint: &Endpoint,
+        request: Request,
+    ) -> Result<T, BinanceError> {
+        log::debug!("{} {}", endpoint.method, endpoint.path);
+        self.rate_limiter
+            .acquire(endpoint.weight, endpoint.is_order)
+            .await?;
+        let response = self.client.execute(request).await?;
+        self.rate_limiter.update(&response);
+
+        let result = parse_response(response).await;
+        if let Err(error) = &result {
+            log::warn!("{} {} failed: {error}", endpoint.method, endpoint.path);
+        }
+        return result;
+    }
+}
+
+fn has_param(query: &str, name: &str) -> bool {
+    return url::form_urlencoded::parse(query.as_bytes()).any(|(key, _)| key == name);
+}
+
+fn append_param(query: &mut String, name: &str, value: &str) {
+    if !query.is_empty() {
+        query.push('&');
+    }
+    query.push_str(name);
+    query.push('=');
+    query.push_str(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rest_client() -> RestClient {
+        return RestClient::new(
+            Client::new(),
+            Url::parse("https://api.binance.com/api/v3/").unwrap(),
+        )
+        .with_credentials(
+            "test_apikey".to_string(),
+            Arc::new(HmacSigner::new("test_secret".to_string())),
+        );
+    }
+
+    #[test]
+    fn test_signed_request() {
+        let client = rest_client();
+        let endpoint = Endpoint::signed(Method::GET, "order", 4);
+        let params = Params::new()
+            .with("symbol", "BTCUSDT")
+            .with("side", "BUY")
+            .with_opt("orderId", None::<u64>);
+        let query = serde_qs::to_string(&params).unwrap();
+        assert_eq!(query, "symbol=BTCUSDT&side=BUY");
+
+        let request = client
+            .request(&endpoint, &client.signed_query(&query, 1_499_827_319_559))
+            .unwrap();
+        assert_eq!(
+            request.headers().get(API_KEY_HEADER).unwrap(),
+            "test_apikey"
+        );
+        let payload = "symbol=BTCUSDT&side=BUY&timestamp=1499827319559";
+        assert_eq!(
+            request.url().query().unwrap(),
+            format!("{payload}&signature={}", client.signer.sign(payload))
+        );
+    }
+
+    #[test]
+    fn test_signed_request_with_empty_query() {
+        let client = rest_client();
+        let endpoint = Endpoint::signed(Method::GET, "account", 20);
+
+        let request = client
+            .request(&endpoint, &client.signed_query("", 1))
+            .unwrap();
+        assert!(
+            request
+                .url()
+                .query_pairs()
+                .any(|(k, v)| k == "signature" && v == client.signer.sign("timestamp=1"))
+        );
+    }
+
+    #[test]
+    fn test_api_key_request() {
+        let client = rest_client();
+        let endpoint = Endpoint::api_key(Method::POST, "userDataStream", 2);
+
+        let request = client.request(&endpoint, "").unwrap();
+        assert_eq!(
+            request.headers().get(API_KEY_HEADER).unwrap(),
+            "test_apikey"
+        );
+        assert!(request.url().query().is_none());
+    }
+
+    #[test]
+    fn test_public_request() {
+        let client = rest_client();
+        let endpoint = Endpoint::public(Method::GET, "ping", 1);
+
+        let request = client.request(&endpoint, "").unwrap();
+        assert!(request.headers().get(API_KEY_HEADER).is_none());
+        assert_eq!(
+            request.url().as_str(),
+            "https://api.binance.com/api/v3/ping"
+        );
+    }
+
+    #[test]
+    fn test_recv_window_param() {
+        assert!(has_param("symbol=BTCUSDT&recvWindow=5000", "recvWindow"));
+        assert!(!has_param("symbol=BTCUSDT", "recvWindow"));
+
+        let mut query = String::new();
+        append_param(&mut query, "recvWindow", "5000");
+        append_param(&mut query, "timestamp", "1");
+        assert_eq!(query, "recvWindow=5000&timestamp=1");
+    }
+}
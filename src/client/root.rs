@@ -1,10 +1,18 @@
-use reqwest::Client;
-
+use crate::clock::ServerClock;
+use crate::errors::BinanceError;
+use crate::kline_feed::KlineFeed;
+use crate::order_book::OrderBookManager;
+use crate::portfolio::PortfolioValuator;
+use crate::symbol_registry::SymbolRegistry;
+use crate::types::events::UpdateSpeed;
+use crate::types::market::KlineInterval;
+use crate::user_stream::UserDataStream;
 use crate::websocket::BinanceWebSocket;
+use crate::ws_api::BinanceWsApiClient;
 
 use super::{
-    account::BinanceAccountClient, general::BinanceGeneralClient, market::BinanceMarketClient,
-    trading::BinanceTradingClient,
+    account::BinanceAccountClient, builder::BinanceClientBuilder, general::BinanceGeneralClient,
+    market::BinanceMarketClient, trading::BinanceTradingClient,
 };
 
 #[derive(Debug, Clone)]
@@ -14,25 +22,83 @@ pub struct BinanceClient {
     pub trading: BinanceTradingClient,
     pub account: BinanceAccountClient,
     pub websocket: BinanceWebSocket,
+    /// Trading over the WebSocket API, connected on first use.
+    pub ws_api: BinanceWsApiClient,
+    /// The server clock used to stamp the signed requests of all sub-clients.
+    pub clock: ServerClock,
+    /// The exchange information of every symbol, fetched on first use and
+    /// shared with the trading client.
+    pub symbols: SymbolRegistry,
 }
 
 impl BinanceClient {
+    /// Creates a client for the default mainnet or testnet endpoints.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be initialized, like [`Client::new`].
     #[must_use]
     pub fn new(api_key: String, secret: String, testnet: bool) -> Self {
-        let client = Client::new();
-        return Self {
-            general: BinanceGeneralClient::new(client.clone(), testnet),
-            market: BinanceMarketClient::new(client.clone(), testnet),
-            trading: BinanceTradingClient::new(
-                client.clone(),
-                api_key.clone(),
-                secret.clone(),
-                None,
-                testnet,
-            ),
-            account: BinanceAccountClient::new(client, api_key, secret, testnet),
-            websocket: BinanceWebSocket::new(testnet),
-        };
+        return BinanceClientBuilder::new(api_key, secret)
+            .testnet(testnet)
+            .build()
+            .expect("the default endpoints are valid URLs");
+    }
+
+    /// Returns a user data stream using the account client and stream URL of this client.
+    #[must_use]
+    pub fn user_data_stream(&self) -> UserDataStream {
+        return UserDataStream::new(self.account.clone(), self.websocket.base_url().clone());
+    }
+
+    /// Starts maintaining the order book of `symbol` with the market client and
+    /// streams of this client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn order_book(
+        &self,
+        symbol: &str,
+        speed: UpdateSpeed,
+    ) -> Result<OrderBookManager, BinanceError> {
+        return OrderBookManager::start(self.market.clone(), &self.websocket, symbol, speed);
+    }
+
+    /// Starts following the closed klines of `symbol` and `interval` with the
+    /// market client and streams of this client, yielding the `backfill`
+    /// latest ones first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn kline_feed(
+        &self,
+        symbol: &str,
+        interval: KlineInterval,
+        backfill: usize,
+    ) -> Result<KlineFeed, BinanceError> {
+        return KlineFeed::start(
+            self.market.clone(),
+            self.clock.clone(),
+            &self.websocket,
+            symbol,
+            interval,
+            backfill,
+        );
+    }
+
+    /// Returns a portfolio valuator using the market client and symbol
+    /// registry of this client.
+    #[must_use]
+    pub fn portfolio_valuator(&self) -> PortfolioValuator {
+        return PortfolioValuator::new(self.market.clone(), self.symbols.clone());
+    }
+
+    /// Returns a builder for a client with custom endpoints, HTTP client or timeouts.
+    #[must_use]
+    pub fn builder(api_key: String, secret: String) -> BinanceClientBuilder {
+        return BinanceClientBuilder::new(api_key, secret);
     }
 }
 
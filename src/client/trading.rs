@@ -1,23 +1,58 @@
-use std::collections::HashMap;
+use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
 use reqwest::{Client, Method};
+use rust_decimal::Decimal;
+use serde::Serialize;
 use url::Url;
 
+use crate::clock::ServerClock;
 use crate::errors::BinanceError;
-use crate::request::{make_request, sign_request};
-use crate::types::general::Error;
-use crate::types::trading::{Order, OrderCreationData};
+use crate::rate_limit::RateLimiter;
+use crate::signer::{HmacSigner, Signer};
+use crate::symbol_registry::SymbolRegistry;
+use crate::types::general::SymbolInfo;
+use crate::types::trading::{
+    AmendedOrderResult, CancelReplaceOrderData, CancelReplaceResult, OcoOrderCreateData, Order,
+    OrderCommissionRates, OrderCreationData, OrderList, OtoOrderCreateData, OtocoOrderCreateData,
+};
+use crate::validation::{FilterViolation, OrderValidator};
 
 use super::get_base_url;
+use super::rest::{Endpoint, Params, RestClient, Service};
+
+const CREATE_ORDER: Endpoint = Endpoint::signed(Method::POST, "order", 1).order();
+const TEST_ORDER: Endpoint = Endpoint::signed(Method::POST, "order/test", 1);
+const GET_ORDER: Endpoint = Endpoint::signed(Method::GET, "order", 4);
+const CANCEL_ORDER: Endpoint = Endpoint::signed(Method::DELETE, "order", 1);
+const CANCEL_REPLACE: Endpoint = Endpoint::signed(Method::POST, "order/cancelReplace", 1).order();
+const AMEND_KEEP_PRIORITY: Endpoint = Endpoint::signed(Method::PUT, "order/amend/keepPriority", 4);
+const OPEN_ORDERS: Endpoint = Endpoint::signed(Method::GET, "openOrders", 6);
+const CANCEL_OPEN_ORDERS: Endpoint = Endpoint::signed(Method::DELETE, "openOrders", 1);
+const ALL_ORDERS: Endpoint = Endpoint::signed(Method::GET, "allOrders", 20);
+const CREATE_OCO: Endpoint = Endpoint::signed(Method::POST, "orderList/oco", 1).order();
+const CREATE_OTO: Endpoint = Endpoint::signed(Method::POST, "orderList/oto", 1).order();
+const CREATE_OTOCO: Endpoint = Endpoint::signed(Method::POST, "orderList/otoco", 1).order();
+const GET_ORDER_LIST: Endpoint = Endpoint::signed(Method::GET, "orderList", 4);
+const CANCEL_ORDER_LIST: Endpoint = Endpoint::signed(Method::DELETE, "orderList", 1);
+const ALL_ORDER_LISTS: Endpoint = Endpoint::signed(Method::GET, "allOrderList", 20);
+const OPEN_ORDER_LISTS: Endpoint = Endpoint::signed(Method::GET, "openOrderList", 6);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestOrderParams<'a> {
+    #[serde(flatten)]
+    order: &'a OrderCreationData,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    compute_commission_rates: bool,
+}
 
 #[derive(Debug, Clone)]
 pub struct BinanceTradingClient {
-    client: Client,
-    api_key: String,
-    secret: String,
-    base_url: Url,
-    recv_window: Option<u32>,
+    rest: RestClient,
+    /// The symbols orders are validated against.
+    symbols: Service<SymbolRegistry>,
+    validate_orders: bool,
 }
 
 impl BinanceTradingClient {
@@ -28,34 +63,135 @@ impl BinanceTradingClient {
         recv_window: Option<u32>,
         testnet: bool,
     ) -> Self {
+        return Self::with_base_url(client, api_key, secret, recv_window, get_base_url(testnet));
+    }
+
+    /// Creates a client that sends requests to `base_url`.
+    ///
+    /// `recv_window` is used for requests that do not specify their own.
+    pub fn with_base_url(
+        client: Client,
+        api_key: String,
+        secret: String,
+        recv_window: Option<u32>,
+        base_url: Url,
+    ) -> Self {
+        let mut rest = RestClient::new(client, base_url)
+            .with_credentials(api_key, Arc::new(HmacSigner::new(secret)));
+        rest.set_recv_window(recv_window);
+        rest.use_server_clock();
         return Self {
-            client,
-            api_key,
-            secret,
-            base_url: get_base_url(testnet),
-            recv_window,
+            rest,
+            symbols: Service::derived(),
+            validate_orders: false,
         };
     }
 
-    pub async fn create_order(&self, order_data: OrderCreationData) -> Result<Order, BinanceError> {
-        let url = self.base_url.join("order")?;
-        let data = &order_data;
-        let method = Method::POST;
+    /// Shares `rate_limiter` with this client instead of its own one.
+    ///
+    /// The server time and exchange information requests of its own clock
+    /// and symbol registry are counted by `rate_limiter` as well.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rest.set_rate_limiter(rate_limiter);
+        self.symbols.reset();
+        return self;
+    }
+
+    pub const fn rate_limiter(&self) -> &RateLimiter {
+        return self.rest.rate_limiter();
+    }
+
+    /// Signs requests with `signer` (e.g. an Ed25519 or RSA key) instead of
+    /// the HMAC secret passed to the constructor.
+    #[must_use]
+    pub fn with_signer(mut self, signer: Arc<dyn Signer>) -> Self {
+        self.rest.set_signer(signer);
+        return self;
+    }
 
-        let request = make_request(&self.client, method, &url, data)?;
-        let signed_request = sign_request(request, &self.api_key, &self.secret).unwrap();
+    /// Shares `clock` with this client instead of its own one.
+    #[must_use]
+    pub fn with_clock(mut self, clock: ServerClock) -> Self {
+        self.rest.set_clock(clock);
+        return self;
+    }
+
+    /// The server clock used to stamp signed requests.
+    pub fn clock(&self) -> Option<&ServerClock> {
+        return self.rest.clock();
+    }
+
+    pub const fn base_url(&self) -> &Url {
+        return self.rest.base_url();
+    }
+
+    pub fn api_key(&self) -> &str {
+        return self.rest.api_key();
+    }
 
-        let response = self.client.execute(signed_request).await?;
+    /// Checks orders against the filters of their symbol before
+    /// [`create_order`](Self::create_order) sends them, failing with
+    /// [`BinanceError::InvalidOrder`] instead of a -1013 error.
+    ///
+    /// The symbols are taken from the [`symbol_registry`](Self::symbol_registry),
+    /// which fetches them with the first order. Filters relative to the
+    /// average price are not checked.
+    #[must_use]
+    pub const fn with_order_validation(mut self, enabled: bool) -> Self {
+        self.validate_orders = enabled;
+        return self;
+    }
+
+    /// Shares `symbols` with this client instead of its own registry.
+    #[must_use]
+    pub fn with_symbol_registry(mut self, symbols: SymbolRegistry) -> Self {
+        self.symbols = Service::Shared(symbols);
+        return self;
+    }
 
-        if !response.status().is_success() {
-            let status_code: u16 = response.status().as_u16();
-            let error: Error = response.json().await?;
-            return Err(BinanceError::Api(status_code, error));
+    /// The symbols orders are validated against.
+    pub fn symbol_registry(&self) -> &SymbolRegistry {
+        return self
+            .symbols
+            .get_or_init(|| SymbolRegistry::new(self.rest.general_client()));
+    }
+
+    pub async fn create_order(&self, order_data: OrderCreationData) -> Result<Order, BinanceError> {
+        if self.validate_orders {
+            let symbol = &order_data.common().symbol;
+            let symbol_info = self
+                .symbol_registry()
+                .symbol(symbol)
+                .await?
+                .ok_or_else(|| FilterViolation::UnknownSymbol(symbol.clone()))?;
+            OrderValidator::new(&symbol_info).validate(&order_data)?;
         }
+        return self.rest.send(&CREATE_ORDER, &order_data).await;
+    }
 
-        let order: Order = response.json().await?;
+    /// Validates a new order without sending it to the matching engine.
+    ///
+    /// With `compute_commission_rates`, the commission rates that would apply
+    /// to the order are returned.
+    pub async fn test_order(
+        &self,
+        order_data: OrderCreationData,
+        compute_commission_rates: bool,
+    ) -> Result<Option<OrderCommissionRates>, BinanceError> {
+        let params = TestOrderParams {
+            order: &order_data,
+            compute_commission_rates,
+        };
+        if !compute_commission_rates {
+            self.rest
+                .send::<serde_json::Value>(&TEST_ORDER, &params)
+                .await?;
+            return Ok(None);
+        }
 
-        Ok(order)
+        let endpoint = TEST_ORDER.with_weight(20);
+        return Ok(Some(self.rest.send(&endpoint, &params).await?));
     }
 
     pub async fn get_order(
@@ -65,45 +201,18 @@ impl BinanceTradingClient {
         orig_client_order_id: Option<&str>,
         recv_window: Option<u32>,
     ) -> Result<Order, BinanceError> {
-        let url = self.base_url.join("order")?;
-        let method = Method::GET;
-        let timestamp = chrono::Utc::now().timestamp_millis().to_string();
-
-        let mut params = HashMap::new();
-
-        params.insert("symbol", symbol.to_string());
-        params.insert("timestamp", timestamp);
-
         if order_id.is_none() && orig_client_order_id.is_none() {
             return Err(BinanceError::MissingParameter(
                 "Either orderId or origClientOrderId must be provided".to_string(),
             ));
         }
 
-        if let Some(order_id) = order_id {
-            params.insert("orderId", order_id.to_string());
-        }
-        if let Some(orig_client_order_id) = orig_client_order_id {
-            params.insert("origClientOrderId", orig_client_order_id.to_string());
-        }
-        if let Some(recv_window) = recv_window {
-            params.insert("recvWindow", recv_window.to_string());
-        }
-
-        let request = make_request(&self.client, method, &url, &params)?;
-        let signed_request = sign_request(request, &self.api_key, &self.secret).unwrap();
-
-        let response = self.client.execute(signed_request).await?;
-
-        if !response.status().is_success() {
-            let status_code: u16 = response.status().as_u16();
-            let error: Error = response.json().await?;
-            return Err(BinanceError::Api(status_code, error));
-        }
-
-        let order: Order = response.json().await?;
-
-        Ok(order)
+        let params = Params::new()
+            .with("symbol", symbol)
+            .with_opt("orderId", order_id)
+            .with_opt("origClientOrderId", orig_client_order_id)
+            .with_opt("recvWindow", recv_window);
+        return self.rest.send(&GET_ORDER, &params).await;
     }
 
     pub async fn get_open_orders(
@@ -111,34 +220,16 @@ impl BinanceTradingClient {
         symbol: Option<&str>,
         recv_window: Option<u32>,
     ) -> Result<Vec<Order>, BinanceError> {
-        let url = self.base_url.join("openOrders")?;
-        let method = Method::GET;
-        let timestamp = chrono::Utc::now().timestamp_millis().to_string();
-
-        let mut params = HashMap::new();
-        params.insert("timestamp", timestamp);
-
-        if let Some(symbol) = symbol {
-            params.insert("symbol", symbol.to_string());
-        }
-        if let Some(recv_window) = recv_window {
-            params.insert("recvWindow", recv_window.to_string());
-        }
-
-        let request = make_request(&self.client, method, &url, &params)?;
-        let signed_request = sign_request(request, &self.api_key, &self.secret).unwrap();
-
-        let response = self.client.execute(signed_request).await?;
-
-        if !response.status().is_success() {
-            let status_code: u16 = response.status().as_u16();
-            let error: Error = response.json().await?;
-            return Err(BinanceError::Api(status_code, error));
-        }
-
-        let orders: Vec<Order> = response.json().await?;
-
-        Ok(orders)
+        // Querying the open orders of all symbols is much heavier.
+        let endpoint = if symbol.is_some() {
+            OPEN_ORDERS
+        } else {
+            OPEN_ORDERS.with_weight(80)
+        };
+        let params = Params::new()
+            .with_opt("symbol", symbol)
+            .with_opt("recvWindow", recv_window);
+        return self.rest.send(&endpoint, &params).await;
     }
 
     pub async fn get_orders(
@@ -149,44 +240,13 @@ impl BinanceTradingClient {
         end_time: Option<DateTime<Utc>>,
         limit: Option<u32>,
     ) -> Result<Vec<Order>, BinanceError> {
-        let url = self.base_url.join("allOrders")?;
-        let method = Method::GET;
-        let timestamp = chrono::Utc::now().timestamp_millis().to_string();
-
-        let mut params = HashMap::new();
-        params.insert("symbol", symbol.to_string());
-        params.insert("timestamp", timestamp);
-
-        if let Some(order_id) = order_id {
-            params.insert("orderId", order_id.to_string());
-        }
-        if let Some(start_time) = start_time {
-            params.insert("startTime", start_time.timestamp_millis().to_string());
-        }
-        if let Some(end_time) = end_time {
-            params.insert("endTime", end_time.timestamp_millis().to_string());
-        }
-        if let Some(limit) = limit {
-            params.insert("limit", limit.to_string());
-        }
-        if let Some(recv_window) = self.recv_window {
-            params.insert("recvWindow", recv_window.to_string());
-        }
-
-        let request = make_request(&self.client, method, &url, &params)?;
-        let signed_request = sign_request(request, &self.api_key, &self.secret).unwrap();
-
-        let response = self.client.execute(signed_request).await?;
-
-        if !response.status().is_success() {
-            let status_code: u16 = response.status().as_u16();
-            let error: Error = response.json().await?;
-            return Err(BinanceError::Api(status_code, error));
-        }
-
-        let orders: Vec<Order> = response.json().await?;
-
-        Ok(orders)
+        let params = Params::new()
+            .with("symbol", symbol)
+            .with_opt("orderId", order_id)
+            .with_opt("startTime", start_time.map(|t| t.timestamp_millis()))
+            .with_opt("endTime", end_time.map(|t| t.timestamp_millis()))
+            .with_opt("limit", limit);
+        return self.rest.send(&ALL_ORDERS, &params).await;
     }
 
     pub async fn cancel_order(
@@ -198,50 +258,77 @@ impl BinanceTradingClient {
         cancel_restriction: Option<&str>,
         recv_window: Option<u32>,
     ) -> Result<Order, BinanceError> {
-        let url = self.base_url.join("order")?;
-        let method = Method::DELETE;
-        let timestamp = chrono::Utc::now().timestamp_millis().to_string();
-
-        let mut params = HashMap::new();
-        params.insert("symbol", symbol.to_string());
-        params.insert("timestamp", timestamp);
-
         if order_id.is_none() && orig_client_order_id.is_none() {
             return Err(BinanceError::MissingParameter(
                 "Either orderId or origClientOrderId must be provided".to_string(),
             ));
         }
 
-        if let Some(order_id) = order_id {
-            params.insert("orderId", order_id.to_string());
-        }
-        if let Some(orig_client_order_id) = orig_client_order_id {
-            params.insert("origClientOrderId", orig_client_order_id.to_string());
-        }
-        if let Some(new_client_order_id) = new_client_order_id {
-            params.insert("newClientOrderId", new_client_order_id.to_string());
-        }
-        if let Some(cancel_restrictions) = cancel_restriction {
-            params.insert("cancelRestrictions", cancel_restrictions.to_string());
-        }
-        if let Some(recv_window) = recv_window {
-            params.insert("recvWindow", recv_window.to_string());
-        }
+        let params = Params::new()
+            .with("symbol", symbol)
+            .with_opt("orderId", order_id)
+            .with_opt("origClientOrderId", orig_client_order_id)
+            .with_opt("newClientOrderId", new_client_order_id)
+            .with_opt("cancelRestrictions", cancel_restriction)
+            .with_opt("recvWindow", recv_window);
+        return self.rest.send(&CANCEL_ORDER, &params).await;
+    }
 
-        let request = make_request(&self.client, method, &url, &params)?;
-        let signed_request = sign_request(request, &self.api_key, &self.secret).unwrap();
+    /// Cancels an order and places a new one on the same symbol in a single
+    /// request.
+    ///
+    /// When Binance reports the cancel and new order results of a failed
+    /// request (-2021 and -2022), they are returned as a [`CancelReplaceResult`]
+    /// instead of an error, so the caller can see which of the two succeeded.
+    pub async fn cancel_replace_order(
+        &self,
+        order_data: CancelReplaceOrderData,
+    ) -> Result<CancelReplaceResult, BinanceError> {
+        if order_data.cancel_order_id.is_none() && order_data.cancel_orig_client_order_id.is_none()
+        {
+            return Err(BinanceError::MissingParameter(
+                "Either cancelOrderId or cancelOrigClientOrderId must be provided".to_string(),
+            ));
+        }
 
-        let response = self.client.execute(signed_request).await?;
+        let result = self.rest.send(&CANCEL_REPLACE, &order_data).await;
+        return CancelReplaceResult::from_result(result);
+    }
 
-        if !response.status().is_success() {
-            let status_code: u16 = response.status().as_u16();
-            let error: Error = response.json().await?;
-            return Err(BinanceError::Api(status_code, error));
+    /// Reduces the quantity of an open order without losing its place in
+    /// the order book.
+    ///
+    /// Fails with [`BinanceError::NotAllowed`] unless `symbol_info` allows
+    /// amending orders.
+    pub async fn amend_order_keep_priority(
+        &self,
+        symbol_info: &SymbolInfo,
+        order_id: Option<u64>,
+        orig_client_order_id: Option<&str>,
+        new_client_order_id: Option<&str>,
+        new_qty: Decimal,
+        recv_window: Option<u32>,
+    ) -> Result<AmendedOrderResult, BinanceError> {
+        if symbol_info.allow_amend != Some(true) {
+            return Err(BinanceError::NotAllowed(format!(
+                "{} does not allow amending orders",
+                symbol_info.symbol
+            )));
+        }
+        if order_id.is_none() && orig_client_order_id.is_none() {
+            return Err(BinanceError::MissingParameter(
+                "Either orderId or origClientOrderId must be provided".to_string(),
+            ));
         }
 
-        let order: Order = response.json().await?;
-
-        Ok(order)
+        let params = Params::new()
+            .with("symbol", &symbol_info.symbol)
+            .with_opt("orderId", order_id)
+            .with_opt("origClientOrderId", orig_client_order_id)
+            .with_opt("newClientOrderId", new_client_order_id)
+            .with("newQty", &new_qty)
+            .with_opt("recvWindow", recv_window);
+        return self.rest.send(&AMEND_KEEP_PRIORITY, &params).await;
     }
 
     pub async fn cancel_open_orders(
@@ -249,46 +336,133 @@ impl BinanceTradingClient {
         symbol: &str,
         recv_window: Option<u32>,
     ) -> Result<Vec<Order>, BinanceError> {
-        let url = self.base_url.join("openOrders")?;
-        let method = Method::DELETE;
-        let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+        let params = Params::new()
+            .with("symbol", symbol)
+            .with_opt("recvWindow", recv_window);
+        return self.rest.send(&CANCEL_OPEN_ORDERS, &params).await;
+    }
+
+    /// Places an OCO order list: two orders of which the second is canceled
+    /// as soon as the first one is filled.
+    pub async fn create_oco_order(
+        &self,
+        order_data: OcoOrderCreateData,
+    ) -> Result<OrderList, BinanceError> {
+        return self.rest.send(&CREATE_OCO, &order_data).await;
+    }
+
+    /// Places an OTO order list: a working order that places the pending
+    /// order once it is filled.
+    pub async fn create_oto_order(
+        &self,
+        order_data: OtoOrderCreateData,
+    ) -> Result<OrderList, BinanceError> {
+        return self.rest.send(&CREATE_OTO, &order_data).await;
+    }
 
-        let mut params = HashMap::new();
-        params.insert("symbol", symbol.to_string());
-        params.insert("timestamp", timestamp);
+    /// Places an OTOCO order list: a working order that places the pending
+    /// OCO pair once it is filled.
+    pub async fn create_otoco_order(
+        &self,
+        order_data: OtocoOrderCreateData,
+    ) -> Result<OrderList, BinanceError> {
+        return self.rest.send(&CREATE_OTOCO, &order_data).await;
+    }
 
-        if let Some(recv_window) = recv_window {
-            params.insert("recvWindow", recv_window.to_string());
+    pub async fn get_order_list(
+        &self,
+        order_list_id: Option<i64>,
+        orig_client_order_id: Option<&str>,
+        recv_window: Option<u32>,
+    ) -> Result<OrderList, BinanceError> {
+        if order_list_id.is_none() && orig_client_order_id.is_none() {
+            return Err(BinanceError::MissingParameter(
+                "Either orderListId or origClientOrderId must be provided".to_string(),
+            ));
         }
 
-        let request = make_request(&self.client, method, &url, &params)?;
-        let signed_request = sign_request(request, &self.api_key, &self.secret).unwrap();
+        let params = Params::new()
+            .with_opt("orderListId", order_list_id)
+            .with_opt("origClientOrderId", orig_client_order_id)
+            .with_opt("recvWindow", recv_window);
+        return self.rest.send(&GET_ORDER_LIST, &params).await;
+    }
 
-        let response = self.client.execute(signed_request).await?;
+    /// Returns the order lists with an id of at least `from_id`, or those
+    /// created between `start_time` and `end_time`.
+    pub async fn get_all_order_lists(
+        &self,
+        from_id: Option<i64>,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+        limit: Option<u32>,
+    ) -> Result<Vec<OrderList>, BinanceError> {
+        let params = Params::new()
+            .with_opt("fromId", from_id)
+            .with_opt("startTime", start_time.map(|t| t.timestamp_millis()))
+            .with_opt("endTime", end_time.map(|t| t.timestamp_millis()))
+            .with_opt("limit", limit);
+        return self.rest.send(&ALL_ORDER_LISTS, &params).await;
+    }
 
-        if !response.status().is_success() {
-            let status_code: u16 = response.status().as_u16();
-            let error: Error = response.json().await?;
-            return Err(BinanceError::Api(status_code, error));
-        }
+    pub async fn get_open_order_lists(
+        &self,
+        recv_window: Option<u32>,
+    ) -> Result<Vec<OrderList>, BinanceError> {
+        let params = Params::new().with_opt("recvWindow", recv_window);
+        return self.rest.send(&OPEN_ORDER_LISTS, &params).await;
+    }
 
-        let orders: Vec<Order> = response.json().await?;
+    /// Cancels every order of an order list.
+    pub async fn cancel_order_list(
+        &self,
+        symbol: &str,
+        order_list_id: Option<i64>,
+        list_client_order_id: Option<&str>,
+        new_client_order_id: Option<&str>,
+        recv_window: Option<u32>,
+    ) -> Result<OrderList, BinanceError> {
+        if order_list_id.is_none() && list_client_order_id.is_none() {
+            return Err(BinanceError::MissingParameter(
+                "Either orderListId or listClientOrderId must be provided".to_string(),
+            ));
+        }
 
-        Ok(orders)
+        let params = Params::new()
+            .with("symbol", symbol)
+            .with_opt("orderListId", order_list_id)
+            .with_opt("listClientOrderId", list_client_order_id)
+            .with_opt("newClientOrderId", new_client_order_id)
+            .with_opt("recvWindow", recv_window);
+        return self.rest.send(&CANCEL_ORDER_LIST, &params).await;
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::env;
-
-    use rust_decimal::Decimal;
     use serial_test::serial;
 
-    use crate::types::trading::{CommonOrderCreateData, OrderSide, OrderStatus, TimeInForce};
+    use crate::client::BinanceGeneralClient;
+    use crate::mock::MockServer;
+    use crate::types::general::RateLimitType;
+    use crate::types::trading::{
+        CancelReplaceMode, CancelReplaceResponse, CancelReplaceStatus, CommonOrderCreateData,
+        ContingencyType, ListOrderStatus, OcoLeg, OrderListLeg, OrderSide, OrderStatus, OrderType,
+        TimeInForce,
+    };
 
     use super::*;
 
+    fn mock_client(server: &MockServer) -> BinanceTradingClient {
+        return BinanceTradingClient::with_base_url(
+            Client::new(),
+            server.api_key().to_string(),
+            server.secret().to_string(),
+            None,
+            server.api_url(),
+        );
+    }
+
     #[test]
     fn test_new_client() {
         let api_key = "test_api_key";
@@ -301,10 +475,9 @@ mod tests {
             None,
             true,
         );
-        assert_eq!(client.api_key, api_key);
-        assert_eq!(client.secret, secret);
+        assert_eq!(client.api_key(), api_key);
         assert_eq!(
-            client.base_url.as_str(),
+            client.base_url().as_str(),
             "https://testnet.binance.vision/api/v3/"
         );
 
@@ -315,21 +488,21 @@ mod tests {
             None,
             false,
         );
-        assert_eq!(client.api_key, api_key);
-        assert_eq!(client.secret, secret);
-        assert_eq!(client.base_url.as_str(), "https://api.binance.com/api/v3/");
+        assert_eq!(client.api_key(), api_key);
+        assert_eq!(
+            client.base_url().as_str(),
+            "https://api.binance.com/api/v3/"
+        );
     }
 
     #[tokio::test]
     async fn test_create_order() {
-        let api_key = env::var("BINANCE_TEST_API_KEY").unwrap();
-        let secret = env::var("BINANCE_TEST_SECRET").unwrap();
+        let server = MockServer::start().await;
 
         let order_data = OrderCreationData::Limit {
             common: CommonOrderCreateData {
                 symbol: "BTCUSDT".to_string(),
                 side: OrderSide::Buy,
-                timestamp: chrono::Utc::now().timestamp_millis().try_into().unwrap(),
                 ..Default::default()
             },
             time_in_force: TimeInForce::Fok,
@@ -338,14 +511,12 @@ mod tests {
             iceberg_qty: None,
         };
 
-        let client = BinanceTradingClient::new(
-            Client::new(),
-            api_key.to_string(),
-            secret.to_string(),
-            None,
-            true,
-        );
-        let result = client.create_order(order_data).await.unwrap();
+        // Request futures can be spawned onto the runtime.
+        let client = mock_client(&server);
+        let result = tokio::spawn(async move { client.create_order(order_data).await })
+            .await
+            .unwrap()
+            .unwrap();
         assert_eq!(result.symbol, "BTCUSDT");
         assert_eq!(result.price, Decimal::from_str_exact("80000").unwrap());
         assert_eq!(result.time_in_force, TimeInForce::Fok);
@@ -353,24 +524,123 @@ mod tests {
         assert_eq!(result.status, OrderStatus::Expired);
     }
 
+    #[tokio::test]
+    async fn test_create_order_validation() {
+        let server = MockServer::start().await;
+        let client = mock_client(&server).with_order_validation(true);
+
+        // 80000 is not a multiple of the tick size of the mock.
+        let result = client.create_order(limit_order(80_000)).await;
+        assert!(matches!(
+            result,
+            Err(BinanceError::InvalidOrder(
+                FilterViolation::PriceNotOnTick { .. }
+            ))
+        ));
+        let order = client.create_order(limit_order(80_002)).await.unwrap();
+        assert_eq!(order.price, Decimal::from(80_002));
+
+        let mut order = limit_order(80_002);
+        order.common_mut().symbol = "XRPUSDT".to_string();
+        let result = client.create_order(order).await;
+        assert!(matches!(
+            result,
+            Err(BinanceError::InvalidOrder(FilterViolation::UnknownSymbol(s))) if s == "XRPUSDT"
+        ));
+
+        let requests = server.requests();
+        let count = |path: &str| requests.iter().filter(|r| r.path == path).count();
+        assert_eq!(count("exchangeInfo"), 1);
+        assert_eq!(count("order"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_services_share_rate_limiter() {
+        let server = MockServer::start().await;
+        let rate_limiter = RateLimiter::default();
+        let client = mock_client(&server)
+            .with_rate_limiter(rate_limiter.clone())
+            .with_order_validation(true);
+
+        client.create_order(limit_order(80_002)).await.unwrap();
+        // The server time and exchange information requests are counted too.
+        let paths: Vec<_> = server.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, ["exchangeInfo", "time", "order"]);
+        let raw_requests = rate_limiter
+            .usage()
+            .into_iter()
+            .find(|usage| usage.rate_limit_type == RateLimitType::RawRequests)
+            .unwrap();
+        assert_eq!(raw_requests.used, 3);
+
+        // A shared clock is kept whichever order the builder methods are
+        // called in.
+        let clock = client.clock().unwrap().clone();
+        let client = mock_client(&server)
+            .with_clock(clock.clone())
+            .with_rate_limiter(RateLimiter::default());
+        client.get_open_orders(None, None).await.unwrap();
+        let syncs = server
+            .requests()
+            .iter()
+            .filter(|r| r.path == "time")
+            .count();
+        assert_eq!(syncs, 1);
+    }
+
+    #[tokio::test]
+    async fn test_test_order() {
+        let server = MockServer::start().await;
+        let client = mock_client(&server);
+        let order_data = || OrderCreationData::Market {
+            common: CommonOrderCreateData {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Sell,
+                ..Default::default()
+            },
+            quantity: Some(Decimal::from_str_exact("0.01").unwrap()),
+            quote_order_qty: None,
+        };
+
+        let result = client.test_order(order_data(), false).await.unwrap();
+        assert!(result.is_none());
+
+        let rates = client
+            .test_order(order_data(), true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rates.standard_commission_for_order.taker,
+            Decimal::from_str_exact("0.001").unwrap()
+        );
+        assert_eq!(rates.discount.discount_asset, "BNB");
+
+        let requests = server.requests();
+        let request = requests.last().unwrap();
+        assert_eq!(request.path, "order/test");
+        assert_eq!(
+            request.param("computeCommissionRates").as_deref(),
+            Some("true")
+        );
+        assert_eq!(request.param("type").as_deref(), Some("MARKET"));
+        assert!(server.orders().is_empty());
+    }
+
     #[tokio::test]
     async fn test_get_open_orders() {
-        let api_key = env::var("BINANCE_TEST_API_KEY").unwrap();
-        let secret = env::var("BINANCE_TEST_SECRET").unwrap();
+        let server = MockServer::start().await;
 
-        let client =
-            BinanceTradingClient::new(Client::new(), api_key.clone(), secret.clone(), None, true);
+        let client = mock_client(&server);
 
         client.get_open_orders(None, None).await.unwrap();
     }
 
     #[tokio::test]
     async fn test_get_orders() {
-        let api_key = env::var("BINANCE_TEST_API_KEY").unwrap();
-        let secret = env::var("BINANCE_TEST_SECRET").unwrap();
+        let server = MockServer::start().await;
 
-        let client =
-            BinanceTradingClient::new(Client::new(), api_key.clone(), secret.clone(), None, true);
+        let client = mock_client(&server);
 
         client
             .get_orders("BTCUSDT", None, None, None, None)
@@ -381,16 +651,14 @@ mod tests {
     #[tokio::test]
     #[serial]
     async fn test_order_pipeline() {
-        let api_key = env::var("BINANCE_TEST_API_KEY").unwrap();
-        let secret = env::var("BINANCE_TEST_SECRET").unwrap();
+        let server = MockServer::start().await;
 
-        let client = BinanceTradingClient::new(Client::new(), api_key, secret, None, true);
+        let client = mock_client(&server);
 
         let order_data = OrderCreationData::Limit {
             common: CommonOrderCreateData {
                 symbol: "BTCUSDT".to_string(),
                 side: OrderSide::Buy,
-                timestamp: chrono::Utc::now().timestamp_millis().try_into().unwrap(),
                 ..Default::default()
             },
             time_in_force: TimeInForce::Gtc,
@@ -403,6 +671,7 @@ mod tests {
         assert_eq!(created_order.symbol, "BTCUSDT");
         assert_eq!(created_order.time_in_force, TimeInForce::Gtc);
         assert_eq!(created_order.side, OrderSide::Buy);
+        assert_eq!(created_order.type_, OrderType::Limit);
 
         let order = client
             .get_order("BTCUSDT", Some(created_order.order_id), None, None)
@@ -433,16 +702,14 @@ mod tests {
     #[tokio::test]
     #[serial]
     async fn test_cancel_open_orders() {
-        let api_key = env::var("BINANCE_TEST_API_KEY").unwrap();
-        let secret = env::var("BINANCE_TEST_SECRET").unwrap();
+        let server = MockServer::start().await;
 
-        let client = BinanceTradingClient::new(Client::new(), api_key, secret, None, true);
+        let client = mock_client(&server);
 
         let order_data = OrderCreationData::Limit {
             common: CommonOrderCreateData {
                 symbol: "BTCUSDT".to_string(),
                 side: OrderSide::Buy,
-                timestamp: chrono::Utc::now().timestamp_millis().try_into().unwrap(),
                 ..Default::default()
             },
             time_in_force: TimeInForce::Gtc,
@@ -459,4 +726,243 @@ mod tests {
         assert_eq!(orders[0].symbol, "BTCUSDT");
         assert_eq!(orders[0].order_id, created_order.order_id);
     }
+
+    #[tokio::test]
+    async fn test_oco_order_list() {
+        let server = MockServer::start().await;
+        let client = mock_client(&server);
+
+        let mut above = OcoLeg::new(OrderType::LimitMaker);
+        above.price = Some(Decimal::from(90_000));
+        above.client_order_id = Some("take-profit".to_string());
+        let mut below = OcoLeg::new(OrderType::StopLossLimit);
+        below.price = Some(Decimal::from(79_000));
+        below.stop_price = Some(Decimal::from(80_000));
+        below.time_in_force = Some(TimeInForce::Gtc);
+        let mut order_data = OcoOrderCreateData::new(
+            "BTCUSDT".to_string(),
+            OrderSide::Sell,
+            Decimal::from_str_exact("0.01").unwrap(),
+            above,
+            below,
+        );
+        order_data.list_client_order_id = Some("my-oco".to_string());
+
+        let list = client.create_oco_order(order_data).await.unwrap();
+        assert_eq!(list.list_client_order_id, "my-oco");
+        assert_eq!(list.list_order_status, ListOrderStatus::Executing);
+        assert_eq!(list.orders.len(), 2);
+        assert_eq!(list.order_reports.len(), 2);
+        assert_eq!(list.order_reports[0].client_order_id, "take-profit");
+        assert_eq!(list.order_reports[0].order_list_id, list.order_list_id);
+
+        let request = server.requests().pop().unwrap();
+        assert_eq!(request.param("aboveType").as_deref(), Some("LIMIT_MAKER"));
+        assert_eq!(request.param("belowStopPrice").as_deref(), Some("80000"));
+        assert_eq!(request.param("belowTimeInForce").as_deref(), Some("GTC"));
+
+        let fetched = client
+            .get_order_list(None, Some("my-oco"), None)
+            .await
+            .unwrap();
+        assert_eq!(fetched.order_list_id, list.order_list_id);
+        assert!(fetched.order_reports.is_empty());
+        assert_eq!(client.get_open_order_lists(None).await.unwrap().len(), 1);
+
+        let canceled = client
+            .cancel_order_list("BTCUSDT", Some(list.order_list_id), None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(canceled.list_order_status, ListOrderStatus::AllDone);
+        assert!(
+            canceled
+                .order_reports
+                .iter()
+                .all(|order| order.status == OrderStatus::Canceled)
+        );
+        assert!(client.get_open_order_lists(None).await.unwrap().is_empty());
+
+        let lists = client
+            .get_all_order_lists(None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(lists.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_oto_order_lists() {
+        let server = MockServer::start().await;
+        let client = mock_client(&server);
+        let quantity = Decimal::from_str_exact("0.01").unwrap();
+
+        let mut working = OrderListLeg::new(OrderType::Limit, OrderSide::Buy, quantity);
+        working.price = Some(Decimal::from(80_000));
+        working.time_in_force = Some(TimeInForce::Gtc);
+        let mut pending = OrderListLeg::new(OrderType::LimitMaker, OrderSide::Sell, quantity);
+        pending.price = Some(Decimal::from(90_000));
+
+        let list = client
+            .create_oto_order(OtoOrderCreateData::new(
+                "BTCUSDT".to_string(),
+                working,
+                pending,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(list.contingency_type, ContingencyType::Oto);
+        assert_eq!(list.order_reports[0].status, OrderStatus::New);
+        assert_eq!(list.order_reports[1].status, OrderStatus::PendingNew);
+        assert_eq!(list.order_reports[1].side, OrderSide::Sell);
+
+        let request = server.requests().pop().unwrap();
+        assert_eq!(request.param("workingType").as_deref(), Some("LIMIT"));
+        assert_eq!(request.param("pendingType").as_deref(), Some("LIMIT_MAKER"));
+        assert_eq!(request.param("pendingPrice").as_deref(), Some("90000"));
+
+        let mut working = OrderListLeg::new(OrderType::LimitMaker, OrderSide::Buy, quantity);
+        working.price = Some(Decimal::from(80_000));
+        let mut above = OcoLeg::new(OrderType::LimitMaker);
+        above.price = Some(Decimal::from(90_000));
+        let mut below = OcoLeg::new(OrderType::StopLoss);
+        below.stop_price = Some(Decimal::from(75_000));
+
+        let list = client
+            .create_otoco_order(OtocoOrderCreateData::new(
+                "BTCUSDT".to_string(),
+                working,
+                OrderSide::Sell,
+                quantity,
+                above,
+                below,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(list.orders.len(), 3);
+        assert!(
+            list.order_reports[1..]
+                .iter()
+                .all(|order| order.status == OrderStatus::PendingNew
+                    && order.side == OrderSide::Sell)
+        );
+
+        let request = server.requests().pop().unwrap();
+        assert_eq!(request.path, "orderList/otoco");
+        assert_eq!(request.param("pendingSide").as_deref(), Some("SELL"));
+        assert_eq!(
+            request.param("pendingAboveType").as_deref(),
+            Some("LIMIT_MAKER")
+        );
+        assert_eq!(
+            request.param("pendingBelowStopPrice").as_deref(),
+            Some("75000")
+        );
+    }
+
+    fn limit_order(price: i64) -> OrderCreationData {
+        return OrderCreationData::Limit {
+            common: CommonOrderCreateData {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Buy,
+                ..Default::default()
+            },
+            time_in_force: TimeInForce::Gtc,
+            quantity: Decimal::from_str_exact("0.01").unwrap(),
+            price: Decimal::from(price),
+            iceberg_qty: None,
+        };
+    }
+
+    #[tokio::test]
+    async fn test_cancel_replace_order() {
+        let server = MockServer::start().await;
+        let client = mock_client(&server);
+        let order = client.create_order(limit_order(80_000)).await.unwrap();
+
+        let result = client
+            .cancel_replace_order(CancelReplaceOrderData::new(
+                CancelReplaceMode::StopOnFailure,
+                order.order_id,
+                limit_order(81_000),
+            ))
+            .await
+            .unwrap();
+        assert!(result.is_success());
+        let new_order = result.new_order().unwrap();
+        assert_eq!(new_order.price, Decimal::from(81_000));
+        assert_eq!(new_order.status, OrderStatus::New);
+
+        // The order is already canceled, so only the new order is placed.
+        let result = client
+            .cancel_replace_order(CancelReplaceOrderData::new(
+                CancelReplaceMode::AllowFailure,
+                order.order_id,
+                limit_order(82_000),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(result.cancel_result, CancelReplaceStatus::Failure);
+        assert_eq!(result.new_order_result, CancelReplaceStatus::Success);
+        assert!(matches!(
+            result.cancel_response,
+            Some(CancelReplaceResponse::Error(ref error)) if error.code == -2011
+        ));
+        assert!(result.new_order().is_some());
+
+        let result = client
+            .cancel_replace_order(CancelReplaceOrderData::new(
+                CancelReplaceMode::StopOnFailure,
+                order.order_id,
+                limit_order(83_000),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(result.cancel_result, CancelReplaceStatus::Failure);
+        assert_eq!(result.new_order_result, CancelReplaceStatus::NotAttempted);
+        assert!(result.new_order().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_amend_order_keep_priority() {
+        let server = MockServer::start().await;
+        let client = mock_client(&server);
+        let general = BinanceGeneralClient::with_base_url(Client::new(), server.api_url());
+        let mut symbol_info = general
+            .get_exchange_info(&["BTCUSDT"])
+            .await
+            .unwrap()
+            .symbols
+            .remove(0);
+        let order = client.create_order(limit_order(80_000)).await.unwrap();
+
+        let new_qty = Decimal::from_str_exact("0.005").unwrap();
+        let result = client
+            .amend_order_keep_priority(
+                &symbol_info,
+                Some(order.order_id),
+                None,
+                Some("amended"),
+                new_qty,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.amended_order.order_id, order.order_id);
+        assert_eq!(result.amended_order.qty, new_qty);
+        assert_eq!(result.amended_order.client_order_id, "amended");
+        assert!(result.list_status.is_none());
+
+        symbol_info.allow_amend = Some(false);
+        let error = client
+            .amend_order_keep_priority(
+                &symbol_info,
+                Some(order.order_id),
+                None,
+                None,
+                new_qty,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error, BinanceError::NotAllowed(_)));
+    }
 }
@@ -0,0 +1,170 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use crate::client::BinanceGeneralClient;
+use crate::errors::BinanceError;
+
+/// How long a clock offset is trusted before it is measured again.
+const DEFAULT_SYNC_INTERVAL: Duration = Duration::from_mins(5);
+
+/// Estimates the offset between the local clock and the Binance server time,
+/// so that signed requests are stamped with the server time instead of the
+/// (possibly drifting) local time.
+///
+/// The offset is measured with `GET /api/v3/time` before the first signed
+/// request and again once it is older than the sync interval. Clones share
+/// the same estimate.
+#[derive(Debug, Clone)]
+pub struct ServerClock {
+    general: Arc<BinanceGeneralClient>,
+    state: Arc<Mutex<ClockState>>,
+}
+
+#[derive(Debug)]
+struct ClockState {
+    offset: i64,
+    latency: i64,
+    /// Local time in milliseconds of the last successful sync.
+    synced_at: Option<i64>,
+    sync_interval: Duration,
+}
+
+fn local_millis() -> i64 {
+    return chrono::Utc::now().timestamp_millis();
+}
+
+impl ServerClock {
+    /// Creates a clock that queries the server time with `general`.
+    #[must_use]
+    pub fn new(general: BinanceGeneralClient) -> Self {
+        return Self {
+            general: Arc::new(general),
+            state: Arc::new(Mutex::new(ClockState {
+                offset: 0,
+                latency: 0,
+                synced_at: None,
+                sync_interval: DEFAULT_SYNC_INTERVAL,
+            })),
+        };
+    }
+
+    /// Sets how long a measured offset is used before the clock is synced again.
+    #[must_use]
+    pub fn with_sync_interval(self, sync_interval: Duration) -> Self {
+        self.lock().sync_interval = sync_interval;
+        return self;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ClockState> {
+        return self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+    }
+
+    /// The estimated server time minus the local time, in milliseconds.
+    #[must_use]
+    pub fn offset(&self) -> i64 {
+        return self.lock().offset;
+    }
+
+    /// The round-trip time of the last sync, in milliseconds.
+    #[must_use]
+    pub fn latency(&self) -> i64 {
+        return self.lock().latency;
+    }
+
+    /// The current server time in milliseconds according to the last sync.
+    #[must_use]
+    pub fn now(&self) -> i64 {
+        return local_millis() + self.offset();
+    }
+
+    /// Measures the clock offset and round-trip latency.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server time cannot be fetched.
+    pub async fn sync(&self) -> Result<(), BinanceError> {
+        let sent_at = local_millis();
+        let server_time = self.general.get_time().await?.server_time;
+        let received_at = local_millis();
+
+        // The server time was taken roughly halfway through the round trip.
+        let latency = received_at - sent_at;
+        let offset = server_time.cast_signed() - (sent_at + latency / 2);
+        log::debug!("Server clock offset {offset}ms, latency {latency}ms");
+
+        let mut state = self.lock();
+        state.offset = offset;
+        state.latency = latency;
+        state.synced_at = Some(received_at);
+        drop(state);
+        return Ok(());
+    }
+
+    /// Returns the server time for a signed request, syncing first if the
+    /// offset has never been measured or is older than the sync interval.
+    ///
+    /// If the sync fails, the last known offset is used.
+    pub async fn timestamp(&self) -> i64 {
+        let stale = {
+            let state = self.lock();
+            state.synced_at.is_none_or(|synced_at| {
+                let age = (local_millis() - synced_at).cast_unsigned();
+                Duration::from_millis(age) >= state.sync_interval
+            })
+        };
+        if stale && let Err(error) = self.sync().await {
+            log::warn!("Failed to sync server clock: {error}");
+        }
+        return self.now();
+    }
+
+    /// Syncs the clock every `interval` in a background task until the
+    /// returned handle is aborted.
+    #[must_use]
+    pub fn spawn_sync(&self, interval: Duration) -> JoinHandle<()> {
+        let clock = self.clone();
+        return tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(error) = clock.sync().await {
+                    log::warn!("Failed to sync server clock: {error}");
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Client;
+
+    use crate::mock::MockServer;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sync_offset() {
+        let server = MockServer::start().await;
+        server.set_time_offset(-60_000);
+        let clock = ServerClock::new(BinanceGeneralClient::with_base_url(
+            Client::new(),
+            server.api_url(),
+        ));
+        assert_eq!(clock.offset(), 0);
+
+        let timestamp = clock.timestamp().await;
+        assert!((clock.offset() + 60_000).abs() < 1_000);
+        assert!((local_millis() - 60_000 - timestamp).abs() < 1_000);
+        assert!(clock.latency() >= 0);
+
+        // The offset is reused until the sync interval has passed.
+        clock.timestamp().await;
+        assert_eq!(server.requests().len(), 1);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::types::general::Error;
+use std::time::Duration;
+
+use crate::types::general::{BinanceErrorCode, Error};
+use crate::validation::FilterViolation;
 
 #[derive(Debug, thiserror::Error)]
 pub enum BinanceError {
@@ -9,14 +12,185 @@ pub enum BinanceError {
     Http(#[from] reqwest::Error),
 
     #[error("WebSocket error: {0}")]
-    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+
+    #[error("Query serialization error: {0}")]
+    Query(#[from] serde_qs::Error),
 
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    /// A stream message that could not be decoded as the expected event.
+    #[error("Invalid stream event: {0} - {1}")]
+    InvalidEvent(serde_json::Error, String),
+
     #[error("API error: {0} - {1:?}")]
     Api(u16, Error),
 
+    /// An error response to a combined stream method such as `SUBSCRIBE`;
+    /// unlike API errors it has no HTTP status.
+    #[error("Stream error: {0:?}")]
+    Stream(Error),
+
+    /// A non-success HTTP status whose body is not a Binance error payload.
+    #[error("Unexpected HTTP status: {0} - {1}")]
+    Status(u16, String),
+
+    /// The request was not sent because it would exceed a rate limit.
+    #[error("Rate limit reached, retry after {0:?}")]
+    RateLimited(Duration),
+
+    /// A private key used for signing could not be loaded.
+    #[error("Invalid private key: {0}")]
+    InvalidKey(String),
+
     #[error("Missing parameter: {0}")]
     MissingParameter(String),
+
+    /// The order was not sent because it breaks a trading rule of its symbol.
+    #[error("Invalid order: {0}")]
+    InvalidOrder(#[from] FilterViolation),
+
+    /// The request is not allowed for the symbol, e.g. amending orders of a
+    /// symbol without `allowAmend`.
+    #[error("Not allowed: {0}")]
+    NotAllowed(String),
+
+    /// The request was not sent because the WebSocket API is not connected.
+    #[error("Not connected: {0}")]
+    NotConnected(String),
+
+    /// The WebSocket API connection was lost before the response of a sent
+    /// request was received.
+    #[error("Connection lost before a response was received")]
+    ConnectionLost,
+
+    /// No response was received within the given time; the request may
+    /// still have been executed.
+    #[error("No response within {0:?}")]
+    Timeout(Duration),
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for BinanceError {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+        return Self::WebSocket(Box::new(error));
+    }
+}
+
+impl BinanceError {
+    /// Returns the HTTP status of an error response.
+    #[must_use]
+    pub const fn status(&self) -> Option<u16> {
+        return match self {
+            Self::Api(status, _) | Self::Status(status, _) => Some(*status),
+            _ => None,
+        };
+    }
+
+    /// Returns the Binance error code of an error response.
+    #[must_use]
+    pub const fn error_code(&self) -> Option<BinanceErrorCode> {
+        return match self {
+            Self::Api(_, error) => Some(error.error_code()),
+            _ => None,
+        };
+    }
+
+    /// The request was rejected because of a rate limit (HTTP 429), an IP ban
+    /// (HTTP 418) or a request weight / order count error code.
+    #[must_use]
+    pub fn is_rate_limited(&self) -> bool {
+        return matches!(self, Self::RateLimited(_))
+            || matches!(self.status(), Some(429 | 418))
+            || self
+                .error_code()
+                .is_some_and(BinanceErrorCode::is_rate_limited);
+    }
+
+    /// The request reached Binance but its outcome is unknown (HTTP 5xx or an
+    /// unknown-execution error code): an order may have been placed or canceled
+    /// and must be checked before the request is sent again.
+    #[must_use]
+    pub fn is_unknown_execution_status(&self) -> bool {
+        return matches!(self, Self::ConnectionLost | Self::Timeout(_))
+            || self.status().is_some_and(|status| status >= 500)
+            || self
+                .error_code()
+                .is_some_and(BinanceErrorCode::is_unknown_execution_status);
+    }
+
+    /// The request was certainly not executed and can be sent again, possibly
+    /// after backing off (rate limits) or resynchronising the clock (-1021).
+    ///
+    /// Connection errors are retryable only if they happened before the request
+    /// was sent, since otherwise the execution status is unknown.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        return match self {
+            Self::Http(error) => error.is_connect(),
+            Self::RateLimited(_) | Self::NotConnected(_) => true,
+            Self::Status(status, _) => matches!(status, 429 | 418),
+            Self::Api(status, error) => {
+                matches!(status, 429 | 418) || error.error_code().is_retryable()
+            }
+            _ => false,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_error(status: u16, code: i32) -> BinanceError {
+        return BinanceError::Api(
+            status,
+            Error {
+                code,
+                msg: String::new(),
+                data: None,
+            },
+        );
+    }
+
+    #[test]
+    fn test_error_code_round_trip() {
+        assert_eq!(
+            BinanceErrorCode::from_code(-1021),
+            BinanceErrorCode::InvalidTimestamp
+        );
+        assert_eq!(BinanceErrorCode::NewOrderRejected.code(), -2010);
+        assert_eq!(
+            BinanceErrorCode::from_code(-9999),
+            BinanceErrorCode::Other(-9999)
+        );
+        assert_eq!(BinanceErrorCode::Other(-9999).code(), -9999);
+    }
+
+    #[test]
+    fn test_classification() {
+        let timestamp = api_error(400, -1021);
+        assert!(timestamp.is_retryable());
+        assert!(!timestamp.is_rate_limited());
+
+        let rejected = api_error(400, -2010);
+        assert!(!rejected.is_retryable());
+        assert!(!rejected.is_unknown_execution_status());
+
+        let timeout = api_error(400, -1007);
+        assert!(timeout.is_unknown_execution_status());
+        assert!(!timeout.is_retryable());
+
+        let too_many_orders = api_error(429, -1015);
+        assert!(too_many_orders.is_rate_limited());
+        assert!(too_many_orders.is_retryable());
+
+        assert!(api_error(500, -1000).is_unknown_execution_status());
+        assert!(!BinanceError::MissingParameter(String::new()).is_retryable());
+        assert!(BinanceError::NotConnected(String::new()).is_retryable());
+        assert!(BinanceError::ConnectionLost.is_unknown_execution_status());
+        let no_response = BinanceError::Timeout(Duration::from_secs(10));
+        assert!(no_response.is_unknown_execution_status());
+        assert!(!no_response.is_retryable());
+    }
 }
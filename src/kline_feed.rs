@@ -0,0 +1,327 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::client::BinanceMarketClient;
+use crate::clock::ServerClock;
+use crate::errors::BinanceError;
+use crate::types::market::{Kline, KlineInterval};
+use crate::websocket::{BinanceWebSocket, ConnectionState, EventStream};
+
+/// The most klines Binance returns for one request.
+const MAX_KLINES_PER_REQUEST: usize = 1000;
+
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// A continuous, ordered sequence of the closed klines of a symbol and
+/// interval, starting with the latest ones from the REST API and continuing
+/// with the kline stream.
+///
+/// Candles received twice are dropped, and candles missed by the stream,
+/// e.g. while reconnecting, are fetched from the REST API, so that every
+/// kline opens when the previous one closes. Failed requests are yielded as
+/// errors and retried. Whether a candle is closed is decided with the server
+/// time, so that a skewed local clock neither yields open candles nor drops
+/// the latest closed one.
+#[derive(Debug)]
+pub struct KlineFeed {
+    symbol: String,
+    interval: KlineInterval,
+    klines: mpsc::Receiver<Result<Kline, BinanceError>>,
+    task: JoinHandle<()>,
+}
+
+impl Stream for KlineFeed {
+    type Item = Result<Kline, BinanceError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        return self.klines.poll_recv(cx);
+    }
+}
+
+impl Drop for KlineFeed {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl KlineFeed {
+    /// Starts following the closed klines of `symbol` and `interval`,
+    /// yielding the `backfill` latest ones first. `clock` gives the server
+    /// time the candles are checked against.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn start(
+        market: BinanceMarketClient,
+        clock: ServerClock,
+        websocket: &BinanceWebSocket,
+        symbol: &str,
+        interval: KlineInterval,
+        backfill: usize,
+    ) -> Result<Self, BinanceError> {
+        let symbol = symbol.to_uppercase();
+        // The stream is opened first so that no candle closes unseen during
+        // the backfill.
+        let live = websocket.closed_klines(&symbol, &interval, None)?;
+        let (klines_tx, klines) = mpsc::channel(64);
+        let feed = Feed {
+            market,
+            clock,
+            symbol: symbol.clone(),
+            interval: interval.clone(),
+            klines: klines_tx,
+            next_open_time: None,
+        };
+        let task = tokio::spawn(feed.run(live, backfill));
+        return Ok(Self {
+            symbol,
+            interval,
+            klines,
+            task,
+        });
+    }
+
+    #[must_use]
+    pub fn symbol(&self) -> &str {
+        return &self.symbol;
+    }
+
+    #[must_use]
+    pub const fn interval(&self) -> &KlineInterval {
+        return &self.interval;
+    }
+}
+
+struct Feed {
+    market: BinanceMarketClient,
+    clock: ServerClock,
+    symbol: String,
+    interval: KlineInterval,
+    klines: mpsc::Sender<Result<Kline, BinanceError>>,
+    /// The open time of the next kline to yield, once one was yielded.
+    next_open_time: Option<DateTime<Utc>>,
+}
+
+impl Feed {
+    async fn run(mut self, mut live: EventStream<Kline>, backfill: usize) {
+        let latest = loop {
+            match self.latest(backfill).await {
+                Ok(klines) => break klines,
+                Err(e) => {
+                    if !self.retry(e).await {
+                        return;
+                    }
+                }
+            }
+        };
+        if !self.yield_all(latest).await {
+            return;
+        }
+
+        let mut state = live.state();
+        state.mark_unchanged();
+        loop {
+            tokio::select! {
+                kline = live.next() => {
+                    let Some(kline) = kline else {
+                        return;
+                    };
+                    let delivered = match kline {
+                        Ok(kline) => self.push(kline).await,
+                        Err(e) => self.klines.send(Err(e)).await.is_ok(),
+                    };
+                    if !delivered {
+                        return;
+                    }
+                }
+                changed = state.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                    // Fetch the candles that closed while disconnected right
+                    // away rather than when the next one closes.
+                    let reconnected = *state.borrow_and_update() == ConnectionState::Connected;
+                    if reconnected && !self.refill(self.server_time().await).await {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Yields `kline`, after the klines missing before it.
+    async fn push(&mut self, kline: Kline) -> bool {
+        if let Some(next_open_time) = self.next_open_time {
+            if kline.open_time < next_open_time {
+                return true;
+            }
+            if kline.open_time > next_open_time && !self.refill(kline.open_time).await {
+                return false;
+            }
+        }
+        return self.yield_all(vec![kline]).await;
+    }
+
+    /// Yields the klines closed before `end` and not yielded yet, retrying
+    /// until they are fetched. Returns false once the receiver is dropped.
+    async fn refill(&mut self, end: DateTime<Utc>) -> bool {
+        let Some(start) = self.next_open_time else {
+            return true;
+        };
+        if start >= end {
+            return true;
+        }
+        loop {
+            let klines = self
+                .market
+                .get_historical_klines(&self.symbol, self.interval.clone(), start, end, None)
+                .await;
+            match klines {
+                Ok(klines) => {
+                    let missing = klines.into_iter().filter(|k| k.close_time < end).collect();
+                    return self.yield_all(missing).await;
+                }
+                Err(e) => {
+                    if !self.retry(e).await {
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fetches the `count` latest closed klines, paging back in time.
+    async fn latest(&self, count: usize) -> Result<Vec<Kline>, BinanceError> {
+        let now = self.server_time().await;
+        let mut klines = Vec::new();
+        let mut end_time = None;
+        while klines.len() < count {
+            // One more kline than missing, as the latest one is still open.
+            let limit = (count - klines.len() + 1).min(MAX_KLINES_PER_REQUEST);
+            let mut page = self
+                .market
+                .get_klines(
+                    &self.symbol,
+                    self.interval.clone(),
+                    u32::try_from(limit).ok(),
+                    None,
+                    end_time,
+                    None,
+                )
+                .await?;
+            page.retain(|kline| kline.close_time < now);
+            let Some(first) = page.first() else {
+                break;
+            };
+            end_time = Some(first.open_time - chrono::Duration::milliseconds(1));
+            page.append(&mut klines);
+            klines = page;
+        }
+        let excess = klines.len().saturating_sub(count);
+        klines.drain(..excess);
+        return Ok(klines);
+    }
+
+    async fn server_time(&self) -> DateTime<Utc> {
+        let timestamp = self.clock.timestamp().await;
+        return DateTime::from_timestamp_millis(timestamp).unwrap_or_else(Utc::now);
+    }
+
+    async fn yield_all(&mut self, klines: Vec<Kline>) -> bool {
+        for kline in klines {
+            if self
+                .next_open_time
+                .is_some_and(|next| kline.open_time < next)
+            {
+                continue;
+            }
+            self.next_open_time = Some(kline.close_time + chrono::Duration::milliseconds(1));
+            if self.klines.send(Ok(kline)).await.is_err() {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    /// Yields `error` and waits before retrying. Returns false once the
+    /// receiver is dropped.
+    async fn retry(&self, error: BinanceError) -> bool {
+        log::error!("Failed to fetch the klines of {}: {error}", self.symbol);
+        if self.klines.send(Err(error)).await.is_err() {
+            return false;
+        }
+        tokio::time::sleep(RETRY_DELAY).await;
+        return true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockServer;
+
+    #[tokio::test]
+    async fn test_kline_feed() {
+        let server = MockServer::start().await;
+        let client = server.client();
+        let mut feed = client
+            .kline_feed("btcusdt", KlineInterval::OneSecond, 1500)
+            .unwrap();
+        assert_eq!(feed.symbol(), "BTCUSDT");
+
+        let mut klines = Vec::new();
+        while klines.len() < 1502 {
+            klines.push(feed.next().await.unwrap().unwrap());
+        }
+        // The stream jumps ahead; the skipped candles are fetched.
+        server.set_time_offset(5_000);
+        while klines.len() < 1510 {
+            klines.push(feed.next().await.unwrap().unwrap());
+        }
+
+        for pair in klines.windows(2) {
+            assert_eq!(
+                pair[1].open_time,
+                pair[0].close_time + chrono::Duration::milliseconds(1)
+            );
+        }
+        let requests = server.requests();
+        let kline_requests = requests.iter().filter(|r| r.path == "klines").count();
+        assert!(kline_requests >= 3, "{kline_requests}");
+    }
+
+    #[tokio::test]
+    async fn test_kline_feed_skewed_clock() {
+        // The local clock is a minute ahead of the server.
+        let server = MockServer::start().await;
+        server.set_time_offset(-60_000);
+        let client = server.client();
+        let mut feed = client
+            .kline_feed("BTCUSDT", KlineInterval::OneSecond, 5)
+            .unwrap();
+
+        let mut klines = Vec::new();
+        while klines.len() < 5 {
+            klines.push(feed.next().await.unwrap().unwrap());
+        }
+        // The candle still open on the server is not yielded by the backfill.
+        let server_time = Utc::now() - chrono::Duration::seconds(60);
+        assert!(klines[4].close_time < server_time);
+        while klines.len() < 7 {
+            klines.push(feed.next().await.unwrap().unwrap());
+        }
+        for pair in klines.windows(2) {
+            assert_eq!(
+                pair[1].open_time,
+                pair[0].close_time + chrono::Duration::milliseconds(1)
+            );
+        }
+    }
+}
@@ -1,7 +1,20 @@
 mod client;
+pub mod clock;
 pub mod errors;
+pub mod kline_feed;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod order_book;
+pub mod portfolio;
+pub mod rate_limit;
 mod request;
+pub mod signer;
+pub mod stream_manager;
+pub mod symbol_registry;
 pub mod types;
+pub mod user_stream;
+pub mod validation;
 pub mod websocket;
+pub mod ws_api;
 
-pub use client::BinanceClient;
+pub use client::{BinanceClient, BinanceClientBuilder};
@@ -0,0 +1,427 @@
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+
+use super::{MockBalance, MockSymbol};
+
+/// Returns the length of a kline interval (e.g. `1m`, `1d`) in milliseconds.
+pub fn interval_millis(interval: &str) -> Option<i64> {
+    let (count, unit) = interval.split_at(interval.len().checked_sub(1)?);
+    let count: i64 = count.parse().ok()?;
+    let unit = match unit {
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        "w" => 604_800_000,
+        "M" => 2_592_000_000,
+        _ => return None,
+    };
+    return Some(count * unit);
+}
+
+/// Parses a UTC offset such as `+08:00` or `-5`.
+fn timezone_offset_millis(timezone: &str) -> Option<i64> {
+    let (sign, offset) = match timezone.split_at_checked(1)? {
+        ("+", offset) => (1, offset),
+        ("-", offset) => (-1, offset),
+        _ => return None,
+    };
+    let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+    let minutes = hours.parse::<i64>().ok()? * 60 + minutes.parse::<i64>().ok()?;
+    return Some(sign * minutes * 60_000);
+}
+
+/// The price step between generated order book levels and trades.
+fn tick(price: Decimal) -> Decimal {
+    return (price / Decimal::from(10_000))
+        .round_dp(8)
+        .max(Decimal::new(1, 8));
+}
+
+pub fn exchange_info(
+    symbols: &[&MockSymbol],
+    show_permission_sets: bool,
+    server_time: i64,
+) -> Value {
+    return json!({
+        "timezone": "UTC",
+        "serverTime": server_time,
+        "rateLimits": [
+            { "rateLimitType": "REQUEST_WEIGHT", "interval": "MINUTE", "intervalNum": 1, "limit": 6000 },
+            { "rateLimitType": "ORDERS", "interval": "SECOND", "intervalNum": 10, "limit": 100 },
+            { "rateLimitType": "ORDERS", "interval": "DAY", "intervalNum": 1, "limit": 200_000 },
+            { "rateLimitType": "RAW_REQUESTS", "interval": "MINUTE", "intervalNum": 5, "limit": 61000 }
+        ],
+        "exchangeFilters": [],
+        "symbols": symbols
+            .iter()
+            .map(|s| symbol_info(s, show_permission_sets))
+            .collect::<Vec<_>>(),
+    });
+}
+
+fn symbol_info(symbol: &MockSymbol, show_permission_sets: bool) -> Value {
+    let tick_size = tick(symbol.price);
+    return json!({
+        "symbol": symbol.symbol,
+        "status": symbol.status,
+        "baseAsset": symbol.base_asset,
+        "baseAssetPrecision": 8,
+        "quoteAsset": symbol.quote_asset,
+        "quotePrecision": 8,
+        "quoteAssetPrecision": 8,
+        "baseCommissionPrecision": 8,
+        "quoteCommissionPrecision": 8,
+        "orderTypes": [
+            "LIMIT", "LIMIT_MAKER", "MARKET", "STOP_LOSS", "STOP_LOSS_LIMIT",
+            "TAKE_PROFIT", "TAKE_PROFIT_LIMIT"
+        ],
+        "icebergAllowed": true,
+        "ocoAllowed": true,
+        "otoAllowed": true,
+        "quoteOrderQtyMarketAllowed": true,
+        "allowTrailingStop": true,
+        "cancelReplaceAllowed": true,
+        "allowAmend": true,
+        "isSpotTradingAllowed": true,
+        "isMarginTradingAllowed": false,
+        "filters": [
+            {
+                "filterType": "PRICE_FILTER",
+                "minPrice": tick_size.to_string(),
+                "maxPrice": (symbol.price * Decimal::from(100)).to_string(),
+                "tickSize": tick_size.to_string()
+            },
+            {
+                "filterType": "LOT_SIZE",
+                "minQty": "0.00001000",
+                "maxQty": "9000.00000000",
+                "stepSize": "0.00001000"
+            },
+            { "filterType": "ICEBERG_PARTS", "limit": 10 },
+            {
+                "filterType": "MARKET_LOT_SIZE",
+                "minQty": "0.00000000",
+                "maxQty": "100.00000000",
+                "stepSize": "0.00000000"
+            },
+            {
+                "filterType": "TRAILING_DELTA",
+                "minTrailingAboveDelta": 10,
+                "maxTrailingAboveDelta": 2000,
+                "minTrailingBelowDelta": 10,
+                "maxTrailingBelowDelta": 2000
+            },
+            {
+                "filterType": "PERCENT_PRICE_BY_SIDE",
+                "bidMultiplierUp": "5",
+                "bidMultiplierDown": "0.2",
+                "askMultiplierUp": "5",
+                "askMultiplierDown": "0.2",
+                "avgPriceMins": 5
+            },
+            {
+                "filterType": "NOTIONAL",
+                "minNotional": "5.00000000",
+                "applyMinToMarket": true,
+                "maxNotional": "9000000.00000000",
+                "applyMaxToMarket": false,
+                "avgPriceMins": 5
+            },
+            { "filterType": "MAX_NUM_ORDERS", "maxNumOrders": 200 },
+            { "filterType": "MAX_NUM_ORDER_LISTS", "maxNumOrderLists": 20 },
+            { "filterType": "MAX_NUM_ALGO_ORDERS", "maxNumAlgoOrders": 5 },
+            { "filterType": "MAX_NUM_ORDER_AMENDS", "maxNumOrderAmends": 10 }
+        ],
+        "permissions": [],
+        "permissionSets": if show_permission_sets { json!([["SPOT"]]) } else { json!([]) },
+        "defaultSelfTradePreventionMode": "EXPIRE_MAKER",
+        "allowedSelfTradePreventionModes": ["NONE", "EXPIRE_TAKER", "EXPIRE_MAKER", "EXPIRE_BOTH"]
+    });
+}
+
+pub fn order_book(price: Decimal, limit: usize, last_update_id: u64) -> Value {
+    let tick = tick(price);
+    let bids = (1..=limit)
+        .map(|i| json!([(price - tick * Decimal::from(i)).to_string(), "1.00000000"]))
+        .collect::<Vec<_>>();
+    let asks = (1..=limit)
+        .map(|i| json!([(price + tick * Decimal::from(i)).to_string(), "1.00000000"]))
+        .collect::<Vec<_>>();
+    return json!({ "lastUpdateId": last_update_id, "bids": bids, "asks": asks });
+}
+
+pub fn trades(price: Decimal, first_id: i64, limit: i64, now: i64) -> Value {
+    let tick = tick(price);
+    return (0..limit)
+        .map(|i| {
+            let price = price + tick * Decimal::from(i % 3);
+            let qty = Decimal::new(1, 3);
+            json!({
+                "id": first_id + i,
+                "price": price.to_string(),
+                "qty": qty.to_string(),
+                "quoteQty": (price * qty).to_string(),
+                "time": now - (limit - i) * 1_000,
+                "isBuyerMaker": i % 2 == 0,
+                "isBestMatch": true
+            })
+        })
+        .collect();
+}
+
+pub fn agg_trades(price: Decimal, first_id: i64, limit: i64, now: i64) -> Value {
+    return (0..limit)
+        .map(|i| {
+            let id = first_id + i;
+            json!({
+                "a": id,
+                "p": price.to_string(),
+                "q": "0.00100000",
+                "f": id * 2,
+                "l": id * 2 + 1,
+                "T": now - (limit - i) * 1_000,
+                "m": i % 2 == 0,
+                "M": true
+            })
+        })
+        .collect();
+}
+
+/// Generates up to `limit` klines of `interval_ms` opening in `[start, end]`.
+pub fn klines(price: Decimal, interval_ms: i64, start: i64, end: i64, limit: usize) -> Value {
+    let first_open = (start + interval_ms - 1).div_euclid(interval_ms) * interval_ms;
+    return (0..)
+        .map(|i| first_open + i * interval_ms)
+        .take_while(|open_time| *open_time <= end)
+        .take(limit)
+        .map(|open_time| {
+            let tick = tick(price);
+            json!([
+                open_time,
+                price.to_string(),
+                (price + tick).to_string(),
+                (price - tick).to_string(),
+                price.to_string(),
+                "10.00000000",
+                open_time + interval_ms - 1,
+                (price * Decimal::TEN).to_string(),
+                100,
+                "5.00000000",
+                (price * Decimal::from(5)).to_string(),
+                "0"
+            ])
+        })
+        .collect();
+}
+
+/// A ticker containing the fields of every ticker variant (24hr, trading day,
+/// rolling window, full and mini).
+pub fn ticker(symbol: &MockSymbol, window_ms: i64, now: i64) -> Value {
+    let price = symbol.price;
+    let open = price - tick(price) * Decimal::TEN;
+    let change = price - open;
+    return json!({
+        "symbol": symbol.symbol,
+        "priceChange": change.to_string(),
+        "priceChangePercent": (change / open * Decimal::ONE_HUNDRED).round_dp(3).to_string(),
+        "weightedAvgPrice": price.to_string(),
+        "prevClosePrice": open.to_string(),
+        "lastPrice": price.to_string(),
+        "lastQty": "0.00100000",
+        "bidPrice": (price - tick(price)).to_string(),
+        "bidQty": "1.00000000",
+        "askPrice": (price + tick(price)).to_string(),
+        "askQty": "1.00000000",
+        "openPrice": open.to_string(),
+        "highPrice": (price + tick(price)).to_string(),
+        "lowPrice": open.to_string(),
+        "volume": "1000.00000000",
+        "quoteVolume": (price * Decimal::from(1000)).to_string(),
+        "openTime": now - window_ms,
+        "closeTime": now,
+        "firstId": 1,
+        "lastId": 1000,
+        "count": 1000
+    });
+}
+
+pub fn ticker_price(symbol: &MockSymbol) -> Value {
+    return json!({ "symbol": symbol.symbol, "price": symbol.price.to_string() });
+}
+
+pub fn book_ticker(symbol: &MockSymbol) -> Value {
+    let tick = tick(symbol.price);
+    return json!({
+        "symbol": symbol.symbol,
+        "bidPrice": (symbol.price - tick).to_string(),
+        "bidQty": "1.00000000",
+        "askPrice": (symbol.price + tick).to_string(),
+        "askQty": "1.00000000"
+    });
+}
+
+pub fn average_price(symbol: &MockSymbol, now: i64) -> Value {
+    return json!({ "mins": 5, "price": symbol.price.to_string(), "closeTime": now });
+}
+
+pub fn account(balances: &[MockBalance], omit_zero_balances: bool, now: i64) -> Value {
+    let balances = balances
+        .iter()
+        .filter(|b| !omit_zero_balances || !(b.free + b.locked).is_zero())
+        .map(|b| {
+            json!({
+                "asset": b.asset,
+                "free": b.free.to_string(),
+                "locked": b.locked.to_string()
+            })
+        })
+        .collect::<Vec<_>>();
+    return json!({
+        "makerCommission": 10,
+        "takerCommission": 10,
+        "buyerCommission": 0,
+        "sellerCommission": 0,
+        "commissionRates": {
+            "maker": "0.00100000",
+            "taker": "0.00100000",
+            "buyer": "0.00000000",
+            "seller": "0.00000000"
+        },
+        "canTrade": true,
+        "canWithdraw": true,
+        "canDeposit": true,
+        "brokered": false,
+        "requireSelfTradePrevention": false,
+        "preventSor": false,
+        "updateTime": now,
+        "accountType": "SPOT",
+        "balances": balances,
+        "permissions": ["SPOT"],
+        "uid": 354_937_868
+    });
+}
+
+/// Generates one event of a market data stream (e.g. `aggTrade`, `kline_1m`).
+pub fn stream_event(symbol: &MockSymbol, stream: &str, seq: u64, now: i64) -> Option<Value> {
+    let price = symbol.price;
+    let name = symbol.symbol.as_str();
+    let tick = tick(price);
+    let event = match stream {
+        "aggTrade" => json!({
+            "e": "aggTrade", "E": now, "s": name, "a": seq,
+            "p": price.to_string(), "q": "0.00100000",
+            "f": seq * 2, "l": seq * 2 + 1, "T": now, "m": seq.is_multiple_of(2), "M": true
+        }),
+        "trade" => json!({
+            "e": "trade", "E": now, "s": name, "t": seq,
+            "p": price.to_string(), "q": "0.00100000",
+            "T": now, "m": seq.is_multiple_of(2), "M": true
+        }),
+        "bookTicker" => json!({
+            "u": seq, "s": name,
+            "b": (price - tick).to_string(), "B": "1.00000000",
+            "a": (price + tick).to_string(), "A": "1.00000000"
+        }),
+        "miniTicker" => json!({
+            "e": "24hrMiniTicker", "E": now, "s": name,
+            "c": price.to_string(), "o": (price - tick).to_string(),
+            "h": (price + tick).to_string(), "l": (price - tick).to_string(),
+            "v": "1000.00000000", "q": (price * Decimal::from(1000)).to_string()
+        }),
+        "ticker" => json!({
+            "e": "24hrTicker", "E": now, "s": name,
+            "p": tick.to_string(), "P": "0.010", "w": price.to_string(),
+            "x": (price - tick).to_string(), "c": price.to_string(), "Q": "0.00100000",
+            "b": (price - tick).to_string(), "B": "1.00000000",
+            "a": (price + tick).to_string(), "A": "1.00000000",
+            "o": (price - tick).to_string(), "h": (price + tick).to_string(),
+            "l": (price - tick).to_string(), "v": "1000.00000000",
+            "q": (price * Decimal::from(1000)).to_string(),
+            "O": now - 86_400_000, "C": now, "F": 1, "L": 1000, "n": 1000
+        }),
+        "avgPrice" => json!({
+            "e": "avgPrice", "E": now, "s": name, "i": "5m",
+            "w": price.to_string(), "T": now
+        }),
+        _ if stream.starts_with("ticker_") => {
+            let window = &stream["ticker_".len()..];
+            let window_ms = interval_millis(window)?;
+            json!({
+                "e": format!("{window}Ticker"), "E": now, "s": name,
+                "p": tick.to_string(), "P": "0.010",
+                "o": (price - tick).to_string(), "h": (price + tick).to_string(),
+                "l": (price - tick).to_string(), "c": price.to_string(),
+                "w": price.to_string(), "v": "1000.00000000",
+                "q": (price * Decimal::from(1000)).to_string(),
+                "O": now - window_ms, "C": now, "F": 1, "L": 1000, "n": 1000
+            })
+        }
+        _ => {
+            // Kline streams may open their candles in a timezone
+            // (`kline_<interval>@<offset>`), and every fifth update closes
+            // the candle.
+            let kline = stream.strip_prefix("kline_")?;
+            let (interval, offset) = match kline.split_once('@') {
+                Some((interval, timezone)) => (interval, timezone_offset_millis(timezone)?),
+                None => (kline, 0),
+            };
+            let interval_ms = interval_millis(interval)?;
+            let open_time = (now + offset).div_euclid(interval_ms) * interval_ms - offset;
+            json!({
+                "e": "kline", "E": now, "s": name,
+                "k": {
+                    "t": open_time, "T": open_time + interval_ms - 1, "s": name, "i": interval,
+                    "f": seq, "L": seq + 10,
+                    "o": price.to_string(), "c": price.to_string(),
+                    "h": (price + tick).to_string(), "l": (price - tick).to_string(),
+                    "v": "10.00000000", "n": 10, "x": seq.is_multiple_of(5),
+                    "q": (price * Decimal::TEN).to_string(),
+                    "V": "5.00000000", "Q": (price * Decimal::from(5)).to_string(), "B": "0"
+                }
+            })
+        }
+    };
+    return Some(event);
+}
+
+/// A `depthUpdate` event changing one bid and one ask level of the book
+/// returned by [`order_book`], removing the level on every fourth update.
+pub fn depth_update(symbol: &MockSymbol, first: u64, last: u64, seq: u64, now: i64) -> Value {
+    let tick = tick(symbol.price);
+    let offset = tick * Decimal::from(seq % 5 + 1);
+    let quantity = if seq.is_multiple_of(4) {
+        Decimal::ZERO
+    } else {
+        Decimal::from(seq)
+    };
+    return json!({
+        "e": "depthUpdate", "E": now, "s": symbol.symbol, "U": first, "u": last,
+        "b": [[(symbol.price - offset).to_string(), quantity.to_string()]],
+        "a": [[(symbol.price + offset).to_string(), quantity.to_string()]]
+    });
+}
+
+/// An `executionReport` user data event for an order in Binance order JSON.
+pub fn execution_report(order: &Value, execution_type: &str, now: i64) -> Value {
+    let is_trade = execution_type == "TRADE";
+    let zero = json!("0");
+    let empty = json!("");
+    return json!({
+        "e": "executionReport", "E": now, "s": order["symbol"],
+        "c": order["clientOrderId"], "S": order["side"], "o": order["type"],
+        "f": order["timeInForce"], "q": order["origQty"], "p": order["price"],
+        "P": order["stopPrice"], "F": "0", "g": order["orderListId"],
+        "C": order.get("origClientOrderId").unwrap_or(&empty),
+        "x": execution_type, "X": order["status"], "r": "NONE", "i": order["orderId"],
+        "l": if is_trade { &order["executedQty"] } else { &zero },
+        "z": order["executedQty"],
+        "L": if is_trade { &order["price"] } else { &zero },
+        "n": "0", "N": null, "T": now, "t": if is_trade { now } else { -1 },
+        "w": order["status"] == "NEW", "m": false, "O": order["time"],
+        "Z": order["cummulativeQuoteQty"],
+        "Y": if is_trade { &order["cummulativeQuoteQty"] } else { &zero },
+        "Q": order["origQuoteOrderQty"], "W": order["workingTime"],
+        "V": order["selfTradePreventionMode"]
+    });
+}
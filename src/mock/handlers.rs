@@ -0,0 +1,1432 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use axum::Router;
+use axum::body::Body;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, RawQuery, State};
+use axum::http::{HeaderMap, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+use tokio::sync::broadcast;
+
+use super::{MockResponse, MockState, MockSymbol, RecordedRequest, SharedState, fixtures, lock};
+
+/// The default `recvWindow` applied by Binance when a signed request does not send one.
+const DEFAULT_RECV_WINDOW: i64 = 5000;
+
+/// Binance closes stream connections that receive more messages per second.
+const MAX_STREAM_MESSAGES_PER_SECOND: usize = 5;
+
+pub fn router(state: SharedState) -> Router {
+    return Router::new()
+        .route("/ws/{stream}", get(handle_stream))
+        .route("/ws-api/v3", get(handle_ws_api))
+        .route("/stream", get(handle_combined_stream))
+        .fallback(handle_rest)
+        .with_state(state);
+}
+
+fn into_response(response: MockResponse) -> Response {
+    let mut builder = Response::builder()
+        .status(response.status)
+        .header("Content-Type", "application/json");
+    for (name, value) in &response.headers {
+        builder = builder.header(name, value);
+    }
+    return builder
+        .body(Body::from(response.body))
+        .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response());
+}
+
+async fn handle_rest(
+    State(state): State<SharedState>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let Some(path) = uri.path().strip_prefix("/api/v3/") else {
+        return into_response(MockResponse::error(404, -1000, "Unknown endpoint."));
+    };
+    let query = uri.query().unwrap_or("");
+    let api_key = headers
+        .get("X-MBX-APIKEY")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let mut state = lock(&state);
+    state.requests.push(RecordedRequest {
+        method: method.clone(),
+        path: path.to_string(),
+        query: query.to_string(),
+        api_key: api_key.clone(),
+    });
+
+    let scripted = state
+        .scripted
+        .get_mut(&(method.clone(), path.to_string()))
+        .and_then(VecDeque::pop_front);
+    if let Some(response) = scripted {
+        return into_response(response);
+    }
+
+    let request = Request {
+        method,
+        path,
+        query,
+        body: &body,
+        api_key: api_key.as_deref(),
+        params: Params::parse(query, &body),
+    };
+    let result = dispatch(&mut state, &request);
+    drop(state);
+
+    return match result {
+        Ok(value) => into_response(MockResponse::json(&value)),
+        Err(response) => into_response(response),
+    };
+}
+
+struct Request<'a> {
+    method: Method,
+    path: &'a str,
+    query: &'a str,
+    body: &'a str,
+    api_key: Option<&'a str>,
+    params: Params,
+}
+
+struct Params(Vec<(String, String)>);
+
+impl Params {
+    fn parse(query: &str, body: &str) -> Self {
+        return Self(
+            url::form_urlencoded::parse(query.as_bytes())
+                .chain(url::form_urlencoded::parse(body.as_bytes()))
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                .collect(),
+        );
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        return self
+            .0
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str());
+    }
+
+    fn required(&self, name: &str) -> Result<&str, MockResponse> {
+        return self.get(name).filter(|v| !v.is_empty()).ok_or_else(|| {
+            MockResponse::error(
+                400,
+                -1102,
+                &format!(
+                    "Mandatory parameter '{name}' was not sent, was empty/null, or malformed."
+                ),
+            )
+        });
+    }
+
+    fn parse_required<T: FromStr>(&self, name: &str) -> Result<T, MockResponse> {
+        return self
+            .required(name)?
+            .parse()
+            .map_err(|_| illegal_parameter(name));
+    }
+
+    fn parse_opt<T: FromStr>(&self, name: &str) -> Result<Option<T>, MockResponse> {
+        return self
+            .get(name)
+            .map(|value| value.parse().map_err(|_| illegal_parameter(name)))
+            .transpose();
+    }
+}
+
+fn illegal_parameter(name: &str) -> MockResponse {
+    return MockResponse::error(
+        400,
+        -1100,
+        &format!("Illegal characters found in parameter '{name}'."),
+    );
+}
+
+fn invalid_symbol() -> MockResponse {
+    return MockResponse::error(400, -1121, "Invalid symbol.");
+}
+
+fn is_signed(path: &str) -> bool {
+    return matches!(
+        path,
+        "order"
+            | "order/test"
+            | "order/cancelReplace"
+            | "order/amend/keepPriority"
+            | "openOrders"
+            | "allOrders"
+            | "orderList/oco"
+            | "orderList/oto"
+            | "orderList/otoco"
+            | "orderList"
+            | "allOrderList"
+            | "openOrderList"
+            | "myTrades"
+            | "account"
+    );
+}
+
+fn verify_api_key(state: &MockState, request: &Request) -> Result<(), MockResponse> {
+    if request.api_key != Some(state.api_key.as_str()) {
+        return Err(MockResponse::error(
+            401,
+            -2015,
+            "Invalid API-key, IP, or permissions for action.",
+        ));
+    }
+    return Ok(());
+}
+
+/// Verifies the API key, signature and timestamp of a signed request.
+fn verify_signed(state: &MockState, request: &Request) -> Result<(), MockResponse> {
+    verify_api_key(state, request)?;
+
+    let signature = request.params.required("signature")?;
+    let mut payload = request
+        .query
+        .split('&')
+        .filter(|pair| !pair.starts_with("signature="))
+        .collect::<Vec<_>>()
+        .join("&");
+    payload.push_str(request.body);
+    if state.signer.sign(&payload) != signature {
+        return Err(MockResponse::error(
+            400,
+            -1022,
+            "Signature for this request is not valid.",
+        ));
+    }
+
+    return verify_timestamp(state, &request.params);
+}
+
+/// Rejects requests whose `timestamp` is outside of their `recvWindow`.
+fn verify_timestamp(state: &MockState, params: &Params) -> Result<(), MockResponse> {
+    let timestamp: i64 = params.parse_required("timestamp")?;
+    let recv_window: i64 = params
+        .parse_opt("recvWindow")?
+        .unwrap_or(DEFAULT_RECV_WINDOW);
+    let server_time = state.server_time();
+    if timestamp >= server_time + 1000 || server_time - timestamp > recv_window {
+        return Err(MockResponse::error(
+            400,
+            -1021,
+            "Timestamp for this request is outside of the recvWindow.",
+        ));
+    }
+    return Ok(());
+}
+
+fn dispatch(state: &mut MockState, request: &Request) -> Result<Value, MockResponse> {
+    if request.path == "userDataStream" {
+        verify_api_key(state, request)?;
+        return user_data_stream(state, request);
+    }
+    if is_signed(request.path) {
+        verify_signed(state, request)?;
+        return dispatch_signed(state, request);
+    }
+    return dispatch_public(state, request);
+}
+
+/// Creates, keeps alive or closes a listen key.
+fn user_data_stream(state: &mut MockState, request: &Request) -> Result<Value, MockResponse> {
+    if request.method == Method::POST {
+        if state.listen_keys.is_empty() {
+            let listen_key = format!("mocklistenkey{:0>51}", state.requests.len());
+            state.listen_keys.push(listen_key);
+        }
+        return Ok(json!({ "listenKey": state.listen_keys[0] }));
+    }
+
+    let listen_key = request.params.required("listenKey")?;
+    let Some(index) = state.listen_keys.iter().position(|k| k == listen_key) else {
+        return Err(MockResponse::error(
+            400,
+            -1125,
+            "This listenKey does not exist.",
+        ));
+    };
+    if request.method == Method::DELETE {
+        state.listen_keys.remove(index);
+    }
+    return Ok(json!({}));
+}
+
+fn dispatch_public(state: &MockState, request: &Request) -> Result<Value, MockResponse> {
+    let params = &request.params;
+    let now = state.server_time();
+    return match (&request.method, request.path) {
+        (&Method::GET, "ping") => Ok(json!({})),
+        (&Method::GET, "time") => Ok(json!({ "serverTime": now })),
+        (&Method::GET, "exchangeInfo") => {
+            let permissions = params.get("permissions");
+            let status = params.get("symbolStatus");
+            if (permissions.is_some() || status.is_some())
+                && (params.get("symbol").is_some() || params.get("symbols").is_some())
+            {
+                return Err(MockResponse::error(
+                    400,
+                    -1128,
+                    "Combination of optional parameters invalid.",
+                ));
+            }
+            let mut symbols = requested_symbols(state, params)?;
+            if let Some(status) = status {
+                symbols.retain(|s| s.status == status);
+            }
+            // Every mock symbol has the single permission set `["SPOT"]`.
+            if let Some(permissions) = permissions
+                && !permissions.contains("SPOT")
+            {
+                symbols.clear();
+            }
+            let show_permission_sets = params.get("showPermissionSets") != Some("false");
+            Ok(fixtures::exchange_info(&symbols, show_permission_sets, now))
+        }
+        (&Method::GET, "depth") => {
+            let symbol = symbol(state, params)?;
+            let limit = params.parse_opt("limit")?.unwrap_or(100).min(5000);
+            let last_update_id = state.depth_update_id(&symbol.symbol);
+            Ok(fixtures::order_book(symbol.price, limit, last_update_id))
+        }
+        (&Method::GET, "trades" | "historicalTrades") => {
+            let symbol = symbol(state, params)?;
+            let limit = params.parse_opt("limit")?.unwrap_or(500).min(1000);
+            let from_id = params.parse_opt("fromId")?.unwrap_or(1);
+            Ok(fixtures::trades(symbol.price, from_id, limit, now))
+        }
+        (&Method::GET, "aggTrades") => {
+            let symbol = symbol(state, params)?;
+            let limit = params.parse_opt("limit")?.unwrap_or(500).min(1000);
+            let from_id = params.parse_opt("fromId")?.unwrap_or(1);
+            Ok(fixtures::agg_trades(symbol.price, from_id, limit, now))
+        }
+        (&Method::GET, "klines" | "uiKlines") => {
+            let symbol = symbol(state, params)?;
+            let interval = params.required("interval")?;
+            let interval_ms = fixtures::interval_millis(interval)
+                .ok_or_else(|| MockResponse::error(400, -1120, "Invalid interval."))?;
+            let limit = params.parse_opt("limit")?.unwrap_or(500).min(1000);
+            let end = params.parse_opt("endTime")?.unwrap_or(now).min(now);
+            let start = params
+                .parse_opt("startTime")?
+                .unwrap_or_else(|| end - interval_ms * i64::try_from(limit).unwrap_or(i64::MAX));
+            Ok(fixtures::klines(
+                symbol.price,
+                interval_ms,
+                start,
+                end,
+                limit,
+            ))
+        }
+        (&Method::GET, "avgPrice") => Ok(fixtures::average_price(symbol(state, params)?, now)),
+        (&Method::GET, "ticker/24hr" | "ticker/tradingDay" | "ticker") => {
+            let window_ms = match params.get("windowSize") {
+                Some(window) => fixtures::interval_millis(window)
+                    .ok_or_else(|| MockResponse::error(400, -1100, "Invalid windowSize."))?,
+                None => 86_400_000,
+            };
+            let symbols = requested_symbols(state, params)?;
+            Ok(symbols
+                .iter()
+                .map(|s| fixtures::ticker(s, window_ms, now))
+                .collect())
+        }
+        (&Method::GET, "ticker/price") => Ok(requested_symbols(state, params)?
+            .into_iter()
+            .map(fixtures::ticker_price)
+            .collect()),
+        (&Method::GET, "ticker/bookTicker") => Ok(requested_symbols(state, params)?
+            .into_iter()
+            .map(fixtures::book_ticker)
+            .collect()),
+        _ => Err(MockResponse::error(404, -1000, "Unknown endpoint.")),
+    };
+}
+
+fn dispatch_signed(state: &mut MockState, request: &Request) -> Result<Value, MockResponse> {
+    let params = &request.params;
+    let now = state.server_time();
+    return match (&request.method, request.path) {
+        (&Method::POST, "order") => create_order(state, params, now, -1),
+        (&Method::POST, "order/test") => test_order(state, params, now),
+        (&Method::GET, "order") => {
+            let index = find_order(state, params)?
+                .ok_or_else(|| MockResponse::error(400, -2013, "Order does not exist."))?;
+            Ok(state.orders[index].clone())
+        }
+        (&Method::DELETE, "order") => {
+            let index = find_order(state, params)?
+                .ok_or_else(|| MockResponse::error(400, -2011, "Unknown order sent."))?;
+            let new_client_order_id = params.get("newClientOrderId");
+            let canceled = cancel_order(&mut state.orders[index], new_client_order_id, now)?;
+            state.push_user_event(fixtures::execution_report(&canceled, "CANCELED", now));
+            Ok(canceled)
+        }
+        (&Method::POST, "order/cancelReplace") => cancel_replace_order(state, params, now),
+        (&Method::PUT, "order/amend/keepPriority") => amend_order(state, params, now),
+        (&Method::GET, "openOrders") => {
+            let symbol = params.get("symbol");
+            Ok(state
+                .orders
+                .iter()
+                .filter(|o| is_open(o) && symbol.is_none_or(|s| o["symbol"] == s))
+                .cloned()
+                .collect())
+        }
+        (&Method::DELETE, "openOrders") => {
+            let symbol = params.required("symbol")?.to_string();
+            let canceled = state
+                .orders
+                .iter_mut()
+                .filter(|o| is_open(o) && o["symbol"] == symbol.as_str())
+                .map(|o| cancel_order(o, params.get("newClientOrderId"), now))
+                .collect::<Result<Vec<_>, _>>()?;
+            if canceled.is_empty() {
+                return Err(MockResponse::error(400, -2011, "Unknown order sent."));
+            }
+            Ok(Value::Array(canceled))
+        }
+        (&Method::GET, "allOrders") => {
+            let symbol = params.required("symbol")?;
+            let order_id: u64 = params.parse_opt("orderId")?.unwrap_or(0);
+            let start: i64 = params.parse_opt("startTime")?.unwrap_or(i64::MIN);
+            let end: i64 = params.parse_opt("endTime")?.unwrap_or(i64::MAX);
+            let limit = params.parse_opt("limit")?.unwrap_or(500).min(1000);
+            Ok(state
+                .orders
+                .iter()
+                .filter(|o| {
+                    let time = o["time"].as_i64().unwrap_or_default();
+                    o["symbol"] == symbol
+                        && o["orderId"].as_u64().unwrap_or_default() >= order_id
+                        && (start..=end).contains(&time)
+                })
+                .take(limit)
+                .cloned()
+                .collect())
+        }
+        (_, path) if path.starts_with("orderList") || path.ends_with("OrderList") => {
+            dispatch_order_list(state, request)
+        }
+        (&Method::GET, "myTrades") => my_trades(state, params),
+        (&Method::GET, "account") => {
+            let omit_zero = params.parse_opt("omitZeroBalances")?.unwrap_or(false);
+            Ok(fixtures::account(&state.balances, omit_zero, now))
+        }
+        _ => Err(MockResponse::error(404, -1000, "Unknown endpoint.")),
+    };
+}
+
+fn dispatch_order_list(state: &mut MockState, request: &Request) -> Result<Value, MockResponse> {
+    let params = &request.params;
+    let now = state.server_time();
+    return match (&request.method, request.path) {
+        (&Method::POST, "orderList/oco") => {
+            create_order_list(state, params, now, "OCO", &[&["above"], &["below"]])
+        }
+        (&Method::POST, "orderList/oto") => {
+            create_order_list(state, params, now, "OTO", &[&["working"], &["pending"]])
+        }
+        (&Method::POST, "orderList/otoco") => create_order_list(
+            state,
+            params,
+            now,
+            "OTO",
+            &[
+                &["working"],
+                &["pendingAbove", "pending"],
+                &["pendingBelow", "pending"],
+            ],
+        ),
+        (&Method::GET, "orderList") => {
+            let index = find_order_list(state, params, "origClientOrderId")?
+                .ok_or_else(|| MockResponse::error(400, -2018, "Order list does not exist."))?;
+            Ok(state.order_lists[index].clone())
+        }
+        (&Method::DELETE, "orderList") => {
+            params.required("symbol")?;
+            let index = find_order_list(state, params, "listClientOrderId")?
+                .ok_or_else(|| MockResponse::error(400, -2011, "Unknown order sent."))?;
+            cancel_order_list(state, index, params, now)
+        }
+        (&Method::GET, "allOrderList") => {
+            let from_id: i64 = params.parse_opt("fromId")?.unwrap_or(0);
+            let start: i64 = params.parse_opt("startTime")?.unwrap_or(i64::MIN);
+            let end: i64 = params.parse_opt("endTime")?.unwrap_or(i64::MAX);
+            let limit = params.parse_opt("limit")?.unwrap_or(500).min(1000);
+            Ok(state
+                .order_lists
+                .iter()
+                .filter(|l| {
+                    let time = l["transactionTime"].as_i64().unwrap_or_default();
+                    l["orderListId"].as_i64().unwrap_or_default() >= from_id
+                        && (start..=end).contains(&time)
+                })
+                .take(limit)
+                .cloned()
+                .collect())
+        }
+        (&Method::GET, "openOrderList") => Ok(state
+            .order_lists
+            .iter()
+            .filter(|l| l["listOrderStatus"] == "EXECUTING")
+            .cloned()
+            .collect()),
+        _ => Err(MockResponse::error(404, -1000, "Unknown endpoint.")),
+    };
+}
+
+/// The account trades of a symbol: the most recent ones, or the oldest ones
+/// from `fromId` or in the `startTime`..`endTime` window of at most 24 hours.
+fn my_trades(state: &MockState, params: &Params) -> Result<Value, MockResponse> {
+    let symbol = params.required("symbol")?;
+    let order_id: Option<u64> = params.parse_opt("orderId")?;
+    let from_id: Option<u64> = params.parse_opt("fromId")?;
+    let start: Option<i64> = params.parse_opt("startTime")?;
+    let end: Option<i64> = params.parse_opt("endTime")?;
+    let limit = params.parse_opt("limit")?.unwrap_or(500).min(1000);
+    if let (Some(start), Some(end)) = (start, end)
+        && end - start > 86_400_000
+    {
+        return Err(MockResponse::error(
+            400,
+            -1127,
+            "More than 24 hours between startTime and endTime.",
+        ));
+    }
+
+    let trades = state
+        .trades
+        .iter()
+        .filter(|t| {
+            let id = t["id"].as_u64().unwrap_or_default();
+            let time = t["time"].as_i64().unwrap_or_default();
+            t["symbol"] == symbol
+                && order_id.is_none_or(|order_id| t["orderId"] == order_id)
+                && from_id.is_none_or(|from_id| id >= from_id)
+                && start.is_none_or(|start| time >= start)
+                && end.is_none_or(|end| time <= end)
+        })
+        .collect::<Vec<_>>();
+    let skip = if from_id.is_none() && start.is_none() {
+        trades.len().saturating_sub(limit)
+    } else {
+        0
+    };
+    return Ok(trades.into_iter().skip(skip).take(limit).cloned().collect());
+}
+
+fn symbol<'a>(state: &'a MockState, params: &Params) -> Result<&'a MockSymbol, MockResponse> {
+    let symbol = params.required("symbol")?;
+    return state.symbol(symbol).ok_or_else(invalid_symbol);
+}
+
+/// Resolves the `symbol` or `symbols` parameter, defaulting to every listed symbol.
+fn requested_symbols<'a>(
+    state: &'a MockState,
+    params: &Params,
+) -> Result<Vec<&'a MockSymbol>, MockResponse> {
+    let names: Vec<String> = if let Some(symbols) = params.get("symbols") {
+        serde_json::from_str(symbols).map_err(|_| {
+            MockResponse::error(400, -1100, "Illegal characters found in 'symbols'.")
+        })?
+    } else if let Some(symbol) = params.get("symbol") {
+        vec![symbol.to_string()]
+    } else {
+        return Ok(state.symbols.iter().collect());
+    };
+
+    return names
+        .iter()
+        .map(|name| state.symbol(name).ok_or_else(invalid_symbol))
+        .collect();
+}
+
+fn is_open(order: &Value) -> bool {
+    return matches!(
+        order["status"].as_str(),
+        Some("NEW" | "PENDING_NEW" | "PARTIALLY_FILLED")
+    );
+}
+
+fn find_order(state: &MockState, params: &Params) -> Result<Option<usize>, MockResponse> {
+    let symbol = params.required("symbol")?;
+    let order_id: Option<u64> = params.parse_opt("orderId")?;
+    let client_order_id = params.get("origClientOrderId");
+    if order_id.is_none() && client_order_id.is_none() {
+        return Err(MockResponse::error(
+            400,
+            -1102,
+            "Param 'origClientOrderId' or 'orderId' must be sent, but both were empty/null!",
+        ));
+    }
+
+    return Ok(state.orders.iter().position(|o| {
+        o["symbol"] == symbol
+            && order_id.is_none_or(|id| o["orderId"] == id)
+            && client_order_id.is_none_or(|id| o["clientOrderId"] == id)
+    }));
+}
+
+fn cancel_order(
+    order: &mut Value,
+    new_client_order_id: Option<&str>,
+    now: i64,
+) -> Result<Value, MockResponse> {
+    if !is_open(order) {
+        return Err(MockResponse::error(400, -2011, "Unknown order sent."));
+    }
+    order["status"] = json!("CANCELED");
+    order["updateTime"] = json!(now);
+
+    let mut response = order.clone();
+    response["origClientOrderId"] = order["clientOrderId"].clone();
+    if let Some(client_order_id) = new_client_order_id {
+        response["clientOrderId"] = json!(client_order_id);
+    }
+    return Ok(response);
+}
+
+/// Cancels `cancelOrderId` / `cancelOrigClientOrderId` and places the new
+/// order. Partial failures are reported in the `data` of a -2021 or -2022
+/// error like Binance does.
+fn cancel_replace_order(
+    state: &mut MockState,
+    params: &Params,
+    now: i64,
+) -> Result<Value, MockResponse> {
+    let mode = params.required("cancelReplaceMode")?;
+    if !matches!(mode, "STOP_ON_FAILURE" | "ALLOW_FAILURE") {
+        return Err(illegal_parameter("cancelReplaceMode"));
+    }
+    let symbol = params.required("symbol")?;
+    let order_id: Option<u64> = params.parse_opt("cancelOrderId")?;
+    let client_order_id = params.get("cancelOrigClientOrderId");
+    if order_id.is_none() && client_order_id.is_none() {
+        return Err(MockResponse::error(
+            400,
+            -1102,
+            "Param 'cancelOrigClientOrderId' or 'cancelOrderId' must be sent, but both were empty/null!",
+        ));
+    }
+    new_order(state, params, now, 0)?;
+
+    let required_status = match params.get("cancelRestrictions") {
+        Some("ONLY_NEW") => Some("NEW"),
+        Some("ONLY_PARTIALLY_FILLED") => Some("PARTIALLY_FILLED"),
+        Some(_) => return Err(illegal_parameter("cancelRestrictions")),
+        None => None,
+    };
+    let order = state.orders.iter_mut().find(|o| {
+        o["symbol"] == symbol
+            && order_id.is_none_or(|id| o["orderId"] == id)
+            && client_order_id.is_none_or(|id| o["clientOrderId"] == id)
+    });
+    let cancel_response = match order {
+        Some(order) if required_status.is_some_and(|status| order["status"] != status) => {
+            Err(json!({
+                "code": -2011,
+                "msg": "Order was not canceled due to cancel restrictions."
+            }))
+        }
+        Some(order) => cancel_order(order, params.get("cancelNewClientOrderId"), now)
+            .map_err(|response| serde_json::from_str(&response.body).unwrap_or_default()),
+        None => Err(json!({ "code": -2011, "msg": "Unknown order sent." })),
+    };
+
+    let (cancel_result, cancel_response) = match cancel_response {
+        Ok(response) => ("SUCCESS", response),
+        Err(error) => ("FAILURE", error),
+    };
+    if cancel_result == "FAILURE" && mode == "STOP_ON_FAILURE" {
+        return Err(cancel_replace_error(
+            400,
+            -2022,
+            "Order cancel-replace failed.",
+            &json!({
+                "cancelResult": "FAILURE",
+                "newOrderResult": "NOT_ATTEMPTED",
+                "cancelResponse": cancel_response,
+                "newOrderResponse": null,
+            }),
+        ));
+    }
+
+    let new_order_response = create_order(state, params, now, -1)?;
+    let result = json!({
+        "cancelResult": cancel_result,
+        "newOrderResult": "SUCCESS",
+        "cancelResponse": cancel_response,
+        "newOrderResponse": new_order_response,
+    });
+    if cancel_result == "FAILURE" {
+        return Err(cancel_replace_error(
+            409,
+            -2021,
+            "Order cancel-replace partially failed.",
+            &result,
+        ));
+    }
+    return Ok(result);
+}
+
+fn cancel_replace_error(status: u16, code: i32, msg: &str, data: &Value) -> MockResponse {
+    return MockResponse {
+        status,
+        headers: Vec::new(),
+        body: json!({ "code": code, "msg": msg, "data": data }).to_string(),
+    };
+}
+
+/// Reduces the quantity of an open order, keeping its id and time.
+fn amend_order(state: &mut MockState, params: &Params, now: i64) -> Result<Value, MockResponse> {
+    let new_qty: Decimal = params.parse_required("newQty")?;
+    let index = find_order(state, params)?
+        .ok_or_else(|| MockResponse::error(400, -2013, "Order does not exist."))?;
+    let order = &mut state.orders[index];
+    if !is_open(order) {
+        return Err(MockResponse::error(400, -2013, "Order does not exist."));
+    }
+
+    let quantity: Decimal = order["origQty"]
+        .as_str()
+        .and_then(|qty| qty.parse().ok())
+        .unwrap_or_default();
+    let executed: Decimal = order["executedQty"]
+        .as_str()
+        .and_then(|qty| qty.parse().ok())
+        .unwrap_or_default();
+    if new_qty <= executed || new_qty >= quantity {
+        return Err(MockResponse::error(
+            400,
+            -2038,
+            "Order amend (quantity increase) is not supported.",
+        ));
+    }
+
+    let orig_client_order_id = order["clientOrderId"].clone();
+    let client_order_id = params
+        .get("newClientOrderId")
+        .map_or_else(|| format!("mockamend{}", order["orderId"]), str::to_string);
+    order["origQty"] = json!(new_qty.to_string());
+    order["clientOrderId"] = json!(client_order_id);
+    order["updateTime"] = json!(now);
+
+    return Ok(json!({
+        "transactTime": now,
+        "executionId": now,
+        "amendedOrder": {
+            "symbol": order["symbol"],
+            "orderId": order["orderId"],
+            "orderListId": order["orderListId"],
+            "origClientOrderId": orig_client_order_id,
+            "clientOrderId": order["clientOrderId"],
+            "price": order["price"],
+            "qty": order["origQty"],
+            "executedQty": order["executedQty"],
+            "preventedQty": "0",
+            "quoteOrderQty": order["origQuoteOrderQty"],
+            "cumulativeQuoteQty": order["cummulativeQuoteQty"],
+            "status": order["status"],
+            "timeInForce": order["timeInForce"],
+            "type": order["type"],
+            "side": order["side"],
+            "workingTime": order["workingTime"],
+            "selfTradePreventionMode": order["selfTradePreventionMode"],
+        },
+    }));
+}
+
+fn create_order(
+    state: &mut MockState,
+    params: &Params,
+    now: i64,
+    order_list_id: i64,
+) -> Result<Value, MockResponse> {
+    let mut order = new_order(state, params, now, state.next_order_id)?;
+    order["orderListId"] = json!(order_list_id);
+    if order["status"] == "FILLED" {
+        let quantity = params.parse_opt("quantity")?;
+        let executed = order["executedQty"].as_str().and_then(|q| q.parse().ok());
+        let price = symbol(state, params)?.price;
+        state.push_trade(
+            &order,
+            price,
+            executed.or(quantity).unwrap_or_default(),
+            now,
+        );
+    }
+    let execution_type = if order["status"] == "FILLED" {
+        "TRADE"
+    } else {
+        "NEW"
+    };
+    state.push_user_event(fixtures::execution_report(&order, execution_type, now));
+    state.next_order_id += 1;
+    state.orders.push(order.clone());
+    return Ok(order);
+}
+
+/// Validates an order like `create_order` without placing it.
+fn test_order(state: &MockState, params: &Params, now: i64) -> Result<Value, MockResponse> {
+    new_order(state, params, now, 0)?;
+    if params.get("computeCommissionRates") != Some("true") {
+        return Ok(json!({}));
+    }
+    return Ok(json!({
+        "standardCommissionForOrder": { "maker": "0.00100000", "taker": "0.00100000" },
+        "specialCommissionForOrder": { "maker": "0.00000000", "taker": "0.00000000" },
+        "taxCommissionForOrder": { "maker": "0.00000000", "taker": "0.00000000" },
+        "discount": {
+            "enabledForAccount": true,
+            "enabledForSymbol": true,
+            "discountAsset": "BNB",
+            "discount": "0.25000000"
+        }
+    }));
+}
+
+fn new_order(
+    state: &MockState,
+    params: &Params,
+    now: i64,
+    order_id: u64,
+) -> Result<Value, MockResponse> {
+    let symbol = symbol(state, params)?;
+    let side = params.required("side")?;
+    let type_ = params.required("type")?;
+    let time_in_force = params.get("timeInForce").unwrap_or("GTC");
+    let price: Decimal = params.parse_opt("price")?.unwrap_or_default();
+
+    let quantity = match (
+        params.parse_opt::<Decimal>("quantity")?,
+        params.parse_opt::<Decimal>("quoteOrderQty")?,
+    ) {
+        (Some(quantity), _) => quantity,
+        (None, Some(quote)) if type_ == "MARKET" => (quote / symbol.price).round_dp(8),
+        _ => params.required("quantity")?.parse().unwrap_or_default(),
+    };
+    if type_ != "MARKET" && price.is_zero() && params.get("stopPrice").is_none() {
+        params.required("price")?;
+    }
+
+    let status = match (type_, time_in_force) {
+        ("MARKET", _) => "FILLED",
+        ("LIMIT", "IOC" | "FOK") => "EXPIRED",
+        _ => "NEW",
+    };
+    let (executed_qty, quote_qty) = if status == "FILLED" {
+        (quantity, (quantity * symbol.price).round_dp(8))
+    } else {
+        (Decimal::ZERO, Decimal::ZERO)
+    };
+
+    let client_order_id = params
+        .get("newClientOrderId")
+        .map_or_else(|| format!("mock{order_id}"), str::to_string);
+
+    let order = json!({
+        "symbol": symbol.symbol,
+        "orderId": order_id,
+        "orderListId": -1,
+        "clientOrderId": client_order_id,
+        "transactTime": now,
+        "time": now,
+        "updateTime": now,
+        "workingTime": now,
+        "price": price.to_string(),
+        "origQty": quantity.to_string(),
+        "executedQty": executed_qty.to_string(),
+        "origQuoteOrderQty": params.get("quoteOrderQty").unwrap_or("0"),
+        "cummulativeQuoteQty": quote_qty.to_string(),
+        "status": status,
+        "timeInForce": time_in_force,
+        "type": type_,
+        "side": side,
+        "stopPrice": params.get("stopPrice").unwrap_or("0"),
+        "isWorking": true,
+        "selfTradePreventionMode": params.get("selfTradePreventionMode").unwrap_or("NONE"),
+    });
+    return Ok(order);
+}
+
+/// The parameters of one order of a list, read from the parameters with the
+/// first of `prefixes` that is set (e.g. `pendingAboveType`, `pendingSide`),
+/// falling back to the unprefixed parameters shared by the list.
+fn leg_params(params: &Params, prefixes: &[&str]) -> Params {
+    let mut leg = Vec::new();
+    for name in [
+        "symbol",
+        "side",
+        "quantity",
+        "type",
+        "price",
+        "stopPrice",
+        "trailingDelta",
+        "timeInForce",
+        "icebergQty",
+        "clientOrderId",
+    ] {
+        let capitalized = format!("{}{}", name[..1].to_uppercase(), &name[1..]);
+        let Some(value) = prefixes
+            .iter()
+            .find_map(|prefix| params.get(&format!("{prefix}{capitalized}")))
+            .or_else(|| params.get(name))
+        else {
+            continue;
+        };
+        let name = if name == "clientOrderId" {
+            "newClientOrderId"
+        } else {
+            name
+        };
+        leg.push((name.to_string(), value.to_string()));
+    }
+    return Params(leg);
+}
+
+/// Places the orders of an order list, one per list of leg prefixes. Every
+/// leg is validated before any order is placed. Pending orders wait for the
+/// working order to be filled.
+fn create_order_list(
+    state: &mut MockState,
+    params: &Params,
+    now: i64,
+    contingency_type: &str,
+    legs: &[&[&str]],
+) -> Result<Value, MockResponse> {
+    let pending = legs
+        .iter()
+        .map(|prefixes| prefixes[0].starts_with("pending"))
+        .collect::<Vec<_>>();
+    let legs = legs
+        .iter()
+        .map(|prefixes| leg_params(params, prefixes))
+        .collect::<Vec<_>>();
+    for leg in &legs {
+        new_order(state, leg, now, 0)?;
+    }
+
+    let order_list_id = state.next_order_list_id;
+    state.next_order_list_id += 1;
+    let mut reports = Vec::new();
+    for (leg, pending) in legs.iter().zip(pending) {
+        let mut order = new_order(state, leg, now, state.next_order_id)?;
+        state.next_order_id += 1;
+        order["orderListId"] = json!(order_list_id);
+        if pending {
+            order["status"] = json!("PENDING_NEW");
+        }
+        state.orders.push(order.clone());
+        reports.push(order);
+    }
+
+    let list = json!({
+        "orderListId": order_list_id,
+        "contingencyType": contingency_type,
+        "listStatusType": "EXEC_STARTED",
+        "listOrderStatus": "EXECUTING",
+        "listClientOrderId": params
+            .get("listClientOrderId")
+            .map_or_else(|| format!("mocklist{order_list_id}"), str::to_string),
+        "transactionTime": now,
+        "symbol": params.required("symbol")?,
+        "orders": reports
+            .iter()
+            .map(|o| json!({
+                "symbol": o["symbol"],
+                "orderId": o["orderId"],
+                "clientOrderId": o["clientOrderId"],
+            }))
+            .collect::<Vec<_>>(),
+    });
+    state.order_lists.push(list.clone());
+
+    let mut response = list;
+    response["orderReports"] = Value::Array(reports);
+    return Ok(response);
+}
+
+fn find_order_list(
+    state: &MockState,
+    params: &Params,
+    client_id_param: &str,
+) -> Result<Option<usize>, MockResponse> {
+    let order_list_id: Option<i64> = params.parse_opt("orderListId")?;
+    let client_id = params.get(client_id_param);
+    if order_list_id.is_none() && client_id.is_none() {
+        return Err(MockResponse::error(
+            400,
+            -1102,
+            &format!(
+                "Param '{client_id_param}' or 'orderListId' must be sent, but both were empty/null!"
+            ),
+        ));
+    }
+
+    return Ok(state.order_lists.iter().position(|l| {
+        params.get("symbol").is_none_or(|s| l["symbol"] == s)
+            && order_list_id.is_none_or(|id| l["orderListId"] == id)
+            && client_id.is_none_or(|id| l["listClientOrderId"] == id)
+    }));
+}
+
+fn cancel_order_list(
+    state: &mut MockState,
+    index: usize,
+    params: &Params,
+    now: i64,
+) -> Result<Value, MockResponse> {
+    let list = &mut state.order_lists[index];
+    if list["listOrderStatus"] != "EXECUTING" {
+        return Err(MockResponse::error(400, -2011, "Unknown order sent."));
+    }
+    list["listStatusType"] = json!("ALL_DONE");
+    list["listOrderStatus"] = json!("ALL_DONE");
+    list["transactionTime"] = json!(now);
+    let mut response = list.clone();
+
+    let order_list_id = response["orderListId"].clone();
+    let reports = state
+        .orders
+        .iter_mut()
+        .filter(|o| o["orderListId"] == order_list_id && is_open(o))
+        .map(|o| cancel_order(o, params.get("newClientOrderId"), now))
+        .collect::<Result<Vec<_>, _>>()?;
+    response["orderReports"] = Value::Array(reports);
+    return Ok(response);
+}
+
+async fn handle_stream(
+    State(state): State<SharedState>,
+    Path(stream): Path<String>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let is_listen_key = lock(&state).listen_keys.contains(&stream);
+    if is_listen_key {
+        return ws.on_upgrade(move |socket| serve_user_stream(state, stream, socket));
+    }
+    return ws.on_upgrade(move |socket| serve_stream(state, stream, socket));
+}
+
+/// Forwards the user data events published by the server until the listen
+/// key expires or the client disconnects.
+async fn serve_user_stream(state: SharedState, listen_key: String, mut socket: WebSocket) {
+    let mut events = lock(&state).user_events.subscribe();
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                let expired = event["e"] == "listenKeyExpired";
+                if expired && event["listenKey"] != listen_key.as_str() {
+                    continue;
+                }
+                if socket.send(Message::Text(event.to_string().into())).await.is_err() || expired {
+                    return;
+                }
+            }
+            message = socket.recv() => {
+                if !matches!(message, Some(Ok(_))) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Generates the next event of `stream` (`<symbol>@<stream>`), if the symbol
+/// is listed and the stream is supported.
+fn stream_event(state: &SharedState, stream: &str, seq: u64) -> Option<Value> {
+    let (symbol, kind) = stream.split_once('@')?;
+    let mut state = lock(state);
+    let now = state.server_time();
+    if let Some(kind) = symbol.strip_prefix('!') {
+        // All market streams (`!<stream>@arr`) push an array of the events
+        // of every symbol.
+        let events = state
+            .symbols
+            .iter()
+            .map(|s| fixtures::stream_event(s, kind, seq, now))
+            .collect::<Option<Vec<_>>>()?;
+        return Some(Value::Array(events));
+    }
+    let symbol = symbol.to_uppercase();
+    if kind == "depth" || kind == "depth@100ms" {
+        state.symbol(&symbol)?;
+        let (first, last) = state.advance_depth(&symbol, 3);
+        return Some(fixtures::depth_update(
+            state.symbol(&symbol)?,
+            first,
+            last,
+            seq,
+            now,
+        ));
+    }
+    let partial_levels = kind
+        .strip_prefix("depth")
+        .and_then(|levels| levels.trim_end_matches("@100ms").parse().ok());
+    if let Some(levels) = partial_levels {
+        let symbol = state.symbol(&symbol)?;
+        let last_update_id = state.depth_update_id(&symbol.symbol);
+        return Some(fixtures::order_book(symbol.price, levels, last_update_id));
+    }
+    return state
+        .symbol(&symbol)
+        .and_then(|s| fixtures::stream_event(s, kind, seq, now));
+}
+
+/// Pushes generated events of a raw stream (`<symbol>@<stream>`) every 100ms.
+async fn serve_stream(state: SharedState, stream: String, mut socket: WebSocket) {
+    let mut disconnect = lock(&state).stream_disconnect.subscribe();
+    let mut interval = tokio::time::interval(Duration::from_millis(100));
+    for seq in 1.. {
+        tokio::select! {
+            _ = disconnect.recv() => {
+                let _ = socket.send(Message::Close(None)).await;
+                return;
+            }
+            _ = interval.tick() => {}
+        }
+        let Some(event) = stream_event(&state, &stream, seq) else {
+            return;
+        };
+        if socket
+            .send(Message::Text(event.to_string().into()))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+async fn handle_combined_stream(
+    State(state): State<SharedState>,
+    RawQuery(query): RawQuery,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let query = query.unwrap_or_default();
+    let streams = url::form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == "streams")
+        .map(|(_, streams)| {
+            streams
+                .split('/')
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    lock(&state).requests.push(RecordedRequest {
+        method: Method::GET,
+        path: "stream".to_string(),
+        query,
+        api_key: None,
+    });
+    return ws.on_upgrade(move |socket| serve_combined_stream(state, streams, socket));
+}
+
+/// Pushes the events of every subscribed stream in `{stream, data}` envelopes
+/// every 100ms and answers the `SUBSCRIBE`, `UNSUBSCRIBE` and
+/// `LIST_SUBSCRIPTIONS` methods.
+///
+/// Like Binance, the connection is closed when it receives more than 5
+/// messages per second.
+async fn serve_combined_stream(
+    state: SharedState,
+    mut streams: Vec<String>,
+    mut socket: WebSocket,
+) {
+    let mut disconnect = lock(&state).stream_disconnect.subscribe();
+    let mut interval = tokio::time::interval(Duration::from_millis(100));
+    let mut received: VecDeque<Instant> = VecDeque::new();
+    let mut seq = 0;
+    loop {
+        let outgoing = tokio::select! {
+            _ = disconnect.recv() => {
+                let _ = socket.send(Message::Close(None)).await;
+                return;
+            }
+            _ = interval.tick() => {
+                seq += 1;
+                streams
+                    .iter()
+                    .filter_map(|stream| {
+                        let event = stream_event(&state, stream, seq)?;
+                        Some(json!({ "stream": stream, "data": event }))
+                    })
+                    .collect()
+            }
+            message = socket.recv() => {
+                let Some(Ok(message)) = message else {
+                    return;
+                };
+                let Message::Text(text) = message else {
+                    continue;
+                };
+                let now = Instant::now();
+                received.retain(|t| now.duration_since(*t) < Duration::from_secs(1));
+                received.push_back(now);
+                if received.len() > MAX_STREAM_MESSAGES_PER_SECOND {
+                    let _ = socket.send(Message::Close(None)).await;
+                    return;
+                }
+                vec![stream_method(&state, &text, &mut streams)]
+            }
+        };
+        for message in outgoing {
+            if socket
+                .send(Message::Text(message.to_string().into()))
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+/// Handles a `SUBSCRIBE`, `UNSUBSCRIBE` or `LIST_SUBSCRIPTIONS` message.
+fn stream_method(state: &SharedState, text: &str, streams: &mut Vec<String>) -> Value {
+    let request: Value = serde_json::from_str(text).unwrap_or_default();
+    let method = request["method"].as_str().unwrap_or_default();
+    let params = request["params"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    lock(state).requests.push(RecordedRequest {
+        method: Method::GET,
+        path: method.to_string(),
+        query: url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(params.iter().map(|stream| ("params", stream)))
+            .finish(),
+        api_key: None,
+    });
+
+    let result = match method {
+        "SUBSCRIBE" if params.iter().any(|stream| !stream.contains('@')) => {
+            return json!({
+                "error": { "code": 2, "msg": "Invalid request: invalid stream name" },
+                "id": request["id"],
+            });
+        }
+        "SUBSCRIBE" => {
+            for stream in params {
+                if !streams.contains(&stream) {
+                    streams.push(stream);
+                }
+            }
+            Value::Null
+        }
+        "UNSUBSCRIBE" => {
+            streams.retain(|stream| !params.contains(stream));
+            Value::Null
+        }
+        "LIST_SUBSCRIPTIONS" => json!(streams),
+        _ => {
+            return json!({
+                "error": { "code": 2, "msg": "Invalid request: unknown variant" },
+                "id": request["id"],
+            });
+        }
+    };
+    return json!({ "result": result, "id": request["id"] });
+}
+
+/// Maps the WebSocket API methods to the REST endpoints implementing them.
+fn ws_api_endpoint(method: &str) -> Option<(Method, &'static str)> {
+    return match method {
+        "order.place" => Some((Method::POST, "order")),
+        "order.status" => Some((Method::GET, "order")),
+        "order.cancel" => Some((Method::DELETE, "order")),
+        "order.cancelReplace" => Some((Method::POST, "order/cancelReplace")),
+        "openOrders.status" => Some((Method::GET, "openOrders")),
+        "account.status" => Some((Method::GET, "account")),
+        _ => None,
+    };
+}
+
+async fn handle_ws_api(State(state): State<SharedState>, ws: WebSocketUpgrade) -> Response {
+    return ws.on_upgrade(move |socket| serve_ws_api(state, socket));
+}
+
+/// Answers WebSocket API requests until the client disconnects or
+/// [`super::MockServer::disconnect_ws_api`] is called.
+async fn serve_ws_api(state: SharedState, mut socket: WebSocket) {
+    let mut disconnect = lock(&state).ws_api_disconnect.subscribe();
+    let mut logged_on = false;
+    loop {
+        tokio::select! {
+            _ = disconnect.recv() => {
+                let _ = socket.send(Message::Close(None)).await;
+                return;
+            }
+            message = socket.recv() => {
+                let Some(Ok(message)) = message else {
+                    return;
+                };
+                let Message::Text(text) = message else {
+                    continue;
+                };
+                let Some(response) = ws_api_request(&state, &text, &mut logged_on) else {
+                    continue;
+                };
+                if socket.send(Message::Text(response.to_string().into())).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Handles a WebSocket API request with the REST handlers, once the session
+/// is authenticated with `session.logon` or when the request is signed.
+/// Returns `None` for requests of ignored methods.
+fn ws_api_request(state: &SharedState, text: &str, logged_on: &mut bool) -> Option<Value> {
+    let request: Value = serde_json::from_str(text).unwrap_or_default();
+    let method = request["method"].as_str().unwrap_or_default();
+    let params = request["params"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(key, value)| {
+            let value = value
+                .as_str()
+                .map_or_else(|| value.to_string(), str::to_string);
+            (key.clone(), value)
+        })
+        .collect::<Vec<_>>();
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(&params)
+        .finish();
+    let params = Params(params);
+    let endpoint = ws_api_endpoint(method);
+
+    let mut state = lock(state);
+    state.requests.push(RecordedRequest {
+        method: endpoint
+            .as_ref()
+            .map_or(Method::POST, |(method, _)| method.clone()),
+        path: method.to_string(),
+        query,
+        api_key: params.get("apiKey").map(str::to_string),
+    });
+    if state.ws_api_ignored.contains(method) {
+        return None;
+    }
+    let result = match endpoint {
+        _ if method == "session.logon" => {
+            let result = logon(&state, &params);
+            *logged_on |= result.is_ok();
+            result
+        }
+        Some((method, path)) => authorize(&state, &params, *logged_on)
+            .and_then(|()| verify_timestamp(&state, &params))
+            .and_then(|()| {
+                let request = Request {
+                    method,
+                    path,
+                    query: "",
+                    body: "",
+                    api_key: None,
+                    params,
+                };
+                dispatch_signed(&mut state, &request)
+            }),
+        None => Err(MockResponse::error(400, -1000, "Unknown method.")),
+    };
+    drop(state);
+
+    return Some(match result {
+        Ok(result) => json!({ "id": request["id"], "status": 200, "result": result }),
+        Err(response) => json!({
+            "id": request["id"],
+            "status": response.status,
+            "error": serde_json::from_str::<Value>(&response.body).unwrap_or_default(),
+        }),
+    });
+}
+
+/// Checks that a WebSocket API request is sent on a session that is logged on
+/// or is signed itself.
+fn authorize(state: &MockState, params: &Params, logged_on: bool) -> Result<(), MockResponse> {
+    if logged_on {
+        return Ok(());
+    }
+    if params.get("signature").is_none() {
+        return Err(MockResponse::error(
+            401,
+            -1002,
+            "You are not authorized to execute this request.",
+        ));
+    }
+    return verify_ws_signature(state, params);
+}
+
+/// Authenticates a WebSocket API session.
+fn logon(state: &MockState, params: &Params) -> Result<Value, MockResponse> {
+    verify_ws_signature(state, params)?;
+    verify_timestamp(state, params)?;
+
+    let now = state.server_time();
+    return Ok(json!({
+        "apiKey": state.api_key,
+        "authorizedSince": now,
+        "connectedSince": now,
+        "returnRateLimits": true,
+        "serverTime": now,
+    }));
+}
+
+/// Checks the API key and signature of a WebSocket API request; the
+/// signature covers the other parameters sorted by name.
+fn verify_ws_signature(state: &MockState, params: &Params) -> Result<(), MockResponse> {
+    if params.get("apiKey") != Some(state.api_key.as_str()) {
+        return Err(MockResponse::error(
+            401,
+            -2015,
+            "Invalid API-key, IP, or permissions for action.",
+        ));
+    }
+    let signature = params.required("signature")?;
+    let mut pairs = params
+        .0
+        .iter()
+        .filter(|(key, _)| key != "signature")
+        .collect::<Vec<_>>();
+    pairs.sort();
+    let payload = pairs
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("&");
+    if state.signer.sign(&payload) != signature {
+        return Err(MockResponse::error(
+            400,
+            -1022,
+            "Signature for this request is not valid.",
+        ));
+    }
+    return Ok(());
+}
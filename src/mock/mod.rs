@@ -0,0 +1,639 @@
+//! A local stand-in for the Binance REST API, WebSocket API and market data streams.
+//!
+//! [`MockServer`] binds to a random local port and serves the endpoints used by
+//! [`BinanceClient`] from an in-memory exchange: market data is generated around
+//! a configurable price per symbol, orders are stored and can be queried or
+//! canceled, and signed requests are verified exactly like Binance does.
+//! Responses can be scripted per endpoint to inject errors, rate limits or bans.
+//!
+//! Available with the `mock` feature.
+
+mod fixtures;
+mod handlers;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use reqwest::Method;
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use url::Url;
+
+use crate::BinanceClient;
+use crate::signer::{HmacSigner, Signer};
+
+/// The API key accepted by [`MockServer::start`].
+pub const MOCK_API_KEY: &str = "mock_api_key";
+
+/// The secret accepted by [`MockServer::start`].
+pub const MOCK_SECRET: &str = "mock_secret";
+
+/// A scripted HTTP response returned by [`MockServer`] instead of the default one.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockResponse {
+    #[must_use]
+    pub fn json(body: &Value) -> Self {
+        return Self {
+            status: 200,
+            headers: Vec::new(),
+            body: body.to_string(),
+        };
+    }
+
+    /// A Binance error payload (`{"code": ..., "msg": ...}`) with the given HTTP status.
+    #[must_use]
+    pub fn error(status: u16, code: i32, msg: &str) -> Self {
+        return Self {
+            status,
+            headers: Vec::new(),
+            body: json!({ "code": code, "msg": msg }).to_string(),
+        };
+    }
+
+    /// An HTTP 429 response asking the client to back off for `retry_after` seconds.
+    #[must_use]
+    pub fn rate_limited(retry_after: u64) -> Self {
+        return Self::error(
+            429,
+            -1003,
+            "Too much request weight used; please use WebSocket Streams for live updates to avoid polling the API.",
+        )
+        .with_header("Retry-After", &retry_after.to_string());
+    }
+
+    /// An HTTP 418 response telling the client that its IP has been banned for `retry_after` seconds.
+    #[must_use]
+    pub fn banned(retry_after: u64) -> Self {
+        return Self::error(
+            418,
+            -1003,
+            "Way too much request weight used; IP banned until further notice.",
+        )
+        .with_header("Retry-After", &retry_after.to_string());
+    }
+
+    #[must_use]
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        return self;
+    }
+}
+
+/// A request received by [`MockServer`].
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: Method,
+    /// The endpoint path relative to `/api/v3/` (e.g. `ticker/price`), the
+    /// method of a WebSocket API request (e.g. `order.place`) or of a combined
+    /// stream message (e.g. `SUBSCRIBE`), or `stream` for combined stream
+    /// connections.
+    pub path: String,
+    /// The raw query string, including the signature of signed requests.
+    /// WebSocket API parameters are recorded in the same form.
+    pub query: String,
+    pub api_key: Option<String>,
+}
+
+impl RecordedRequest {
+    /// Returns the decoded value of the query parameter `name`.
+    #[must_use]
+    pub fn param(&self, name: &str) -> Option<String> {
+        return url::form_urlencoded::parse(self.query.as_bytes())
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned());
+    }
+}
+
+/// A symbol listed on the mock exchange.
+#[derive(Debug, Clone)]
+pub struct MockSymbol {
+    pub symbol: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    /// The price around which market data is generated and market orders are filled.
+    pub price: Decimal,
+    /// The trading status reported by the exchange information, e.g. `TRADING`.
+    pub status: String,
+}
+
+impl MockSymbol {
+    #[must_use]
+    pub fn new(base_asset: &str, quote_asset: &str, price: Decimal) -> Self {
+        return Self {
+            symbol: format!("{base_asset}{quote_asset}"),
+            base_asset: base_asset.to_string(),
+            quote_asset: quote_asset.to_string(),
+            price,
+            status: "TRADING".to_string(),
+        };
+    }
+}
+
+#[derive(Debug)]
+struct MockBalance {
+    asset: String,
+    free: Decimal,
+    locked: Decimal,
+}
+
+#[derive(Debug)]
+struct MockState {
+    api_key: String,
+    signer: Arc<dyn Signer>,
+    /// Milliseconds added to the local clock to get the server time.
+    time_offset: i64,
+    symbols: Vec<MockSymbol>,
+    balances: Vec<MockBalance>,
+    orders: Vec<Value>,
+    next_order_id: u64,
+    order_lists: Vec<Value>,
+    next_order_list_id: i64,
+    trades: Vec<Value>,
+    listen_keys: Vec<String>,
+    /// Events published to every connected user data stream.
+    user_events: broadcast::Sender<Value>,
+    /// Closes every WebSocket API connection when sent to.
+    ws_api_disconnect: broadcast::Sender<()>,
+    /// WebSocket API methods whose requests are recorded but not answered.
+    ws_api_ignored: HashSet<String>,
+    /// Closes every market data stream connection when sent to.
+    stream_disconnect: broadcast::Sender<()>,
+    /// The last update id of the order book of each symbol, advanced by the
+    /// depth streams.
+    depth_update_ids: HashMap<String, u64>,
+    scripted: HashMap<(Method, String), VecDeque<MockResponse>>,
+    requests: Vec<RecordedRequest>,
+}
+
+impl MockState {
+    fn server_time(&self) -> i64 {
+        return chrono::Utc::now().timestamp_millis() + self.time_offset;
+    }
+
+    /// Publishes a user data event to the connected user data streams.
+    fn push_user_event(&self, event: Value) {
+        // Sending only fails when no stream is connected.
+        let _ = self.user_events.send(event);
+    }
+
+    /// Records a fill of the account, paying a 0.1% commission in BNB.
+    fn push_trade(&mut self, order: &Value, price: Decimal, qty: Decimal, time: i64) {
+        let commission = (price * qty / Decimal::from(600_000)).round_dp(8);
+        self.trades.push(json!({
+            "symbol": order["symbol"],
+            "id": self.trades.len() + 1,
+            "orderId": order["orderId"],
+            "orderListId": order["orderListId"],
+            "price": price.to_string(),
+            "qty": qty.to_string(),
+            "quoteQty": (price * qty).to_string(),
+            "commission": commission.to_string(),
+            "commissionAsset": "BNB",
+            "time": time,
+            "isBuyer": order["side"] == "BUY",
+            "isMaker": order["type"] != "MARKET",
+            "isBestMatch": true
+        }));
+    }
+
+    fn symbol(&self, symbol: &str) -> Option<&MockSymbol> {
+        return self.symbols.iter().find(|s| s.symbol == symbol);
+    }
+
+    fn depth_update_id(&self, symbol: &str) -> u64 {
+        return self.depth_update_ids.get(symbol).copied().unwrap_or(1_000);
+    }
+
+    /// Advances the order book of `symbol` by `count` updates, returning the
+    /// first and last of their ids.
+    fn advance_depth(&mut self, symbol: &str, count: u64) -> (u64, u64) {
+        let first = self.depth_update_id(symbol) + 1;
+        let last = first + count - 1;
+        self.depth_update_ids.insert(symbol.to_string(), last);
+        return (first, last);
+    }
+}
+
+type SharedState = Arc<Mutex<MockState>>;
+
+fn lock(state: &Mutex<MockState>) -> MutexGuard<'_, MockState> {
+    return state.lock().unwrap_or_else(PoisonError::into_inner);
+}
+
+/// A local HTTP and WebSocket server imitating Binance.
+///
+/// The server is stopped when the value is dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    api_key: String,
+    secret: String,
+    signer: Arc<dyn Signer>,
+    state: SharedState,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Starts a server accepting [`MOCK_API_KEY`] and [`MOCK_SECRET`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if no local port can be bound.
+    pub async fn start() -> Self {
+        return Self::with_credentials(MOCK_API_KEY, MOCK_SECRET).await;
+    }
+
+    /// Starts a server accepting the given API key and secret for signed requests.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no local port can be bound.
+    pub async fn with_credentials(api_key: &str, secret: &str) -> Self {
+        let signer = Arc::new(HmacSigner::new(secret.to_string()));
+        return Self::launch(api_key, secret, signer).await;
+    }
+
+    /// Starts a server verifying signed requests with `signer`, e.g. an
+    /// [`Ed25519Signer`](crate::signer::Ed25519Signer). The signature schemes
+    /// supported by Binance are deterministic, so the server simply signs the
+    /// request again and compares the results.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no local port can be bound.
+    pub async fn with_signer(api_key: &str, signer: impl Signer + 'static) -> Self {
+        return Self::launch(api_key, "", Arc::new(signer)).await;
+    }
+
+    async fn launch(api_key: &str, secret: &str, signer: Arc<dyn Signer>) -> Self {
+        let state = Arc::new(Mutex::new(MockState {
+            api_key: api_key.to_string(),
+            signer: signer.clone(),
+            time_offset: 0,
+            symbols: vec![
+                MockSymbol::new("BTC", "USDT", Decimal::from(85_000)),
+                MockSymbol::new("ETH", "USDT", Decimal::from(2_000)),
+                MockSymbol::new("BNB", "USDT", Decimal::from(600)),
+                MockSymbol::new("ETH", "BTC", Decimal::new(235, 4)),
+            ],
+            balances: vec![
+                MockBalance {
+                    asset: "BTC".to_string(),
+                    free: Decimal::ONE,
+                    locked: Decimal::ZERO,
+                },
+                MockBalance {
+                    asset: "USDT".to_string(),
+                    free: Decimal::from(10_000),
+                    locked: Decimal::ZERO,
+                },
+                MockBalance {
+                    asset: "BNB".to_string(),
+                    free: Decimal::ZERO,
+                    locked: Decimal::ZERO,
+                },
+            ],
+            orders: Vec::new(),
+            next_order_id: 1,
+            order_lists: Vec::new(),
+            next_order_list_id: 1,
+            trades: Vec::new(),
+            listen_keys: Vec::new(),
+            user_events: broadcast::channel(64).0,
+            ws_api_disconnect: broadcast::channel(1).0,
+            ws_api_ignored: HashSet::new(),
+            stream_disconnect: broadcast::channel(1).0,
+            depth_update_ids: HashMap::new(),
+            scripted: HashMap::new(),
+            requests: Vec::new(),
+        }));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind mock server");
+        let addr = listener.local_addr().expect("mock server has no address");
+        let router = handlers::router(state.clone());
+        let task = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                log::error!("Mock server stopped: {e}");
+            }
+        });
+
+        return Self {
+            addr,
+            api_key: api_key.to_string(),
+            secret: secret.to_string(),
+            signer,
+            state,
+            task,
+        };
+    }
+
+    /// The root URL of the server, suitable for [`crate::BinanceClientBuilder::base_url`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bound address does not form a valid URL.
+    #[must_use]
+    pub fn url(&self) -> Url {
+        return Url::parse(&format!("http://{}/", self.addr)).unwrap();
+    }
+
+    /// The versioned REST API URL (`<url>/api/v3/`) expected by the sub-clients.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bound address does not form a valid URL.
+    #[must_use]
+    pub fn api_url(&self) -> Url {
+        return self.url().join("api/v3/").unwrap();
+    }
+
+    /// The root URL of the market data streams, suitable for
+    /// [`crate::BinanceClientBuilder::stream_url`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bound address does not form a valid URL.
+    #[must_use]
+    pub fn stream_url(&self) -> Url {
+        return Url::parse(&format!("ws://{}/", self.addr)).unwrap();
+    }
+
+    #[must_use]
+    pub fn api_key(&self) -> &str {
+        return &self.api_key;
+    }
+
+    /// The HMAC secret of the server, empty if it was started with a custom signer.
+    #[must_use]
+    pub fn secret(&self) -> &str {
+        return &self.secret;
+    }
+
+    #[must_use]
+    pub fn signer(&self) -> Arc<dyn Signer> {
+        return self.signer.clone();
+    }
+
+    /// Returns a [`BinanceClient`] whose REST and stream clients all point to this server.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the client cannot be built.
+    #[must_use]
+    pub fn client(&self) -> BinanceClient {
+        return BinanceClient::builder(self.api_key.clone(), self.secret.clone())
+            .signer(self.signer.clone())
+            .base_url(self.url().as_str())
+            .stream_url(self.stream_url().as_str())
+            .ws_api_url(self.stream_url().as_str())
+            .build()
+            .unwrap();
+    }
+
+    /// Queues `response` to be returned by the next `method` request to `path`
+    /// (relative to `/api/v3/`, e.g. `order` or `ticker/price`).
+    ///
+    /// Scripted responses are returned in the order they were pushed, before
+    /// signature checks, and fall back to the default behaviour once exhausted.
+    pub fn push_response(&self, method: Method, path: &str, response: MockResponse) {
+        lock(&self.state)
+            .scripted
+            .entry((method, path.to_string()))
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Shifts the server clock by `offset_ms` milliseconds relative to the local clock.
+    pub fn set_time_offset(&self, offset_ms: i64) {
+        lock(&self.state).time_offset = offset_ms;
+    }
+
+    /// Lists a new symbol, or replaces an existing one with the same name.
+    pub fn add_symbol(&self, symbol: MockSymbol) {
+        let mut state = lock(&self.state);
+        state.symbols.retain(|s| s.symbol != symbol.symbol);
+        state.symbols.push(symbol);
+    }
+
+    /// Delists `symbol`.
+    pub fn remove_symbol(&self, symbol: &str) {
+        lock(&self.state).symbols.retain(|s| s.symbol != symbol);
+    }
+
+    /// Sets the price of a listed symbol.
+    pub fn set_price(&self, symbol: &str, price: Decimal) {
+        let mut state = lock(&self.state);
+        if let Some(s) = state.symbols.iter_mut().find(|s| s.symbol == symbol) {
+            s.price = price;
+        }
+    }
+
+    /// Sets the trading status of a listed symbol, e.g. `BREAK`.
+    pub fn set_symbol_status(&self, symbol: &str, status: &str) {
+        let mut state = lock(&self.state);
+        if let Some(s) = state.symbols.iter_mut().find(|s| s.symbol == symbol) {
+            s.status = status.to_string();
+        }
+    }
+
+    /// Sets the account balance of `asset`.
+    pub fn set_balance(&self, asset: &str, free: Decimal, locked: Decimal) {
+        let mut state = lock(&self.state);
+        state.balances.retain(|b| b.asset != asset);
+        state.balances.push(MockBalance {
+            asset: asset.to_string(),
+            free,
+            locked,
+        });
+    }
+
+    /// Returns every request received so far, in order.
+    #[must_use]
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        return lock(&self.state).requests.clone();
+    }
+
+    /// Returns every order placed so far, as Binance order JSON.
+    #[must_use]
+    pub fn orders(&self) -> Vec<Value> {
+        return lock(&self.state).orders.clone();
+    }
+
+    /// Records a buy of `qty` at `price` on `symbol` at `time` (in
+    /// milliseconds), filling an order that is not otherwise tracked.
+    pub fn add_trade(&self, symbol: &str, price: Decimal, qty: Decimal, time: i64) {
+        let mut state = lock(&self.state);
+        let order = json!({
+            "symbol": symbol,
+            "orderId": state.next_order_id,
+            "orderListId": -1,
+            "side": "BUY",
+            "type": "LIMIT",
+        });
+        state.next_order_id += 1;
+        state.push_trade(&order, price, qty, time);
+    }
+
+    /// Sends `event` (e.g. a `balanceUpdate`) on every connected user data stream.
+    pub fn push_user_event(&self, event: &Value) {
+        lock(&self.state).push_user_event(event.clone());
+    }
+
+    /// Expires every listen key: a `listenKeyExpired` event is sent on their
+    /// streams, which are then closed.
+    pub fn expire_listen_keys(&self) {
+        let mut state = lock(&self.state);
+        let now = state.server_time();
+        for listen_key in std::mem::take(&mut state.listen_keys) {
+            state.push_user_event(json!({
+                "e": "listenKeyExpired",
+                "E": now.to_string(),
+                "listenKey": listen_key,
+            }));
+        }
+        drop(state);
+    }
+
+    /// The number of connected user data streams.
+    #[must_use]
+    pub fn user_stream_connections(&self) -> usize {
+        return lock(&self.state).user_events.receiver_count();
+    }
+
+    /// Closes every WebSocket API connection, as Binance does after 24 hours.
+    pub fn disconnect_ws_api(&self) {
+        // Sending only fails when no connection is open.
+        let _ = lock(&self.state).ws_api_disconnect.send(());
+    }
+
+    /// Records the WebSocket API requests of `method`, e.g. `order.place`,
+    /// without ever answering them.
+    pub fn ignore_ws_api_method(&self, method: &str) {
+        lock(&self.state).ws_api_ignored.insert(method.to_string());
+    }
+
+    /// Closes every market data stream connection, raw or combined.
+    pub fn disconnect_streams(&self) {
+        // Sending only fails when no connection is open.
+        let _ = lock(&self.state).stream_disconnect.send(());
+    }
+
+    /// Advances the order book of `symbol` by `count` updates that are never
+    /// streamed, so that its depth streams skip them.
+    pub fn skip_depth_updates(&self, symbol: &str, count: u64) {
+        lock(&self.state).advance_depth(symbol, count);
+    }
+
+    /// Returns the fills of the account, as Binance `myTrades` JSON.
+    #[must_use]
+    pub fn trades(&self) -> Vec<Value> {
+        return lock(&self.state).trades.clone();
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::BinanceError;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rejects_invalid_signature() {
+        let server = MockServer::start().await;
+        let client = BinanceClient::builder(MOCK_API_KEY.to_string(), "wrong".to_string())
+            .base_url(server.url().as_str())
+            .build()
+            .unwrap();
+
+        let result = client.account.get_account(None, None).await;
+        assert!(matches!(result, Err(BinanceError::Api(400, e)) if e.code == -1022));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_timestamp_outside_recv_window() {
+        let server = MockServer::start().await;
+        server.set_time_offset(-60_000);
+
+        // Sign the request by hand, since the clients stamp requests with the server time.
+        let query = format!("timestamp={}", chrono::Utc::now().timestamp_millis());
+        let signature = server.signer().sign(&query);
+        let response = reqwest::Client::new()
+            .get(server.api_url().join("account").unwrap())
+            .query(&[("timestamp", &query[10..]), ("signature", &signature)])
+            .header("X-MBX-APIKEY", MOCK_API_KEY)
+            .send()
+            .await
+            .unwrap();
+
+        let result = crate::request::parse_response::<Value>(response).await;
+        assert!(matches!(result, Err(BinanceError::Api(400, e)) if e.code == -1021));
+    }
+
+    #[tokio::test]
+    async fn test_scripted_responses() {
+        let server = MockServer::start().await;
+        server.push_response(Method::GET, "account", MockResponse::rate_limited(0));
+        server.push_response(
+            Method::GET,
+            "account",
+            MockResponse::error(
+                400,
+                -2015,
+                "Invalid API-key, IP, or permissions for action.",
+            ),
+        );
+        let client = server.client();
+
+        let result = client.account.get_account(None, None).await;
+        assert!(matches!(result, Err(BinanceError::Api(429, e)) if e.code == -1003));
+
+        let result = client.account.get_account(None, None).await;
+        assert!(matches!(result, Err(BinanceError::Api(400, e)) if e.code == -2015));
+
+        let account = client.account.get_account(None, None).await.unwrap();
+        assert!(!account.balances.is_empty());
+
+        // The first signed request is preceded by a server time sync.
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[0].path, "time");
+        assert_eq!(requests[1].path, "account");
+        assert_eq!(requests[1].api_key.as_deref(), Some(MOCK_API_KEY));
+        assert!(requests[1].param("signature").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_market_data_follows_price() {
+        let server = MockServer::start().await;
+        server.add_symbol(MockSymbol::new("SOL", "USDT", Decimal::from(150)));
+        let client = server.client();
+
+        let prices = client.market.get_ticker_price(&["SOLUSDT"]).await.unwrap();
+        assert_eq!(prices[0].price, Decimal::from(150));
+
+        server.set_price("SOLUSDT", Decimal::from(160));
+        let prices = client.market.get_ticker_price(&["SOLUSDT"]).await.unwrap();
+        assert_eq!(prices[0].price, Decimal::from(160));
+
+        server.remove_symbol("SOLUSDT");
+        assert!(client.market.get_ticker_price(&["SOLUSDT"]).await.is_err());
+    }
+}
@@ -0,0 +1,437 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard};
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use rust_decimal::Decimal;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::client::BinanceMarketClient;
+use crate::errors::BinanceError;
+use crate::types::events::{DepthUpdateEvent, UpdateSpeed};
+use crate::types::market::{Order, OrderBook};
+use crate::types::trading::OrderSide;
+use crate::websocket::{BinanceWebSocket, EventStream};
+
+/// The number of levels of the snapshots the books are synchronized from.
+const SNAPSHOT_LIMIT: u32 = 1000;
+
+const SNAPSHOT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// How a diff depth update relates to a local order book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateResult {
+    /// The update was applied.
+    Applied,
+    /// The update is already part of the book and was ignored.
+    Stale,
+    /// Updates between the book and this one are missing; the book must be
+    /// synchronized again.
+    Gap,
+}
+
+/// An order book kept up to date with diff depth updates.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocalOrderBook {
+    last_update_id: u64,
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+impl LocalOrderBook {
+    #[must_use]
+    pub fn from_snapshot(snapshot: &OrderBook) -> Self {
+        let levels = |orders: &[Order]| {
+            return orders
+                .iter()
+                .filter(|order| !order.quantity.is_zero())
+                .map(|order| (order.price, order.quantity))
+                .collect();
+        };
+        return Self {
+            last_update_id: snapshot.last_update_id,
+            bids: levels(&snapshot.bids),
+            asks: levels(&snapshot.asks),
+        };
+    }
+
+    /// The id of the last update applied to the book.
+    #[must_use]
+    pub const fn last_update_id(&self) -> u64 {
+        return self.last_update_id;
+    }
+
+    /// Applies `event` if it directly follows the last applied update.
+    pub fn apply(&mut self, event: &DepthUpdateEvent) -> UpdateResult {
+        if event.final_update_id <= self.last_update_id {
+            return UpdateResult::Stale;
+        }
+        if event.first_update_id > self.last_update_id + 1 {
+            return UpdateResult::Gap;
+        }
+        for (levels, orders) in [(&mut self.bids, &event.bids), (&mut self.asks, &event.asks)] {
+            for order in orders {
+                if order.quantity.is_zero() {
+                    levels.remove(&order.price);
+                } else {
+                    levels.insert(order.price, order.quantity);
+                }
+            }
+        }
+        self.last_update_id = event.final_update_id;
+        return UpdateResult::Applied;
+    }
+
+    /// The bids, from the highest price.
+    pub fn bids(&self) -> impl Iterator<Item = Order> + '_ {
+        return self
+            .bids
+            .iter()
+            .rev()
+            .map(|(&price, &quantity)| Order { price, quantity });
+    }
+
+    /// The asks, from the lowest price.
+    pub fn asks(&self) -> impl Iterator<Item = Order> + '_ {
+        return self
+            .asks
+            .iter()
+            .map(|(&price, &quantity)| Order { price, quantity });
+    }
+
+    #[must_use]
+    pub fn best_bid(&self) -> Option<Order> {
+        return self.bids().next();
+    }
+
+    #[must_use]
+    pub fn best_ask(&self) -> Option<Order> {
+        return self.asks().next();
+    }
+
+    /// The quantity offered at exactly `price` on the bid (`Buy`) or ask
+    /// (`Sell`) side, zero if there is no such level.
+    #[must_use]
+    pub fn depth_at(&self, side: &OrderSide, price: Decimal) -> Decimal {
+        let levels = match side {
+            OrderSide::Buy => &self.bids,
+            OrderSide::Sell => &self.asks,
+        };
+        return levels.get(&price).copied().unwrap_or_default();
+    }
+
+    /// The total quantity offered at `price` or better: bids at or above it,
+    /// or asks at or below it.
+    #[must_use]
+    pub fn cumulative_depth(&self, side: &OrderSide, price: Decimal) -> Decimal {
+        return match side {
+            OrderSide::Buy => self.bids.range(price..).map(|(_, quantity)| quantity).sum(),
+            OrderSide::Sell => self
+                .asks
+                .range(..=price)
+                .map(|(_, quantity)| quantity)
+                .sum(),
+        };
+    }
+}
+
+/// Maintains the full order book of a symbol from its diff depth stream.
+///
+/// Follows the synchronization procedure documented by Binance: updates are
+/// buffered while a snapshot is fetched, updates older than the snapshot are
+/// dropped, and the book is synchronized again from a new snapshot when an
+/// update is missing, e.g. after a reconnect.
+#[derive(Debug)]
+pub struct OrderBookManager {
+    symbol: String,
+    book: Arc<RwLock<Option<LocalOrderBook>>>,
+    updates: watch::Receiver<Option<u64>>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for OrderBookManager {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl OrderBookManager {
+    /// Starts maintaining the order book of `symbol`, fetching snapshots
+    /// with `market` and following the diff depth stream pushed at `speed`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn start(
+        market: BinanceMarketClient,
+        websocket: &BinanceWebSocket,
+        symbol: &str,
+        speed: UpdateSpeed,
+    ) -> Result<Self, BinanceError> {
+        let symbol = symbol.to_uppercase();
+        let diffs = websocket.diff_depth(&symbol, speed)?;
+        let book = Arc::new(RwLock::new(None));
+        let (updates_tx, updates) = watch::channel(None);
+        let task = tokio::spawn(maintain(
+            market,
+            symbol.clone(),
+            diffs,
+            book.clone(),
+            updates_tx,
+        ));
+        return Ok(Self {
+            symbol,
+            book,
+            updates,
+            task,
+        });
+    }
+
+    #[must_use]
+    pub fn symbol(&self) -> &str {
+        return &self.symbol;
+    }
+
+    /// Returns a receiver notified of every change of the book.
+    ///
+    /// Its value is the id of the last applied update, or `None` while the
+    /// book is being synchronized.
+    #[must_use]
+    pub fn updates(&self) -> watch::Receiver<Option<u64>> {
+        return self.updates.clone();
+    }
+
+    /// Returns a copy of the book, `None` while it is being synchronized.
+    #[must_use]
+    pub fn book(&self) -> Option<LocalOrderBook> {
+        return self.read().clone();
+    }
+
+    #[must_use]
+    pub fn best_bid(&self) -> Option<Order> {
+        return self.read().as_ref()?.best_bid();
+    }
+
+    #[must_use]
+    pub fn best_ask(&self) -> Option<Order> {
+        return self.read().as_ref()?.best_ask();
+    }
+
+    /// See [`LocalOrderBook::depth_at`].
+    #[must_use]
+    pub fn depth_at(&self, side: &OrderSide, price: Decimal) -> Option<Decimal> {
+        return Some(self.read().as_ref()?.depth_at(side, price));
+    }
+
+    /// See [`LocalOrderBook::cumulative_depth`].
+    #[must_use]
+    pub fn cumulative_depth(&self, side: &OrderSide, price: Decimal) -> Option<Decimal> {
+        return Some(self.read().as_ref()?.cumulative_depth(side, price));
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Option<LocalOrderBook>> {
+        return self.book.read().unwrap_or_else(PoisonError::into_inner);
+    }
+}
+
+/// Replaces the book, e.g. with a new snapshot, and publishes its update id.
+fn publish(
+    book: &RwLock<Option<LocalOrderBook>>,
+    updates: &watch::Sender<Option<u64>>,
+    local: Option<LocalOrderBook>,
+) {
+    let last_update_id = local.as_ref().map(LocalOrderBook::last_update_id);
+    *book.write().unwrap_or_else(PoisonError::into_inner) = local;
+    updates.send_replace(last_update_id);
+}
+
+/// Applies `update` to the book in place; a book being synchronized cannot
+/// take updates.
+fn apply(book: &RwLock<Option<LocalOrderBook>>, update: &DepthUpdateEvent) -> UpdateResult {
+    return book
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_mut()
+        .map_or(UpdateResult::Gap, |local| local.apply(update));
+}
+
+async fn maintain(
+    market: BinanceMarketClient,
+    symbol: String,
+    mut diffs: EventStream<DepthUpdateEvent>,
+    book: Arc<RwLock<Option<LocalOrderBook>>>,
+    updates: watch::Sender<Option<u64>>,
+) {
+    // Updates are buffered from the first one received until the snapshot
+    // covering it is fetched; they wait in the stream meanwhile.
+    let Some(mut first) = next_update(&mut diffs).await else {
+        return;
+    };
+    loop {
+        let snapshot = loop {
+            match market.get_depth(&symbol, Some(SNAPSHOT_LIMIT)).await {
+                Ok(snapshot) if snapshot.last_update_id >= first.first_update_id => {
+                    break snapshot;
+                }
+                Ok(_) => {
+                    log::debug!("The order book snapshot of {symbol} is older than its updates");
+                }
+                Err(e) => log::error!("Failed to fetch the order book of {symbol}: {e}"),
+            }
+            tokio::time::sleep(SNAPSHOT_RETRY_DELAY).await;
+        };
+
+        publish(
+            &book,
+            &updates,
+            Some(LocalOrderBook::from_snapshot(&snapshot)),
+        );
+        let mut update = first;
+        first = loop {
+            match apply(&book, &update) {
+                UpdateResult::Applied => {
+                    updates.send_replace(Some(update.final_update_id));
+                }
+                UpdateResult::Stale => {}
+                UpdateResult::Gap => {
+                    log::warn!(
+                        "Missed updates of the order book of {symbol} before {}, resynchronizing",
+                        update.first_update_id
+                    );
+                    publish(&book, &updates, None);
+                    break update;
+                }
+            }
+            let Some(next) = next_update(&mut diffs).await else {
+                return;
+            };
+            update = next;
+        };
+    }
+}
+
+/// Returns the next update of `diffs`, skipping events that cannot be parsed.
+async fn next_update(diffs: &mut EventStream<DepthUpdateEvent>) -> Option<DepthUpdateEvent> {
+    while let Some(event) = diffs.next().await {
+        match event {
+            Ok(update) => return Some(update),
+            Err(e) => log::error!("Invalid event on {}: {e}", diffs.name()),
+        }
+    }
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockServer;
+
+    fn order(price: i64, quantity: i64) -> Order {
+        return Order {
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+        };
+    }
+
+    fn update(first: u64, last: u64, bids: Vec<Order>, asks: Vec<Order>) -> DepthUpdateEvent {
+        return DepthUpdateEvent {
+            event_type: "depthUpdate".to_string(),
+            event_time: 0,
+            symbol: "BTCUSDT".to_string(),
+            first_update_id: first,
+            final_update_id: last,
+            bids,
+            asks,
+        };
+    }
+
+    #[test]
+    fn test_local_order_book() {
+        let snapshot = OrderBook {
+            last_update_id: 10,
+            bids: vec![order(99, 1), order(98, 2), order(97, 3)],
+            asks: vec![order(101, 1), order(102, 2)],
+        };
+        let mut book = LocalOrderBook::from_snapshot(&snapshot);
+        assert_eq!(book.best_bid(), Some(order(99, 1)));
+        assert_eq!(book.best_ask(), Some(order(101, 1)));
+
+        // Updates already in the snapshot are dropped.
+        let stale = update(5, 10, vec![order(99, 0)], Vec::new());
+        assert_eq!(book.apply(&stale), UpdateResult::Stale);
+        assert_eq!(book.best_bid(), Some(order(99, 1)));
+
+        // The first update may start before the snapshot.
+        let first = update(
+            8,
+            12,
+            vec![order(99, 0), order(100, 5)],
+            vec![order(102, 4)],
+        );
+        assert_eq!(book.apply(&first), UpdateResult::Applied);
+        assert_eq!(book.last_update_id(), 12);
+        assert_eq!(book.best_bid(), Some(order(100, 5)));
+        assert_eq!(
+            book.depth_at(&OrderSide::Buy, Decimal::from(99)),
+            Decimal::ZERO
+        );
+        assert_eq!(
+            book.depth_at(&OrderSide::Sell, Decimal::from(102)),
+            Decimal::from(4)
+        );
+        assert_eq!(
+            book.cumulative_depth(&OrderSide::Buy, Decimal::from(98)),
+            Decimal::from(7)
+        );
+        assert_eq!(
+            book.cumulative_depth(&OrderSide::Sell, Decimal::from(105)),
+            Decimal::from(5)
+        );
+        let bids = book.bids().map(|o| o.price).collect::<Vec<_>>();
+        assert_eq!(bids, [100, 98, 97].map(Decimal::from));
+
+        let gap = update(14, 15, vec![order(96, 1)], Vec::new());
+        assert_eq!(book.apply(&gap), UpdateResult::Gap);
+        assert_eq!(book.last_update_id(), 12);
+        assert_eq!(
+            book.depth_at(&OrderSide::Buy, Decimal::from(96)),
+            Decimal::ZERO
+        );
+    }
+
+    #[tokio::test]
+    async fn test_order_book_manager() {
+        let server = MockServer::start().await;
+        let client = server.client();
+        let manager = client.order_book("btcusdt", UpdateSpeed::Fast).unwrap();
+        assert_eq!(manager.symbol(), "BTCUSDT");
+        let mut updates = manager.updates();
+
+        let synced = *updates.wait_for(Option::is_some).await.unwrap();
+        let price = Decimal::from(85_000);
+        let best_bid = manager.best_bid().unwrap();
+        let best_ask = manager.best_ask().unwrap();
+        assert!(best_bid.price < price && price < best_ask.price);
+        assert_eq!(manager.book().unwrap().bids().count(), 1000);
+        assert!(
+            manager.cumulative_depth(&OrderSide::Buy, best_bid.price - Decimal::TEN)
+                > Some(best_bid.quantity)
+        );
+
+        // Missed updates make the manager synchronize from a new snapshot.
+        let skipped = synced.unwrap() + 100;
+        server.skip_depth_updates("BTCUSDT", 100);
+        updates
+            .wait_for(|id| id.is_some_and(|id| id > skipped))
+            .await
+            .unwrap();
+        let snapshots = server
+            .requests()
+            .iter()
+            .filter(|r| r.path == "depth")
+            .count();
+        assert_eq!(snapshots, 2);
+        assert!(manager.best_bid().unwrap().price < price);
+    }
+}
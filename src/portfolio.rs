@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+
+use crate::client::BinanceMarketClient;
+use crate::errors::BinanceError;
+use crate::symbol_registry::SymbolRegistry;
+use crate::types::account::Balance;
+use crate::types::general::SymbolStatus;
+
+/// The assets an asset without a pair with the quote asset is converted
+/// through, in order of preference.
+const DEFAULT_INTERMEDIATES: [&str; 3] = ["BTC", "ETH", "BNB"];
+
+/// The value of the balance of one asset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetValue {
+    pub asset: String,
+    /// The free and locked amount.
+    pub amount: Decimal,
+    /// The price of one unit in the quote asset.
+    pub price: Decimal,
+    pub value: Decimal,
+    /// The symbols the price was derived from, empty for the quote asset
+    /// itself.
+    pub route: Vec<String>,
+}
+
+/// The balances of an account valued in a quote asset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortfolioValue {
+    pub quote_asset: String,
+    /// The sum of the values of `assets`.
+    pub total: Decimal,
+    pub assets: Vec<AssetValue>,
+    /// The balances that could not be converted to the quote asset; they are
+    /// not part of the total.
+    pub unpriced: Vec<Balance>,
+}
+
+/// Values balances in a quote asset with the latest prices of the trading
+/// symbols.
+///
+/// An asset is converted with its pair with the quote asset, in either
+/// direction, or else through the first intermediate asset (BTC, ETH and
+/// BNB by default) that has pairs with both.
+#[derive(Debug, Clone)]
+pub struct PortfolioValuator {
+    market: BinanceMarketClient,
+    symbols: SymbolRegistry,
+    intermediates: Vec<String>,
+}
+
+impl PortfolioValuator {
+    /// Creates a valuator fetching prices with `market` and looking up the
+    /// pairs in `symbols`.
+    #[must_use]
+    pub fn new(market: BinanceMarketClient, symbols: SymbolRegistry) -> Self {
+        return Self {
+            market,
+            symbols,
+            intermediates: DEFAULT_INTERMEDIATES.map(ToString::to_string).to_vec(),
+        };
+    }
+
+    /// Sets the assets converted through when there is no direct pair, in
+    /// order of preference.
+    #[must_use]
+    pub fn with_intermediates(mut self, assets: &[&str]) -> Self {
+        self.intermediates = assets.iter().map(ToString::to_string).collect();
+        return self;
+    }
+
+    /// Values the non-zero `balances` in `quote_asset`, e.g. `USDT`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the symbols or prices cannot be fetched.
+    pub async fn value(
+        &self,
+        balances: &[Balance],
+        quote_asset: &str,
+    ) -> Result<PortfolioValue, BinanceError> {
+        let symbols = self.symbols.all().await?;
+        let prices: HashMap<String, Decimal> = self
+            .market
+            .get_ticker_price(&[])
+            .await?
+            .into_iter()
+            .map(|ticker| (ticker.symbol, ticker.price))
+            .collect();
+        let pairs: HashMap<(&str, &str), (&str, Decimal)> = symbols
+            .iter()
+            .filter(|symbol| symbol.status == SymbolStatus::Trading)
+            .filter_map(|symbol| {
+                let price = *prices.get(&symbol.symbol)?;
+                let pair = (symbol.base_asset.as_str(), symbol.quote_asset.as_str());
+                return (!price.is_zero()).then_some((pair, (symbol.symbol.as_str(), price)));
+            })
+            .collect();
+        let rates = Rates { pairs };
+
+        let mut portfolio = PortfolioValue {
+            quote_asset: quote_asset.to_string(),
+            total: Decimal::ZERO,
+            assets: Vec::new(),
+            unpriced: Vec::new(),
+        };
+        for balance in balances.iter().filter(|balance| !balance.is_zero()) {
+            let Some((price, route)) =
+                rates.route(&balance.asset, quote_asset, &self.intermediates)
+            else {
+                portfolio.unpriced.push(balance.clone());
+                continue;
+            };
+            let amount = balance.total();
+            let value = amount * price;
+            portfolio.total += value;
+            portfolio.assets.push(AssetValue {
+                asset: balance.asset.clone(),
+                amount,
+                price,
+                value,
+                route,
+            });
+        }
+        return Ok(portfolio);
+    }
+}
+
+/// The prices of the trading symbols, by base and quote asset.
+struct Rates<'a> {
+    pairs: HashMap<(&'a str, &'a str), (&'a str, Decimal)>,
+}
+
+impl Rates<'_> {
+    /// The price of `from` in `to` and the symbols it is derived from.
+    fn route(
+        &self,
+        from: &str,
+        to: &str,
+        intermediates: &[String],
+    ) -> Option<(Decimal, Vec<String>)> {
+        if from == to {
+            return Some((Decimal::ONE, Vec::new()));
+        }
+        if let Some((price, symbol)) = self.direct(from, to) {
+            return Some((price, vec![symbol.to_string()]));
+        }
+        return intermediates
+            .iter()
+            .filter(|via| *via != from && *via != to)
+            .find_map(|via| {
+                let (first_price, first) = self.direct(from, via)?;
+                let (second_price, second) = self.direct(via, to)?;
+                let route = vec![first.to_string(), second.to_string()];
+                return Some((first_price * second_price, route));
+            });
+    }
+
+    /// The price of `from` in `to` from their pair, in either direction.
+    fn direct(&self, from: &str, to: &str) -> Option<(Decimal, &str)> {
+        if let Some(&(symbol, price)) = self.pairs.get(&(from, to)) {
+            return Some((price, symbol));
+        }
+        let &(symbol, price) = self.pairs.get(&(to, from))?;
+        return Some((Decimal::ONE / price, symbol));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::{MockServer, MockSymbol};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_portfolio_value() {
+        let server = MockServer::start().await;
+        server.add_symbol(MockSymbol::new("SOL", "BTC", Decimal::new(2, 3)));
+        server.set_balance("ETH", Decimal::from(2), Decimal::ONE);
+        server.set_balance("SOL", Decimal::from(10), Decimal::ZERO);
+        server.set_balance("XYZ", Decimal::ONE, Decimal::ZERO);
+        let client = server.client();
+        let account = client.account.get_account(None, None).await.unwrap();
+        let valuator = client.portfolio_valuator();
+
+        let portfolio = valuator.value(&account.balances, "USDT").await.unwrap();
+        let values: HashMap<_, _> = portfolio
+            .assets
+            .iter()
+            .map(|asset| (asset.asset.as_str(), asset))
+            .collect();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values["USDT"].value, Decimal::from(10_000));
+        assert!(values["USDT"].route.is_empty());
+        assert_eq!(values["BTC"].value, Decimal::from(85_000));
+        assert_eq!(values["ETH"].amount, Decimal::from(3));
+        assert_eq!(values["ETH"].value, Decimal::from(6_000));
+        // There is no SOLUSDT pair; SOL is converted through BTC.
+        assert_eq!(values["SOL"].route, ["SOLBTC", "BTCUSDT"]);
+        assert_eq!(values["SOL"].value, Decimal::from(1_700));
+        assert_eq!(portfolio.total, Decimal::from(102_700));
+        assert_eq!(portfolio.unpriced.len(), 1);
+        assert_eq!(portfolio.unpriced[0].asset, "XYZ");
+
+        // Pairs are used in both directions.
+        let portfolio = valuator.value(&account.balances, "BTC").await.unwrap();
+        let usdt = portfolio.assets.iter().find(|a| a.asset == "USDT").unwrap();
+        assert_eq!(usdt.route, ["BTCUSDT"]);
+        assert_eq!(usdt.value.round_dp(8), Decimal::new(11_764_706, 8));
+
+        // Halted symbols are not used.
+        server.set_symbol_status("SOLBTC", "HALT");
+        client.symbols.refresh().await.unwrap();
+        let portfolio = valuator.value(&account.balances, "USDT").await.unwrap();
+        assert_eq!(portfolio.unpriced.len(), 2);
+        let valuator = valuator.with_intermediates(&[]);
+        let portfolio = valuator.value(&account.balances, "ETH").await.unwrap();
+        assert!(portfolio.assets.iter().all(|a| a.route.len() <= 1));
+    }
+}
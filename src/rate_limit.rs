@@ -0,0 +1,379 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use reqwest::Response;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+use crate::errors::BinanceError;
+use crate::types::general::{RateLimit, RateLimitInterval, RateLimitType};
+
+/// What the [`RateLimiter`] does with a request that would exceed a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RateLimitMode {
+    /// Wait until the limit window resets, then send the request.
+    #[default]
+    Wait,
+    /// Fail immediately with [`BinanceError::RateLimited`].
+    Reject,
+}
+
+/// Current usage of one of the limits tracked by a [`RateLimiter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitUsage {
+    pub rate_limit_type: RateLimitType,
+    pub interval: RateLimitInterval,
+    pub interval_num: u32,
+    pub limit: u32,
+    pub used: u32,
+}
+
+/// Tracks the request weight and order count limits of the REST API.
+///
+/// The limiter starts with the default spot limits and is updated from the
+/// `rateLimits` of [`ExchangeInfo`](crate::types::general::ExchangeInfo) and the
+/// `X-MBX-USED-WEIGHT-*` / `X-MBX-ORDER-COUNT-*` headers of every response.
+/// After a 429 or 418 response no request is sent until `Retry-After` has passed.
+///
+/// Clones share the same state, so a single limiter covers every sub-client of a
+/// [`BinanceClient`](crate::BinanceClient) and all of its clones.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    state: Arc<Mutex<LimiterState>>,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    mode: RateLimitMode,
+    counters: Vec<Counter>,
+    /// Unix time in milliseconds until which requests are blocked by `Retry-After`.
+    blocked_until: i64,
+}
+
+impl Default for LimiterState {
+    fn default() -> Self {
+        return Self {
+            mode: RateLimitMode::default(),
+            counters: default_limits().into_iter().map(Counter::new).collect(),
+            blocked_until: 0,
+        };
+    }
+}
+
+#[derive(Debug)]
+struct Counter {
+    limit: RateLimit,
+    used: u32,
+    window_start: i64,
+}
+
+impl Counter {
+    const fn new(limit: RateLimit) -> Self {
+        return Self {
+            limit,
+            used: 0,
+            window_start: 0,
+        };
+    }
+
+    /// Resets the counter if `now` is in a later window than the last request.
+    const fn roll(&mut self, now: i64) {
+        let window = self.limit.window_millis();
+        let start = now - now.rem_euclid(window);
+        if start != self.window_start {
+            self.window_start = start;
+            self.used = 0;
+        }
+    }
+
+    const fn window_end(&self) -> i64 {
+        return self.window_start + self.limit.window_millis();
+    }
+
+    fn cost(&self, weight: u32, is_order: bool) -> u32 {
+        return match self.limit.rate_limit_type {
+            RateLimitType::RequestWeight => weight,
+            RateLimitType::Orders => u32::from(is_order),
+            RateLimitType::RawRequests => 1,
+            RateLimitType::Unknown => 0,
+        };
+    }
+}
+
+/// The spot API limits used until exchange information has been loaded.
+fn default_limits() -> Vec<RateLimit> {
+    let limit = |rate_limit_type, interval, interval_num, limit| RateLimit {
+        rate_limit_type,
+        interval,
+        interval_num,
+        limit,
+    };
+    return vec![
+        limit(
+            RateLimitType::RequestWeight,
+            RateLimitInterval::Minute,
+            1,
+            6000,
+        ),
+        limit(RateLimitType::Orders, RateLimitInterval::Second, 10, 100),
+        limit(RateLimitType::Orders, RateLimitInterval::Day, 1, 200_000),
+        limit(
+            RateLimitType::RawRequests,
+            RateLimitInterval::Minute,
+            5,
+            61000,
+        ),
+    ];
+}
+
+fn now_millis() -> i64 {
+    return chrono::Utc::now().timestamp_millis();
+}
+
+impl RateLimiter {
+    #[must_use]
+    pub fn new(mode: RateLimitMode) -> Self {
+        let limiter = Self::default();
+        limiter.lock().mode = mode;
+        return limiter;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LimiterState> {
+        return self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+    }
+
+    /// Replaces the tracked limits, e.g. with the `rateLimits` of the exchange
+    /// information. The usage of limits that did not change is kept, and
+    /// limits of unknown types or intervals are ignored.
+    pub fn set_limits(&self, limits: &[RateLimit]) {
+        let mut state = self.lock();
+        let counters = limits
+            .iter()
+            .filter(|limit| limit.is_known())
+            .map(|limit| {
+                let previous = state.counters.iter().find(|counter| {
+                    counter.limit.rate_limit_type == limit.rate_limit_type
+                        && counter.limit.window_millis() == limit.window_millis()
+                });
+                return Counter {
+                    limit: limit.clone(),
+                    used: previous.map_or(0, |counter| counter.used),
+                    window_start: previous.map_or(0, |counter| counter.window_start),
+                };
+            })
+            .collect();
+        state.counters = counters;
+    }
+
+    /// Returns the usage of every tracked limit in the current windows.
+    #[must_use]
+    pub fn usage(&self) -> Vec<RateLimitUsage> {
+        let now = now_millis();
+        let mut state = self.lock();
+        return state
+            .counters
+            .iter_mut()
+            .map(|counter| {
+                counter.roll(now);
+                return RateLimitUsage {
+                    rate_limit_type: counter.limit.rate_limit_type,
+                    interval: counter.limit.interval,
+                    interval_num: counter.limit.interval_num,
+                    limit: counter.limit.limit,
+                    used: counter.used,
+                };
+            })
+            .collect();
+    }
+
+    /// Reserves `weight` (and one order if `is_order` is set) for a request,
+    /// waiting for the limit windows to reset if necessary.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BinanceError::RateLimited`] if the request would exceed a limit
+    /// and the limiter is in [`RateLimitMode::Reject`] mode.
+    pub async fn acquire(&self, weight: u32, is_order: bool) -> Result<(), BinanceError> {
+        loop {
+            let (mode, wait) = {
+                let mut state = self.lock();
+                let wait = state.try_acquire(now_millis(), weight, is_order);
+                (state.mode, wait)
+            };
+            let Some(wait) = wait else {
+                return Ok(());
+            };
+
+            if mode == RateLimitMode::Reject {
+                return Err(BinanceError::RateLimited(wait));
+            }
+            log::warn!("Rate limit reached, waiting {wait:?}");
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Updates the usage from the rate limit headers of a response and blocks
+    /// further requests for `Retry-After` seconds after a 429 or 418 response.
+    pub fn update(&self, response: &Response) {
+        self.update_from_headers(response.status().as_u16(), response.headers());
+    }
+
+    fn update_from_headers(&self, status: u16, headers: &HeaderMap) {
+        let now = now_millis();
+        let mut state = self.lock();
+        for counter in &mut state.counters {
+            let used = counter
+                .limit
+                .header_name()
+                .and_then(|name| headers.get(name))
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u32>().ok());
+            if let Some(used) = used {
+                counter.roll(now);
+                counter.used = counter.used.max(used);
+            }
+        }
+
+        if matches!(status, 429 | 418) {
+            let retry_after = headers
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<i64>().ok());
+            if let Some(seconds) = retry_after {
+                state.blocked_until = state.blocked_until.max(now + seconds * 1000);
+            }
+        }
+    }
+}
+
+impl LimiterState {
+    /// Reserves the request if no limit is exceeded, otherwise returns how long
+    /// to wait before trying again.
+    fn try_acquire(&mut self, now: i64, weight: u32, is_order: bool) -> Option<Duration> {
+        let mut wait_until = self.blocked_until;
+        for counter in &mut self.counters {
+            counter.roll(now);
+            let cost = counter.cost(weight, is_order);
+            // A request heavier than the whole limit can only be sent in an empty window.
+            if cost > 0
+                && counter.used > 0
+                && counter.used.saturating_add(cost) > counter.limit.limit
+            {
+                wait_until = wait_until.max(counter.window_end());
+            }
+        }
+
+        if wait_until > now {
+            let millis = (wait_until - now).cast_unsigned();
+            return Some(Duration::from_millis(millis));
+        }
+
+        for counter in &mut self.counters {
+            counter.used = counter.used.saturating_add(counter.cost(weight, is_order));
+        }
+        return None;
+    }
+}
+
+/// Request weight of `GET /api/v3/depth` for the given `limit`.
+pub(crate) const fn depth_weight(limit: Option<u32>) -> u32 {
+    return match limit {
+        None => 5,
+        Some(limit) if limit <= 100 => 5,
+        Some(limit) if limit <= 500 => 25,
+        Some(limit) if limit <= 1000 => 50,
+        Some(_) => 250,
+    };
+}
+
+/// Request weight of `GET /api/v3/ticker/24hr` for `count` symbols (0 for all).
+pub(crate) const fn ticker_24hr_weight(count: usize) -> u32 {
+    return match count {
+        1..=20 => 2,
+        21..=100 => 40,
+        _ => 80,
+    };
+}
+
+/// Request weight of the rolling window and trading day tickers: 4 per symbol,
+/// capped at 200.
+pub(crate) fn ticker_window_weight(count: usize) -> u32 {
+    if count == 0 {
+        return 200;
+    }
+    return u32::try_from(count).map_or(200, |count| count.saturating_mul(4).min(200));
+}
+
+/// Request weight of `ticker/price` and `ticker/bookTicker`.
+pub(crate) const fn ticker_price_weight(count: usize) -> u32 {
+    return if count == 1 { 2 } else { 4 };
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn daily_weight_limit(limit: u32) -> RateLimit {
+        return RateLimit {
+            rate_limit_type: RateLimitType::RequestWeight,
+            interval: RateLimitInterval::Day,
+            interval_num: 1,
+            limit,
+        };
+    }
+
+    #[tokio::test]
+    async fn test_reject_over_limit() {
+        let limiter = RateLimiter::new(RateLimitMode::Reject);
+        limiter.set_limits(&[daily_weight_limit(10)]);
+
+        limiter.acquire(6, false).await.unwrap();
+        limiter.acquire(4, false).await.unwrap();
+        let error = limiter.acquire(1, false).await.unwrap_err();
+        assert!(matches!(error, BinanceError::RateLimited(_)));
+        assert!(error.is_rate_limited());
+        assert_eq!(limiter.usage()[0].used, 10);
+    }
+
+    #[tokio::test]
+    async fn test_headers_and_retry_after() {
+        let limiter = RateLimiter::new(RateLimitMode::Reject);
+        let clone = limiter.clone();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-mbx-used-weight-1m", HeaderValue::from_static("5999"));
+        headers.insert("x-mbx-order-count-10s", HeaderValue::from_static("3"));
+        clone.update_from_headers(200, &headers);
+
+        let usage = limiter.usage();
+        assert_eq!(usage[0].rate_limit_type, RateLimitType::RequestWeight);
+        assert_eq!(usage[0].used, 5999);
+        assert_eq!(usage[1].used, 3);
+        assert!(limiter.acquire(2, false).await.is_err());
+
+        let limiter = RateLimiter::new(RateLimitMode::Reject);
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+        limiter.update_from_headers(429, &headers);
+        let Err(BinanceError::RateLimited(wait)) = limiter.acquire(1, false).await else {
+            panic!("request was not rejected");
+        };
+        assert!(wait > Duration::from_secs(29));
+    }
+
+    #[test]
+    fn test_weights() {
+        assert_eq!(depth_weight(None), 5);
+        assert_eq!(depth_weight(Some(500)), 25);
+        assert_eq!(depth_weight(Some(5000)), 250);
+        assert_eq!(ticker_24hr_weight(0), 80);
+        assert_eq!(ticker_24hr_weight(21), 40);
+        assert_eq!(ticker_window_weight(3), 12);
+        assert_eq!(ticker_window_weight(100), 200);
+        assert_eq!(ticker_price_weight(1), 2);
+    }
+}
@@ -0,0 +1,822 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+use futures_util::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message};
+use url::Url;
+
+use crate::errors::BinanceError;
+use crate::types::general::Error;
+
+/// Binance rejects subscriptions beyond 1024 streams on a connection.
+const MAX_STREAMS_PER_CONNECTION: usize = 1024;
+
+/// Binance closes connections that send more than 5 messages per second,
+/// pings and pongs included.
+const MAX_MESSAGES_PER_SECOND: usize = 5;
+
+/// The most streams restored by one `SUBSCRIBE` message after a reconnect.
+const RESTORE_BATCH: usize = 200;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    return mutex.lock().unwrap_or_else(PoisonError::into_inner);
+}
+
+/// Delivers the `data` of the envelopes of one or more streams.
+trait Route: Send + Sync {
+    /// Returns false once the receiver is dropped.
+    fn deliver(&self, stream: &str, data: Value) -> BoxFuture<'_, bool>;
+}
+
+struct TypedRoute<T> {
+    tx: mpsc::Sender<T>,
+}
+
+impl<T: DeserializeOwned + Send + 'static> Route for TypedRoute<T> {
+    fn deliver(&self, stream: &str, data: Value) -> BoxFuture<'_, bool> {
+        let event = serde_json::from_value::<T>(data);
+        if let Err(e) = &event {
+            log::error!("Failed to parse {stream} event: {e}");
+        }
+        return Box::pin(async move {
+            let Ok(event) = event else {
+                return true;
+            };
+            return self.tx.send(event).await.is_ok();
+        });
+    }
+}
+
+enum Command {
+    Subscribe {
+        streams: Vec<String>,
+        route: Arc<dyn Route>,
+        response: oneshot::Sender<Result<(), BinanceError>>,
+    },
+    Unsubscribe {
+        streams: Vec<String>,
+        response: oneshot::Sender<Result<(), BinanceError>>,
+    },
+    List {
+        response: oneshot::Sender<Result<Vec<String>, BinanceError>>,
+    },
+}
+
+/// A subscription waiting to be confirmed by the server.
+type PendingSubscribe = (Vec<String>, oneshot::Sender<Result<(), BinanceError>>);
+
+enum Pending {
+    Subscribe(PendingSubscribe),
+    Unsubscribe(oneshot::Sender<Result<(), BinanceError>>),
+    List(oneshot::Sender<Result<Vec<String>, BinanceError>>),
+    /// A subscription restored after a reconnect.
+    Restore(Vec<String>),
+    /// An unsubscription sent after a receiver was dropped.
+    Cleanup,
+}
+
+/// A connection of the manager and the streams assigned to it.
+struct Shard {
+    commands: mpsc::UnboundedSender<Command>,
+    /// The streams subscribed, or being subscribed, on the connection.
+    streams: Arc<Mutex<HashSet<String>>>,
+}
+
+/// Multiplexes market data streams over combined stream connections
+/// (`/stream`), instead of opening one connection per stream.
+///
+/// Streams are added and removed with the `SUBSCRIBE` and `UNSUBSCRIBE`
+/// methods, and each subscription routes the `data` of its envelopes to its
+/// own typed channel. Streams are spread over as many connections as needed
+/// to stay under 1024 streams per connection, and control messages are
+/// paced to stay under 5 messages per second per connection. Lost connections
+/// are reopened and their subscriptions restored with paced `SUBSCRIBE`
+/// messages, rather than in the connection URL, which could not hold them all.
+///
+/// Delivery waits for the receiver like the `stream_*` methods of
+/// [`BinanceWebSocket`](crate::websocket::BinanceWebSocket), so a slow
+/// receiver holds back the other streams of its connection. Dropping a
+/// receiver unsubscribes its streams.
+#[derive(Clone)]
+pub struct StreamManager {
+    base_url: Url,
+    max_streams: usize,
+    reconnect_delay: Duration,
+    shards: Arc<Mutex<Vec<Shard>>>,
+}
+
+impl std::fmt::Debug for StreamManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f
+            .debug_struct("StreamManager")
+            .field("base_url", &self.base_url)
+            .field("max_streams", &self.max_streams)
+            .field("connections", &lock(&self.shards).len())
+            .finish_non_exhaustive();
+    }
+}
+
+impl StreamManager {
+    /// Creates a manager for the streams served under `base_url`
+    /// (e.g. `wss://stream.binance.com:9443/`).
+    #[must_use]
+    pub fn new(base_url: Url) -> Self {
+        return Self {
+            base_url,
+            max_streams: MAX_STREAMS_PER_CONNECTION,
+            reconnect_delay: RECONNECT_DELAY,
+            shards: Arc::default(),
+        };
+    }
+
+    /// Sets how many streams a connection carries before another one is
+    /// opened. Defaults to 1024, the Binance limit.
+    #[must_use]
+    pub fn with_max_streams_per_connection(mut self, max_streams: usize) -> Self {
+        self.max_streams = max_streams.clamp(1, MAX_STREAMS_PER_CONNECTION);
+        return self;
+    }
+
+    /// Sets how long to wait before reconnecting after a connection failure.
+    /// Defaults to 5 seconds.
+    #[must_use]
+    pub const fn with_reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        return self;
+    }
+
+    /// The number of open or opening connections.
+    #[must_use]
+    pub fn connections(&self) -> usize {
+        return lock(&self.shards).len();
+    }
+
+    /// Subscribes to `streams` (e.g. `btcusdt@trade`) and sends their events
+    /// into `tx`.
+    ///
+    /// Streams that are already subscribed are routed to `tx` instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a connection cannot be opened or Binance rejects
+    /// the subscription; the streams of the failed connection are not
+    /// subscribed.
+    pub async fn subscribe<T: DeserializeOwned + Send + 'static>(
+        &self,
+        streams: &[&str],
+        tx: mpsc::Sender<T>,
+    ) -> Result<(), BinanceError> {
+        let route: Arc<dyn Route> = Arc::new(TypedRoute { tx });
+        let mut responses = Vec::new();
+        for (commands, streams) in self.assign(streams) {
+            let (response, receiver) = oneshot::channel();
+            let command = Command::Subscribe {
+                streams,
+                route: route.clone(),
+                response,
+            };
+            if commands.send(command).is_ok() {
+                responses.push(receiver);
+            }
+        }
+
+        let mut result = Ok(());
+        for response in responses {
+            let response = response.await.unwrap_or(Err(BinanceError::ConnectionLost));
+            if result.is_ok() {
+                result = response;
+            }
+        }
+        return result;
+    }
+
+    /// Unsubscribes from `streams`; unknown streams are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Binance rejects the unsubscription.
+    pub async fn unsubscribe(&self, streams: &[&str]) -> Result<(), BinanceError> {
+        let mut responses = Vec::new();
+        for shard in lock(&self.shards).iter() {
+            let assigned = lock(&shard.streams);
+            let streams = streams
+                .iter()
+                .filter(|stream| assigned.contains(**stream))
+                .map(|stream| (*stream).to_string())
+                .collect::<Vec<_>>();
+            drop(assigned);
+            if streams.is_empty() {
+                continue;
+            }
+            let (response, receiver) = oneshot::channel();
+            if shard
+                .commands
+                .send(Command::Unsubscribe { streams, response })
+                .is_ok()
+            {
+                responses.push(receiver);
+            }
+        }
+
+        for response in responses {
+            response
+                .await
+                .unwrap_or(Err(BinanceError::ConnectionLost))?;
+        }
+        return Ok(());
+    }
+
+    /// Lists the streams subscribed on every connection, as reported by
+    /// `LIST_SUBSCRIPTIONS`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a connection is lost before answering.
+    pub async fn list_subscriptions(&self) -> Result<Vec<String>, BinanceError> {
+        let responses = lock(&self.shards)
+            .iter()
+            .filter_map(|shard| {
+                let (response, receiver) = oneshot::channel();
+                shard.commands.send(Command::List { response }).ok()?;
+                Some(receiver)
+            })
+            .collect::<Vec<_>>();
+
+        let mut subscriptions = Vec::new();
+        for response in responses {
+            subscriptions.extend(
+                response
+                    .await
+                    .unwrap_or(Err(BinanceError::ConnectionLost))?,
+            );
+        }
+        subscriptions.sort();
+        return Ok(subscriptions);
+    }
+
+    /// Assigns `streams` to connections with room left, opening new ones as
+    /// needed, and returns the streams to subscribe on each connection.
+    fn assign(&self, streams: &[&str]) -> Vec<(mpsc::UnboundedSender<Command>, Vec<String>)> {
+        let mut shards = lock(&self.shards);
+        let mut assigned: Vec<Vec<String>> = vec![Vec::new(); shards.len()];
+        for stream in streams {
+            let existing = shards
+                .iter()
+                .position(|shard| lock(&shard.streams).contains(*stream));
+            let index = existing.or_else(|| {
+                shards
+                    .iter()
+                    .position(|shard| lock(&shard.streams).len() < self.max_streams)
+            });
+            let index = index.unwrap_or_else(|| {
+                shards.push(self.spawn_shard());
+                assigned.push(Vec::new());
+                shards.len() - 1
+            });
+            lock(&shards[index].streams).insert((*stream).to_string());
+            assigned[index].push((*stream).to_string());
+        }
+
+        return shards
+            .iter()
+            .zip(assigned)
+            .filter(|(_, streams)| !streams.is_empty())
+            .map(|(shard, streams)| (shard.commands.clone(), streams))
+            .collect();
+    }
+
+    fn spawn_shard(&self) -> Shard {
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let streams = Arc::new(Mutex::new(HashSet::new()));
+        let connection = Connection {
+            base_url: self.base_url.clone(),
+            reconnect_delay: self.reconnect_delay,
+            streams: streams.clone(),
+            routes: HashMap::new(),
+            sent: VecDeque::new(),
+            next_id: 1,
+        };
+        tokio::spawn(connection.run(receiver));
+        return Shard { commands, streams };
+    }
+}
+
+/// The task owning a combined stream connection.
+struct Connection {
+    base_url: Url,
+    reconnect_delay: Duration,
+    /// Shared with the manager, which reserves streams before subscribing.
+    streams: Arc<Mutex<HashSet<String>>>,
+    routes: HashMap<String, Arc<dyn Route>>,
+    /// When the control messages of the last second were sent.
+    sent: VecDeque<Instant>,
+    next_id: u64,
+}
+
+impl Connection {
+    /// Serves commands until the manager is dropped, reconnecting with the
+    /// current subscriptions whenever the connection is lost.
+    async fn run(mut self, mut commands: mpsc::UnboundedReceiver<Command>) {
+        let mut waiting = Vec::new();
+        loop {
+            while self.routes.is_empty() {
+                let Some(command) = commands.recv().await else {
+                    return;
+                };
+                self.handle_offline(command, &mut waiting);
+            }
+
+            let ws_stream = match self.connect().await {
+                Ok(ws_stream) => ws_stream,
+                Err(e) => {
+                    log::error!("Combined stream connection failed: {e}");
+                    for (streams, response) in std::mem::take(&mut waiting) {
+                        self.remove(&streams);
+                        let _ = response.send(Err(BinanceError::NotConnected(e.to_string())));
+                    }
+                    if !self.wait_reconnect(&mut commands, &mut waiting).await {
+                        return;
+                    }
+                    continue;
+                }
+            };
+
+            let waiting_streams = std::mem::take(&mut waiting);
+            let Some(unconfirmed) = self.serve(ws_stream, &mut commands, waiting_streams).await
+            else {
+                return;
+            };
+            // Subscriptions not confirmed on the lost connection are sent
+            // again on the next one.
+            waiting = unconfirmed;
+            log::info!("Combined stream connection lost, reconnecting");
+        }
+    }
+
+    /// Opens a connection without streams; they are subscribed by
+    /// [`Connection::restore`].
+    async fn connect(&mut self) -> Result<WsStream, BinanceError> {
+        let url = self.base_url.join("stream")?;
+        let (ws_stream, _) = connect_async(url.as_str()).await?;
+        self.sent.clear();
+        return Ok(ws_stream);
+    }
+
+    /// Handles a command while disconnected; subscriptions are confirmed
+    /// once the connection is open.
+    fn handle_offline(&mut self, command: Command, waiting: &mut Vec<PendingSubscribe>) {
+        match command {
+            Command::Subscribe {
+                streams,
+                route,
+                response,
+            } => {
+                for stream in &streams {
+                    self.routes.insert(stream.clone(), route.clone());
+                }
+                waiting.push((streams, response));
+            }
+            Command::Unsubscribe { streams, response } => {
+                self.remove(&streams);
+                let _ = response.send(Ok(()));
+            }
+            Command::List { response } => {
+                let _ = response.send(Ok(self.routes.keys().cloned().collect()));
+            }
+        }
+    }
+
+    /// Waits for the reconnect delay, handling commands in the meantime.
+    /// Returns false once the manager is dropped.
+    async fn wait_reconnect(
+        &mut self,
+        commands: &mut mpsc::UnboundedReceiver<Command>,
+        waiting: &mut Vec<PendingSubscribe>,
+    ) -> bool {
+        let reconnect = tokio::time::sleep(self.reconnect_delay);
+        tokio::pin!(reconnect);
+        loop {
+            tokio::select! {
+                () = &mut reconnect => return true,
+                command = commands.recv() => {
+                    let Some(command) = command else {
+                        return false;
+                    };
+                    self.handle_offline(command, waiting);
+                }
+            }
+        }
+    }
+
+    /// Subscribes the routed streams on a new connection: the streams of
+    /// `waiting` with their own subscription, and the others in batches of
+    /// [`RESTORE_BATCH`]. Returns false if the connection is lost; the
+    /// subscriptions left unsent are kept pending.
+    async fn restore(
+        &mut self,
+        ws_stream: &mut WsStream,
+        waiting: Vec<PendingSubscribe>,
+        pending: &mut HashMap<u64, Pending>,
+    ) -> bool {
+        let subscribing = waiting
+            .iter()
+            .flat_map(|(streams, _)| streams)
+            .collect::<HashSet<_>>();
+        let mut restored = self
+            .routes
+            .keys()
+            .filter(|stream| !subscribing.contains(stream))
+            .cloned()
+            .collect::<Vec<_>>();
+        restored.sort_unstable();
+
+        let batches = restored
+            .chunks(RESTORE_BATCH)
+            .map(|batch| (batch.to_vec(), Pending::Restore(batch.to_vec())));
+        let subscriptions = waiting
+            .into_iter()
+            .map(|(streams, response)| (streams.clone(), Pending::Subscribe((streams, response))));
+        let mut connected = true;
+        for (streams, entry) in batches.chain(subscriptions).collect::<Vec<_>>() {
+            if connected {
+                connected = self
+                    .send_method(ws_stream, "SUBSCRIBE", streams, entry, pending)
+                    .await;
+            } else {
+                pending.insert(self.next_id, entry);
+                self.next_id += 1;
+            }
+        }
+        return connected;
+    }
+
+    /// Restores the subscriptions, then sends commands and routes envelopes
+    /// until the connection is lost.
+    ///
+    /// Returns the subscriptions that were not confirmed yet, or `None` once
+    /// the manager is dropped.
+    async fn serve(
+        &mut self,
+        mut ws_stream: WsStream,
+        commands: &mut mpsc::UnboundedReceiver<Command>,
+        waiting: Vec<PendingSubscribe>,
+    ) -> Option<Vec<PendingSubscribe>> {
+        let mut pending = HashMap::new();
+        let mut sent = self.restore(&mut ws_stream, waiting, &mut pending).await;
+        while sent {
+            sent = tokio::select! {
+                command = commands.recv() => {
+                    let Some(command) = command else {
+                        let _ = ws_stream.close(None).await;
+                        return None;
+                    };
+                    self.send_command(&mut ws_stream, command, &mut pending).await
+                }
+                message = ws_stream.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        self.receive(&mut ws_stream, &text, &mut pending).await
+                    }
+                    Some(Ok(Message::Ping(ping))) => {
+                        self.throttle().await;
+                        ws_stream.send(Message::Pong(ping)).await.is_ok()
+                    }
+                    Some(Ok(Message::Close(_))) | None => {
+                        log::info!("Combined stream connection closed by server");
+                        false
+                    }
+                    Some(Ok(_)) => true,
+                    Some(Err(e)) => {
+                        log::error!("Combined stream message error: {e}");
+                        false
+                    }
+                },
+            };
+        }
+
+        let unconfirmed = pending
+            .into_values()
+            .filter_map(|pending| match pending {
+                Pending::Subscribe(subscribe) => Some(subscribe),
+                Pending::Unsubscribe(response) => {
+                    // The streams are not restored on the next connection.
+                    let _ = response.send(Ok(()));
+                    None
+                }
+                // Restored streams are still routed and restored again.
+                Pending::List(_) | Pending::Restore(_) | Pending::Cleanup => None,
+            })
+            .collect();
+        return Some(unconfirmed);
+    }
+
+    /// Sends the control message of `command`. Returns false if the
+    /// connection is lost.
+    async fn send_command(
+        &mut self,
+        ws_stream: &mut WsStream,
+        command: Command,
+        pending: &mut HashMap<u64, Pending>,
+    ) -> bool {
+        let (method, streams, entry) = match command {
+            Command::Subscribe {
+                streams,
+                route,
+                response,
+            } => {
+                for stream in &streams {
+                    self.routes.insert(stream.clone(), route.clone());
+                }
+                let params = streams.clone();
+                ("SUBSCRIBE", params, Pending::Subscribe((streams, response)))
+            }
+            Command::Unsubscribe { streams, response } => {
+                self.remove(&streams);
+                ("UNSUBSCRIBE", streams, Pending::Unsubscribe(response))
+            }
+            Command::List { response } => {
+                ("LIST_SUBSCRIPTIONS", Vec::new(), Pending::List(response))
+            }
+        };
+        return self
+            .send_method(ws_stream, method, streams, entry, pending)
+            .await;
+    }
+
+    async fn send_method(
+        &mut self,
+        ws_stream: &mut WsStream,
+        method: &str,
+        streams: Vec<String>,
+        entry: Pending,
+        pending: &mut HashMap<u64, Pending>,
+    ) -> bool {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut request = json!({ "method": method, "id": id });
+        if !streams.is_empty() {
+            request["params"] = json!(streams);
+        }
+        pending.insert(id, entry);
+
+        self.throttle().await;
+        if let Err(e) = ws_stream.send(Message::text(request.to_string())).await {
+            log::error!("Failed to send {method}: {e}");
+            return false;
+        }
+        return true;
+    }
+
+    /// Routes an envelope or completes the request answered by a response.
+    /// Returns false if the connection is lost.
+    async fn receive(
+        &mut self,
+        ws_stream: &mut WsStream,
+        text: &str,
+        pending: &mut HashMap<u64, Pending>,
+    ) -> bool {
+        let mut message: Value = match serde_json::from_str(text) {
+            Ok(message) => message,
+            Err(e) => {
+                log::error!("Failed to parse combined stream message: {e}");
+                return true;
+            }
+        };
+
+        let data = message["data"].take();
+        if let Some(stream) = message["stream"].as_str() {
+            let Some(route) = self.routes.get(stream).cloned() else {
+                return true;
+            };
+            if route.deliver(stream, data).await {
+                return true;
+            }
+            // The receiver was dropped: unsubscribe every stream it received.
+            let streams = self
+                .routes
+                .iter()
+                .filter(|(_, other)| Arc::ptr_eq(other, &route))
+                .map(|(stream, _)| stream.clone())
+                .collect::<Vec<_>>();
+            log::info!("Receiver dropped. Unsubscribing from {streams:?}");
+            self.remove(&streams);
+            return self
+                .send_method(ws_stream, "UNSUBSCRIBE", streams, Pending::Cleanup, pending)
+                .await;
+        }
+
+        let Some(entry) = message["id"].as_u64().and_then(|id| pending.remove(&id)) else {
+            log::warn!("Unexpected combined stream message: {text}");
+            return true;
+        };
+        let error = serde_json::from_value::<Error>(message["error"].take())
+            .ok()
+            .map(BinanceError::Stream);
+        match entry {
+            Pending::Subscribe((streams, response)) => {
+                if let Some(error) = error {
+                    self.remove(&streams);
+                    let _ = response.send(Err(error));
+                } else {
+                    let _ = response.send(Ok(()));
+                }
+            }
+            Pending::Unsubscribe(response) => {
+                let _ = response.send(error.map_or(Ok(()), Err));
+            }
+            Pending::List(response) => {
+                let result = error.map_or_else(
+                    || serde_json::from_value(message["result"].take()).map_err(Into::into),
+                    Err,
+                );
+                let _ = response.send(result);
+            }
+            Pending::Restore(streams) => {
+                if let Some(error) = error {
+                    log::error!("Failed to restore {streams:?}: {error}");
+                    self.remove(&streams);
+                }
+            }
+            Pending::Cleanup => {
+                if let Some(error) = error {
+                    log::warn!("Failed to unsubscribe: {error}");
+                }
+            }
+        }
+        return true;
+    }
+
+    fn remove(&mut self, streams: &[String]) {
+        let mut assigned = lock(&self.streams);
+        for stream in streams {
+            self.routes.remove(stream);
+            assigned.remove(stream);
+        }
+    }
+
+    /// Waits until another message can be sent without exceeding
+    /// [`MAX_MESSAGES_PER_SECOND`].
+    async fn throttle(&mut self) {
+        let window = Duration::from_secs(1);
+        if self.sent.len() >= MAX_MESSAGES_PER_SECOND
+            && let Some(oldest) = self.sent.pop_front()
+        {
+            tokio::time::sleep_until(oldest + window).await;
+        }
+        let now = Instant::now();
+        while self.sent.front().is_some_and(|t| now - *t >= window) {
+            self.sent.pop_front();
+        }
+        self.sent.push_back(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::MockServer;
+    use crate::types::events::{MiniTickerEvent, TradeEvent};
+
+    use super::*;
+
+    fn connections(server: &MockServer) -> usize {
+        return server
+            .requests()
+            .iter()
+            .filter(|r| r.path == "stream")
+            .count();
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_and_route() {
+        let server = MockServer::start().await;
+        let manager = StreamManager::new(server.stream_url());
+
+        // Rejected subscriptions are reported without an HTTP status.
+        let (tx, _rx) = mpsc::channel::<TradeEvent>(1);
+        let error = manager.subscribe(&["btcusdt"], tx).await.unwrap_err();
+        assert!(matches!(&error, BinanceError::Stream(e) if e.code == 2));
+        assert_eq!(error.status(), None);
+
+        let (trades_tx, mut trades) = mpsc::channel::<TradeEvent>(256);
+        let (tickers_tx, mut tickers) = mpsc::channel::<MiniTickerEvent>(256);
+        manager
+            .subscribe(&["btcusdt@trade", "ethusdt@trade"], trades_tx)
+            .await
+            .unwrap();
+        manager
+            .subscribe(&["bnbusdt@miniTicker"], tickers_tx)
+            .await
+            .unwrap();
+        assert_eq!(manager.connections(), 1);
+
+        let mut symbols = HashSet::new();
+        while symbols.len() < 2 {
+            symbols.insert(trades.recv().await.unwrap().symbol);
+        }
+        assert_eq!(tickers.recv().await.unwrap().symbol, "BNBUSDT");
+        assert_eq!(
+            manager.list_subscriptions().await.unwrap(),
+            ["bnbusdt@miniTicker", "btcusdt@trade", "ethusdt@trade"]
+        );
+
+        manager.unsubscribe(&["ethusdt@trade"]).await.unwrap();
+        assert_eq!(
+            manager.list_subscriptions().await.unwrap(),
+            ["bnbusdt@miniTicker", "btcusdt@trade"]
+        );
+
+        // Dropping a receiver unsubscribes its streams.
+        drop(tickers);
+        while manager.list_subscriptions().await.unwrap().len() > 1 {
+            trades.recv().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(connections(&server), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sharding_and_message_rate() {
+        let server = MockServer::start().await;
+        let manager = StreamManager::new(server.stream_url()).with_max_streams_per_connection(4);
+
+        let (tx, _rx) = mpsc::channel::<TradeEvent>(1024);
+        manager
+            .subscribe(&["btcusdt@trade", "ethusdt@trade"], tx.clone())
+            .await
+            .unwrap();
+        // Each subscription is a message; more than 5 per second would get
+        // the connection closed.
+        let started = Instant::now();
+        for stream in ["bnbusdt@trade", "ethbtc@trade"] {
+            manager.subscribe(&[stream], tx.clone()).await.unwrap();
+        }
+        for _ in 0..5 {
+            manager.list_subscriptions().await.unwrap();
+        }
+        assert!(started.elapsed() >= Duration::from_millis(900));
+
+        let streams = ["btcusdt@aggTrade", "ethusdt@aggTrade", "bnbusdt@aggTrade"];
+        let (agg_tx, _agg_rx) = mpsc::channel::<Value>(1024);
+        manager.subscribe(&streams, agg_tx).await.unwrap();
+        assert_eq!(manager.connections(), 2);
+        assert_eq!(manager.list_subscriptions().await.unwrap().len(), 7);
+        assert_eq!(connections(&server), 2);
+    }
+
+    #[tokio::test]
+    async fn test_restore_after_reconnect() {
+        let server = MockServer::start().await;
+        let manager = StreamManager::new(server.stream_url());
+        let (tx, mut rx) = mpsc::channel::<TradeEvent>(1);
+        manager
+            .subscribe(&["btcusdt@trade"], tx.clone())
+            .await
+            .unwrap();
+        manager.subscribe(&["ethusdt@trade"], tx).await.unwrap();
+        // Streams without events, too many for a reconnection URL.
+        let (idle_tx, _idle_rx) = mpsc::channel::<TradeEvent>(1);
+        let idle = (0..300)
+            .map(|i| format!("idle{i}usdt@trade"))
+            .collect::<Vec<_>>();
+        let idle = idle.iter().map(String::as_str).collect::<Vec<_>>();
+        manager.subscribe(&idle, idle_tx).await.unwrap();
+        rx.recv().await.unwrap();
+
+        let subscribes = |server: &MockServer| {
+            return server
+                .requests()
+                .iter()
+                .filter(|r| r.path == "SUBSCRIBE")
+                .count();
+        };
+        let before = subscribes(&server);
+        server.disconnect_streams();
+        while connections(&server) < 2 {
+            rx.recv().await.unwrap();
+        }
+        let mut symbols = HashSet::new();
+        while symbols.len() < 2 {
+            symbols.insert(rx.recv().await.unwrap().symbol);
+        }
+
+        // The connection is opened bare and the streams restored in batches.
+        let request = server
+            .requests()
+            .into_iter()
+            .rfind(|r| r.path == "stream")
+            .unwrap();
+        assert_eq!(request.param("streams"), None);
+        assert_eq!(subscribes(&server) - before, 2);
+        assert_eq!(manager.list_subscriptions().await.unwrap().len(), 302);
+    }
+}
@@ -0,0 +1,374 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::client::BinanceGeneralClient;
+use crate::errors::BinanceError;
+use crate::types::general::{ExchangeInfoQuery, SymbolInfo, SymbolStatus};
+
+/// How long the exchange information is used before it is fetched again.
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_mins(30);
+
+type Symbols = Arc<BTreeMap<String, Arc<SymbolInfo>>>;
+
+/// A difference between two refreshes of a [`SymbolRegistry`].
+#[derive(Debug, Clone)]
+pub enum SymbolChange {
+    /// A symbol that was not returned before.
+    Listed(Arc<SymbolInfo>),
+    /// A symbol that is no longer returned, with its last known information.
+    Delisted(Arc<SymbolInfo>),
+    /// A symbol whose trading status changed, e.g. from `TRADING` to `BREAK`.
+    StatusChanged {
+        symbol: Arc<SymbolInfo>,
+        previous: SymbolStatus,
+    },
+}
+
+impl SymbolChange {
+    /// The name of the changed symbol.
+    #[must_use]
+    pub fn symbol(&self) -> &str {
+        return match self {
+            Self::Listed(symbol) | Self::Delisted(symbol) | Self::StatusChanged { symbol, .. } => {
+                &symbol.symbol
+            }
+        };
+    }
+}
+
+/// The exchange information of the symbols selected by a query, fetched
+/// with `GET /api/v3/exchangeInfo` on first use and again once it is older
+/// than the refresh interval. Clones share the same symbols.
+///
+/// Every refresh is compared with the previous one, and the listings,
+/// delistings and status changes are sent to the receivers returned by
+/// [`subscribe`](Self::subscribe). If a refresh fails, the last known
+/// symbols are used until the next lookup tries again.
+#[derive(Debug, Clone)]
+pub struct SymbolRegistry {
+    general: Arc<BinanceGeneralClient>,
+    query: Arc<ExchangeInfoQuery>,
+    state: Arc<Mutex<RegistryState>>,
+    /// Held while refreshing, so that concurrent lookups fetch only once.
+    refreshing: Arc<tokio::sync::Mutex<()>>,
+    changes: broadcast::Sender<SymbolChange>,
+}
+
+#[derive(Debug)]
+struct RegistryState {
+    symbols: Option<Symbols>,
+    refreshed_at: Option<Instant>,
+    refresh_interval: Duration,
+}
+
+impl SymbolRegistry {
+    /// Creates a registry of every symbol, fetched with `general`.
+    #[must_use]
+    pub fn new(general: BinanceGeneralClient) -> Self {
+        return Self {
+            general: Arc::new(general),
+            query: Arc::new(ExchangeInfoQuery::default()),
+            state: Arc::new(Mutex::new(RegistryState {
+                symbols: None,
+                refreshed_at: None,
+                refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            })),
+            refreshing: Arc::new(tokio::sync::Mutex::new(())),
+            changes: broadcast::channel(256).0,
+        };
+    }
+
+    /// Only keeps the symbols selected by `query`, e.g. those with the
+    /// `SPOT` permission.
+    ///
+    /// A symbol leaving the selection, e.g. when its status no longer matches
+    /// `symbol_status`, is reported as delisted.
+    #[must_use]
+    pub fn with_query(mut self, query: ExchangeInfoQuery) -> Self {
+        self.query = Arc::new(query);
+        return self;
+    }
+
+    /// Sets how long the symbols are used before they are fetched again.
+    #[must_use]
+    pub fn with_refresh_interval(self, refresh_interval: Duration) -> Self {
+        self.lock().refresh_interval = refresh_interval;
+        return self;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RegistryState> {
+        return self.state.lock().unwrap_or_else(PoisonError::into_inner);
+    }
+
+    /// Returns a receiver of the changes found by the following refreshes.
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<SymbolChange> {
+        return self.changes.subscribe();
+    }
+
+    /// Fetches the symbols, publishing and returning their changes since the
+    /// last refresh. The first refresh reports no changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the exchange information cannot be fetched.
+    pub async fn refresh(&self) -> Result<Vec<SymbolChange>, BinanceError> {
+        let _refreshing = self.refreshing.lock().await;
+        return self.fetch().await;
+    }
+
+    async fn fetch(&self) -> Result<Vec<SymbolChange>, BinanceError> {
+        let info = self.general.get_exchange_info_with(&self.query).await?;
+        let symbols: BTreeMap<_, _> = info
+            .symbols
+            .into_iter()
+            .map(|symbol| (symbol.symbol.clone(), Arc::new(symbol)))
+            .collect();
+
+        let mut state = self.lock();
+        let changes = state
+            .symbols
+            .as_ref()
+            .map_or_else(Vec::new, |previous| diff(previous, &symbols));
+        state.symbols = Some(Arc::new(symbols));
+        state.refreshed_at = Some(Instant::now());
+        drop(state);
+
+        for change in &changes {
+            // Sending only fails when nobody is subscribed.
+            let _ = self.changes.send(change.clone());
+        }
+        return Ok(changes);
+    }
+
+    /// Returns the symbols, refreshing them first if they were never fetched
+    /// or are older than the refresh interval.
+    async fn symbols(&self) -> Result<Symbols, BinanceError> {
+        if let Some(symbols) = self.fresh() {
+            return Ok(symbols);
+        }
+        let _refreshing = self.refreshing.lock().await;
+        // Another lookup may have refreshed while this one waited.
+        if let Some(symbols) = self.fresh() {
+            return Ok(symbols);
+        }
+        if let Err(error) = self.fetch().await {
+            let Some(symbols) = self.lock().symbols.clone() else {
+                return Err(error);
+            };
+            log::warn!("Failed to refresh the exchange information: {error}");
+            return Ok(symbols);
+        }
+        return Ok(self.lock().symbols.clone().unwrap_or_default());
+    }
+
+    fn fresh(&self) -> Option<Symbols> {
+        let state = self.lock();
+        let refreshed_at = state.refreshed_at?;
+        if refreshed_at.elapsed() >= state.refresh_interval {
+            return None;
+        }
+        return state.symbols.clone();
+    }
+
+    /// Returns the information of `symbol`, if it is listed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the symbols were never fetched and cannot be.
+    pub async fn symbol(&self, symbol: &str) -> Result<Option<Arc<SymbolInfo>>, BinanceError> {
+        return Ok(self.symbols().await?.get(symbol).cloned());
+    }
+
+    /// Returns the information of every symbol, ordered by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the symbols were never fetched and cannot be.
+    pub async fn all(&self) -> Result<Vec<Arc<SymbolInfo>>, BinanceError> {
+        return Ok(self.symbols().await?.values().cloned().collect());
+    }
+
+    /// Returns the symbols trading `asset` against any quote asset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the symbols were never fetched and cannot be.
+    pub async fn by_base_asset(&self, asset: &str) -> Result<Vec<Arc<SymbolInfo>>, BinanceError> {
+        return self.filter(|symbol| symbol.base_asset == asset).await;
+    }
+
+    /// Returns the symbols quoted in `asset`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the symbols were never fetched and cannot be.
+    pub async fn by_quote_asset(&self, asset: &str) -> Result<Vec<Arc<SymbolInfo>>, BinanceError> {
+        return self.filter(|symbol| symbol.quote_asset == asset).await;
+    }
+
+    /// Returns the symbol trading `base_asset` against `quote_asset`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the symbols were never fetched and cannot be.
+    pub async fn find_pair(
+        &self,
+        base_asset: &str,
+        quote_asset: &str,
+    ) -> Result<Option<Arc<SymbolInfo>>, BinanceError> {
+        let symbols = self.symbols().await?;
+        return Ok(symbols
+            .values()
+            .find(|symbol| symbol.base_asset == base_asset && symbol.quote_asset == quote_asset)
+            .cloned());
+    }
+
+    async fn filter(
+        &self,
+        predicate: impl Fn(&SymbolInfo) -> bool,
+    ) -> Result<Vec<Arc<SymbolInfo>>, BinanceError> {
+        let symbols = self.symbols().await?;
+        return Ok(symbols
+            .values()
+            .filter(|symbol| predicate(symbol))
+            .cloned()
+            .collect());
+    }
+
+    /// Refreshes the symbols every `interval` in a background task until the
+    /// returned handle is aborted, so that changes are published without
+    /// lookups.
+    #[must_use]
+    pub fn spawn_refresh(&self, interval: Duration) -> JoinHandle<()> {
+        let registry = self.clone();
+        return tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(error) = registry.refresh().await {
+                    log::warn!("Failed to refresh the exchange information: {error}");
+                }
+            }
+        });
+    }
+}
+
+/// The changes from `previous` to `current`, ordered by symbol.
+fn diff(
+    previous: &BTreeMap<String, Arc<SymbolInfo>>,
+    current: &BTreeMap<String, Arc<SymbolInfo>>,
+) -> Vec<SymbolChange> {
+    let mut changes = Vec::new();
+    for (name, symbol) in current {
+        match previous.get(name) {
+            None => changes.push(SymbolChange::Listed(symbol.clone())),
+            Some(old) if old.status != symbol.status => {
+                changes.push(SymbolChange::StatusChanged {
+                    symbol: symbol.clone(),
+                    previous: old.status,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, symbol) in previous {
+        if !current.contains_key(name) {
+            changes.push(SymbolChange::Delisted(symbol.clone()));
+        }
+    }
+    changes.sort_by(|a, b| a.symbol().cmp(b.symbol()));
+    return changes;
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Client;
+    use rust_decimal::Decimal;
+
+    use crate::mock::{MockServer, MockSymbol};
+
+    use super::*;
+
+    fn registry(server: &MockServer) -> SymbolRegistry {
+        let general = BinanceGeneralClient::with_base_url(Client::new(), server.api_url());
+        return SymbolRegistry::new(general);
+    }
+
+    fn exchange_info_requests(server: &MockServer) -> usize {
+        return server
+            .requests()
+            .iter()
+            .filter(|r| r.path == "exchangeInfo")
+            .count();
+    }
+
+    #[tokio::test]
+    async fn test_lookups() {
+        let server = MockServer::start().await;
+        let registry = registry(&server);
+
+        let symbol = registry.symbol("BTCUSDT").await.unwrap().unwrap();
+        assert_eq!(symbol.status, SymbolStatus::Trading);
+        assert!(registry.symbol("XRPUSDT").await.unwrap().is_none());
+        let usdt: Vec<_> = registry.by_quote_asset("USDT").await.unwrap();
+        let names: Vec<_> = usdt.iter().map(|s| s.symbol.as_str()).collect();
+        assert_eq!(names, ["BNBUSDT", "BTCUSDT", "ETHUSDT"]);
+        assert_eq!(registry.by_base_asset("ETH").await.unwrap().len(), 2);
+        let pair = registry.find_pair("ETH", "BTC").await.unwrap().unwrap();
+        assert_eq!(pair.symbol, "ETHBTC");
+        assert!(registry.find_pair("BTC", "ETH").await.unwrap().is_none());
+
+        // Clones share the symbols, which are fetched once.
+        assert_eq!(registry.clone().all().await.unwrap().len(), 4);
+        assert_eq!(exchange_info_requests(&server), 1);
+
+        let registry = registry.with_refresh_interval(Duration::ZERO);
+        registry.symbol("BTCUSDT").await.unwrap();
+        assert_eq!(exchange_info_requests(&server), 2);
+
+        // The last known symbols are used when a refresh fails.
+        drop(server);
+        assert!(registry.symbol("BTCUSDT").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_changes() {
+        let server = MockServer::start().await;
+        let registry = registry(&server);
+        let mut changes = registry.subscribe();
+        assert!(registry.refresh().await.unwrap().is_empty());
+
+        server.set_symbol_status("BTCUSDT", "BREAK");
+        server.add_symbol(MockSymbol::new("SOL", "USDT", Decimal::from(150)));
+        server.remove_symbol("ETHBTC");
+        let found = registry.refresh().await.unwrap();
+        assert_eq!(found.len(), 3);
+
+        for expected in found {
+            let change = changes.recv().await.unwrap();
+            assert_eq!(change.symbol(), expected.symbol());
+            match change {
+                SymbolChange::StatusChanged { symbol, previous } => {
+                    assert_eq!(symbol.symbol, "BTCUSDT");
+                    assert_eq!(symbol.status, SymbolStatus::Break);
+                    assert_eq!(previous, SymbolStatus::Trading);
+                }
+                SymbolChange::Delisted(symbol) => assert_eq!(symbol.symbol, "ETHBTC"),
+                SymbolChange::Listed(symbol) => assert_eq!(symbol.symbol, "SOLUSDT"),
+            }
+        }
+
+        // Symbols leaving the query are reported as delisted.
+        let registry = registry.with_query(ExchangeInfoQuery {
+            symbol_status: Some(SymbolStatus::Trading),
+            ..Default::default()
+        });
+        let found = registry.refresh().await.unwrap();
+        assert!(matches!(&found[..], [SymbolChange::Delisted(s)] if s.symbol == "BTCUSDT"));
+    }
+}
@@ -1,3 +1,5 @@
+use chrono::{DateTime, Utc, serde::ts_milliseconds};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,17 +25,79 @@ pub struct Account {
     pub uid: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Account {
+    /// Returns the balance of `asset`, if the account holds or held it.
+    #[must_use]
+    pub fn balance(&self, asset: &str) -> Option<&Balance> {
+        return self.balances.iter().find(|balance| balance.asset == asset);
+    }
+
+    /// The free amount of `asset`, zero if it has no balance.
+    #[must_use]
+    pub fn free(&self, asset: &str) -> Decimal {
+        return self
+            .balance(asset)
+            .map_or(Decimal::ZERO, |balance| balance.free);
+    }
+
+    /// The free and locked amount of `asset`, zero if it has no balance.
+    #[must_use]
+    pub fn total(&self, asset: &str) -> Decimal {
+        return self.balance(asset).map_or(Decimal::ZERO, Balance::total);
+    }
+
+    /// The balances with a free or locked amount.
+    pub fn non_zero_balances(&self) -> impl Iterator<Item = &Balance> {
+        return self.balances.iter().filter(|balance| !balance.is_zero());
+    }
+}
+
+/// Commission rates as fractions, e.g. `0.001` for 0.1%.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CommissionRates {
-    pub maker: String,
-    pub taker: String,
-    pub buyer: String,
-    pub seller: String,
+    pub maker: Decimal,
+    pub taker: Decimal,
+    pub buyer: Decimal,
+    pub seller: Decimal,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Balance {
     pub asset: String,
-    pub free: String,
-    pub locked: String,
+    pub free: Decimal,
+    pub locked: Decimal,
+}
+
+impl Balance {
+    /// The free and locked amount.
+    #[must_use]
+    pub fn total(&self) -> Decimal {
+        return self.free + self.locked;
+    }
+
+    #[must_use]
+    pub const fn is_zero(&self) -> bool {
+        return self.free.is_zero() && self.locked.is_zero();
+    }
+}
+
+/// A fill of one of the account's orders.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::struct_excessive_bools)]
+pub struct AccountTrade {
+    pub symbol: String,
+    pub id: u64,
+    pub order_id: u64,
+    pub order_list_id: i64,
+    pub price: Decimal,
+    pub qty: Decimal,
+    pub quote_qty: Decimal,
+    pub commission: Decimal,
+    pub commission_asset: String,
+    #[serde(with = "ts_milliseconds")]
+    pub time: DateTime<Utc>,
+    pub is_buyer: bool,
+    pub is_maker: bool,
+    pub is_best_match: bool,
 }
@@ -3,6 +3,12 @@ use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::Deserialize;
 
+use crate::types::market::{Kline, Order, OrderBook};
+use crate::types::trading::{
+    ContingencyType, ListOrderStatus, ListStatusType, OrderSide, OrderStatus, OrderType,
+    SelfTradePreventionMode, TimeInForce,
+};
+
 #[derive(Debug, Deserialize)]
 pub struct AggTradeEvent {
     #[serde(rename = "e")]
@@ -129,6 +135,24 @@ pub struct KlineData {
     pub taker_buy_quote_volume: Decimal,
 }
 
+impl From<KlineData> for Kline {
+    fn from(kline: KlineData) -> Self {
+        return Self {
+            open_time: kline.start_time,
+            open_price: kline.open_price,
+            high_price: kline.high_price,
+            low_price: kline.low_price,
+            close_price: kline.close_price,
+            volume: kline.base_volume,
+            close_time: kline.close_time,
+            quote_asset_volume: kline.quote_volume,
+            number_of_trades: u64::try_from(kline.number_of_trades).unwrap_or_default(),
+            taker_buy_base_asset_volume: kline.taker_buy_base_volume,
+            taker_buy_quote_asset_volume: kline.taker_buy_quote_volume,
+        };
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct MiniTickerEvent {
     #[serde(rename = "e")]
@@ -230,3 +254,434 @@ pub struct TickerEvent {
     #[serde(rename = "n")]
     pub total_trades: u64,
 }
+
+/// The statistics of a symbol over a rolling window, pushed by the
+/// `<symbol>@ticker_<window>` and `!ticker_<window>@arr` streams.
+#[derive(Debug, Deserialize)]
+pub struct WindowTickerEvent {
+    /// `1hTicker`, `4hTicker` or `1dTicker`.
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "p")]
+    pub price_change: Decimal,
+
+    #[serde(rename = "P")]
+    pub price_change_percent: Decimal,
+
+    #[serde(rename = "o")]
+    pub open_price: Decimal,
+
+    #[serde(rename = "h")]
+    pub high_price: Decimal,
+
+    #[serde(rename = "l")]
+    pub low_price: Decimal,
+
+    #[serde(rename = "c")]
+    pub last_price: Decimal,
+
+    #[serde(rename = "w")]
+    pub weighted_avg_price: Decimal,
+
+    #[serde(rename = "v")]
+    pub base_volume: Decimal,
+
+    #[serde(rename = "q")]
+    pub quote_volume: Decimal,
+
+    #[serde(rename = "O")]
+    pub open_time: u64,
+
+    #[serde(rename = "C")]
+    pub close_time: u64,
+
+    #[serde(rename = "F")]
+    pub first_trade_id: i64,
+
+    #[serde(rename = "L")]
+    pub last_trade_id: i64,
+
+    #[serde(rename = "n")]
+    pub total_trades: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AvgPriceEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    /// The interval the price is averaged over, e.g. `5m`.
+    #[serde(rename = "i")]
+    pub interval: String,
+
+    #[serde(rename = "w")]
+    pub price: Decimal,
+
+    #[serde(rename = "T")]
+    pub last_trade_time: u64,
+}
+
+/// How often a depth stream pushes its updates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UpdateSpeed {
+    /// Every 1000ms.
+    #[default]
+    Normal,
+    /// Every 100ms.
+    Fast,
+}
+
+impl UpdateSpeed {
+    /// The suffix of the stream names pushing at this speed.
+    #[must_use]
+    pub const fn suffix(self) -> &'static str {
+        return match self {
+            Self::Normal => "",
+            Self::Fast => "@100ms",
+        };
+    }
+}
+
+/// The number of levels pushed by a partial depth stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthLevels {
+    Five,
+    Ten,
+    Twenty,
+}
+
+impl DepthLevels {
+    #[must_use]
+    pub const fn count(self) -> u32 {
+        return match self {
+            Self::Five => 5,
+            Self::Ten => 10,
+            Self::Twenty => 20,
+        };
+    }
+}
+
+/// The top levels of an order book, pushed by the `<symbol>@depth<levels>`
+/// streams in the shape of a depth snapshot.
+pub type PartialDepthEvent = OrderBook;
+
+/// The best bid and ask of a symbol, pushed by the `<symbol>@bookTicker`
+/// streams on every change.
+#[derive(Debug, Deserialize)]
+pub struct BookTickerEvent {
+    #[serde(rename = "u")]
+    pub update_id: u64,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "b")]
+    pub best_bid_price: Decimal,
+
+    #[serde(rename = "B")]
+    pub best_bid_quantity: Decimal,
+
+    #[serde(rename = "a")]
+    pub best_ask_price: Decimal,
+
+    #[serde(rename = "A")]
+    pub best_ask_quantity: Decimal,
+}
+
+/// The changes of an order book between two update ids, pushed by the
+/// `<symbol>@depth` streams. Levels with a zero quantity were removed.
+#[derive(Debug, Deserialize)]
+pub struct DepthUpdateEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "U")]
+    pub first_update_id: u64,
+
+    #[serde(rename = "u")]
+    pub final_update_id: u64,
+
+    #[serde(rename = "b")]
+    pub bids: Vec<Order>,
+
+    #[serde(rename = "a")]
+    pub asks: Vec<Order>,
+}
+
+/// An event of the user data stream, identified by its `e` field.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "e")]
+pub enum UserDataEvent {
+    #[serde(rename = "executionReport")]
+    ExecutionReport(Box<ExecutionReportEvent>),
+
+    #[serde(rename = "outboundAccountPosition")]
+    OutboundAccountPosition(OutboundAccountPositionEvent),
+
+    #[serde(rename = "balanceUpdate")]
+    BalanceUpdate(BalanceUpdateEvent),
+
+    #[serde(rename = "listStatus")]
+    ListStatus(ListStatusEvent),
+
+    /// The listen key of the stream expired; no more events are sent on it.
+    #[serde(rename = "listenKeyExpired")]
+    ListenKeyExpired(ListenKeyExpiredEvent),
+
+    /// The WebSocket API subscription of the stream ended.
+    #[serde(rename = "eventStreamTerminated")]
+    EventStreamTerminated(EventStreamTerminatedEvent),
+
+    /// An event type not known to this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExecutionType {
+    New,
+    Canceled,
+    Replaced,
+    Rejected,
+    Trade,
+    Expired,
+    TradePrevention,
+}
+
+/// An update of one of the account's orders.
+#[derive(Debug, Deserialize)]
+pub struct ExecutionReportEvent {
+    #[serde(rename = "E")]
+    pub event_time: i64,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "c")]
+    pub client_order_id: String,
+
+    #[serde(rename = "S")]
+    pub side: OrderSide,
+
+    #[serde(rename = "o")]
+    pub order_type: OrderType,
+
+    #[serde(rename = "f")]
+    pub time_in_force: TimeInForce,
+
+    #[serde(rename = "q")]
+    pub quantity: Decimal,
+
+    #[serde(rename = "p")]
+    pub price: Decimal,
+
+    #[serde(rename = "P")]
+    pub stop_price: Decimal,
+
+    #[serde(rename = "F")]
+    pub iceberg_quantity: Decimal,
+
+    #[serde(rename = "g")]
+    pub order_list_id: i64,
+
+    /// The client order id of the canceled order, or an empty string.
+    #[serde(rename = "C")]
+    pub orig_client_order_id: String,
+
+    #[serde(rename = "x")]
+    pub execution_type: ExecutionType,
+
+    #[serde(rename = "X")]
+    pub order_status: OrderStatus,
+
+    #[serde(rename = "r")]
+    pub reject_reason: String,
+
+    #[serde(rename = "i")]
+    pub order_id: u64,
+
+    #[serde(rename = "l")]
+    pub last_executed_quantity: Decimal,
+
+    #[serde(rename = "z")]
+    pub cumulative_filled_quantity: Decimal,
+
+    #[serde(rename = "L")]
+    pub last_executed_price: Decimal,
+
+    #[serde(rename = "n")]
+    pub commission_amount: Decimal,
+
+    #[serde(rename = "N")]
+    pub commission_asset: Option<String>,
+
+    #[serde(rename = "T")]
+    pub transaction_time: i64,
+
+    /// The trade id, or -1 if the event is not a trade.
+    #[serde(rename = "t")]
+    pub trade_id: i64,
+
+    #[serde(rename = "w")]
+    pub is_working: bool,
+
+    #[serde(rename = "m")]
+    pub is_maker: bool,
+
+    #[serde(rename = "O")]
+    pub order_creation_time: i64,
+
+    #[serde(rename = "Z")]
+    pub cumulative_quote_quantity: Decimal,
+
+    #[serde(rename = "Y")]
+    pub last_quote_quantity: Decimal,
+
+    #[serde(rename = "Q")]
+    pub quote_order_quantity: Decimal,
+
+    #[serde(rename = "W")]
+    pub working_time: Option<i64>,
+
+    #[serde(rename = "V")]
+    pub self_trade_prevention_mode: SelfTradePreventionMode,
+}
+
+/// The balances of the assets that changed with an account update.
+#[derive(Debug, Deserialize)]
+pub struct OutboundAccountPositionEvent {
+    #[serde(rename = "E")]
+    pub event_time: i64,
+
+    #[serde(rename = "u")]
+    pub last_update_time: i64,
+
+    #[serde(rename = "B")]
+    pub balances: Vec<BalanceEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BalanceEntry {
+    #[serde(rename = "a")]
+    pub asset: String,
+
+    #[serde(rename = "f")]
+    pub free: Decimal,
+
+    #[serde(rename = "l")]
+    pub locked: Decimal,
+}
+
+/// A deposit, withdrawal or transfer of an asset.
+#[derive(Debug, Deserialize)]
+pub struct BalanceUpdateEvent {
+    #[serde(rename = "E")]
+    pub event_time: i64,
+
+    #[serde(rename = "a")]
+    pub asset: String,
+
+    #[serde(rename = "d")]
+    pub delta: Decimal,
+
+    #[serde(rename = "T")]
+    pub clear_time: i64,
+}
+
+/// An update of one of the account's order lists.
+#[derive(Debug, Deserialize)]
+pub struct ListStatusEvent {
+    #[serde(rename = "E")]
+    pub event_time: i64,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "g")]
+    pub order_list_id: i64,
+
+    #[serde(rename = "c")]
+    pub contingency_type: ContingencyType,
+
+    #[serde(rename = "l")]
+    pub list_status_type: ListStatusType,
+
+    #[serde(rename = "L")]
+    pub list_order_status: ListOrderStatus,
+
+    #[serde(rename = "r")]
+    pub list_reject_reason: String,
+
+    #[serde(rename = "C")]
+    pub list_client_order_id: String,
+
+    #[serde(rename = "T")]
+    pub transaction_time: i64,
+
+    #[serde(rename = "O")]
+    pub orders: Vec<ListStatusOrder>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListStatusOrder {
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "i")]
+    pub order_id: u64,
+
+    #[serde(rename = "c")]
+    pub client_order_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListenKeyExpiredEvent {
+    /// Sent as a string for this event.
+    #[serde(rename = "E", deserialize_with = "deserialize_string_or_number")]
+    pub event_time: i64,
+
+    pub listen_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventStreamTerminatedEvent {
+    #[serde(rename = "E")]
+    pub event_time: i64,
+}
+
+fn deserialize_string_or_number<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<i64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        Number(i64),
+        String(String),
+    }
+
+    return match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::Number(value) => Ok(value),
+        StringOrNumber::String(value) => value.parse().map_err(serde::de::Error::custom),
+    };
+}
@@ -4,4 +4,168 @@ use serde::{Deserialize, Serialize};
 pub struct Error {
     pub code: i32,
     pub msg: String,
+    /// Details of partially failed requests, e.g. the cancel and new order
+    /// results of a failed cancel-replace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl Error {
+    #[must_use]
+    pub const fn error_code(&self) -> BinanceErrorCode {
+        return BinanceErrorCode::from_code(self.code);
+    }
+}
+
+macro_rules! error_codes {
+    ($($(#[$meta:meta])* $name:ident = $code:literal,)*) => {
+        /// Error codes returned by the Binance spot API in the `code` field of error payloads.
+        ///
+        /// Codes that are not known to this crate are kept as [`BinanceErrorCode::Other`].
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum BinanceErrorCode {
+            $($(#[$meta])* $name,)*
+            Other(i32),
+        }
+
+        impl BinanceErrorCode {
+            #[must_use]
+            pub const fn from_code(code: i32) -> Self {
+                return match code {
+                    $($code => Self::$name,)*
+                    other => Self::Other(other),
+                };
+            }
+
+            #[must_use]
+            pub const fn code(self) -> i32 {
+                return match self {
+                    $(Self::$name => $code,)*
+                    Self::Other(code) => code,
+                };
+            }
+        }
+    };
+}
+
+error_codes! {
+    /// An unknown error occurred while processing the request.
+    Unknown = -1000,
+    /// Internal error; unable to process the request.
+    Disconnected = -1001,
+    Unauthorized = -1002,
+    /// Too many requests queued or request weight exceeded.
+    TooManyRequests = -1003,
+    /// An unexpected response was received; execution status unknown.
+    UnexpectedResponse = -1006,
+    /// Timeout waiting for response from the backend; execution status unknown.
+    Timeout = -1007,
+    /// The server is currently overloaded with other requests.
+    ServerBusy = -1008,
+    /// The request was rejected by a symbol or exchange filter.
+    InvalidMessage = -1013,
+    UnknownOrderComposition = -1014,
+    /// Too many new orders.
+    TooManyOrders = -1015,
+    ServiceShuttingDown = -1016,
+    UnsupportedOperation = -1020,
+    /// Timestamp for this request is outside of the recvWindow.
+    InvalidTimestamp = -1021,
+    /// Signature for this request is not valid.
+    InvalidSignature = -1022,
+    IllegalChars = -1100,
+    TooManyParameters = -1101,
+    MandatoryParamEmptyOrMalformed = -1102,
+    UnknownParam = -1103,
+    UnreadParameters = -1104,
+    ParamEmpty = -1105,
+    ParamNotRequired = -1106,
+    ParamOverflow = -1108,
+    BadPrecision = -1111,
+    NoDepth = -1112,
+    TimeInForceNotRequired = -1114,
+    InvalidTimeInForce = -1115,
+    InvalidOrderType = -1116,
+    InvalidSide = -1117,
+    EmptyNewClientOrderId = -1118,
+    EmptyOrigClientOrderId = -1119,
+    BadInterval = -1120,
+    BadSymbol = -1121,
+    InvalidSymbolStatus = -1122,
+    InvalidListenKey = -1125,
+    MoreThanXxHours = -1127,
+    OptionalParamsBadCombo = -1128,
+    InvalidParameter = -1130,
+    BadStrategyType = -1134,
+    InvalidJson = -1135,
+    InvalidTickerType = -1139,
+    InvalidCancelRestrictions = -1145,
+    DuplicateSymbols = -1151,
+    OcoOrderTypeRejected = -1158,
+    OcoIcebergQtyTimeInForce = -1160,
+    BuyOcoLimitMustBeBelow = -1165,
+    SellOcoLimitMustBeAbove = -1166,
+    BothOcoOrdersCannotBeLimit = -1168,
+    ParamsBadCombo = -1182,
+    InvalidRequestId = -1190,
+    TooManySubscriptions = -1191,
+    InvalidTimeUnit = -1194,
+    BuyOcoStopLossMustBeAbove = -1196,
+    SellOcoStopLossMustBeBelow = -1197,
+    BuyOcoTakeProfitMustBeBelow = -1198,
+    SellOcoTakeProfitMustBeAbove = -1199,
+    /// The new order was rejected by the matching engine.
+    NewOrderRejected = -2010,
+    /// The cancel request was rejected by the matching engine.
+    CancelRejected = -2011,
+    /// Order does not exist.
+    NoSuchOrder = -2013,
+    BadApiKeyFormat = -2014,
+    /// Invalid API key, IP, or permissions for action.
+    RejectedApiKey = -2015,
+    NoTradingWindow = -2016,
+    /// Cancel-replace partially failed.
+    CancelReplacePartiallyFailed = -2021,
+    /// Cancel-replace failed.
+    CancelReplaceFailed = -2022,
+    OrderArchived = -2026,
+    SubscriptionActive = -2035,
+    SubscriptionInactive = -2036,
+    ClientOrderIdInvalid = -2039,
+    MaximumSubscriptionIds = -2042,
+}
+
+impl BinanceErrorCode {
+    /// The request was rejected because a request weight or order rate limit was exceeded.
+    #[must_use]
+    pub const fn is_rate_limited(self) -> bool {
+        return matches!(self, Self::TooManyRequests | Self::TooManyOrders);
+    }
+
+    /// The request may or may not have been executed, so its outcome must be
+    /// checked (e.g. by querying the order) before it is sent again.
+    #[must_use]
+    pub const fn is_unknown_execution_status(self) -> bool {
+        return matches!(self, Self::UnexpectedResponse | Self::Timeout);
+    }
+
+    /// The request was not executed and can be sent again, possibly after
+    /// backing off or resynchronising the clock.
+    #[must_use]
+    pub const fn is_retryable(self) -> bool {
+        return matches!(
+            self,
+            Self::Disconnected
+                | Self::TooManyRequests
+                | Self::ServerBusy
+                | Self::TooManyOrders
+                | Self::InvalidTimestamp
+        );
+    }
+}
+
+impl From<i32> for BinanceErrorCode {
+    fn from(code: i32) -> Self {
+        return Self::from_code(code);
+    }
 }
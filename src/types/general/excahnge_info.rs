@@ -1,20 +1,155 @@
+use std::fmt;
+
 use serde::Deserialize;
 
+use super::filters::{ExchangeFilter, LotSizeFilter, PriceFilter, SymbolFilter};
+use crate::types::trading::OrderType;
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExchangeInfo {
     pub timezone: String,
     pub server_time: u64,
     pub rate_limits: Vec<RateLimit>,
-    pub exchange_filters: Vec<serde_json::Value>,
+    pub exchange_filters: Vec<ExchangeFilter>,
     pub symbols: Vec<SymbolInfo>,
     pub sors: Option<Vec<Sor>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct RateLimit {
-    #[serde(flatten)]
-    pub details: serde_json::Value,
+    pub rate_limit_type: RateLimitType,
+    pub interval: RateLimitInterval,
+    pub interval_num: u32,
+    pub limit: u32,
+}
+
+impl RateLimit {
+    /// Whether the type and interval of the limit are known, so that it can
+    /// be tracked.
+    #[must_use]
+    pub const fn is_known(&self) -> bool {
+        return !matches!(self.rate_limit_type, RateLimitType::Unknown)
+            && !matches!(self.interval, RateLimitInterval::Unknown);
+    }
+
+    /// The length of the rate limit window in milliseconds.
+    #[must_use]
+    pub const fn window_millis(&self) -> i64 {
+        return self.interval.millis() * self.interval_num as i64;
+    }
+
+    /// The response header reporting the current usage of this limit
+    /// (e.g. `x-mbx-used-weight-1m`), if Binance sends one.
+    #[must_use]
+    pub fn header_name(&self) -> Option<String> {
+        let prefix = match self.rate_limit_type {
+            RateLimitType::RequestWeight => "x-mbx-used-weight",
+            RateLimitType::Orders => "x-mbx-order-count",
+            RateLimitType::RawRequests | RateLimitType::Unknown => return None,
+        };
+        return Some(format!(
+            "{prefix}-{}{}",
+            self.interval_num,
+            self.interval.letter()
+        ));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RateLimitType {
+    RequestWeight,
+    Orders,
+    RawRequests,
+    /// A limit type added after this version; it is not tracked.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RateLimitInterval {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    /// An interval added after this version; limits over it are not tracked.
+    #[serde(other)]
+    Unknown,
+}
+
+impl RateLimitInterval {
+    /// The length of the interval in milliseconds, zero if it is unknown.
+    #[must_use]
+    pub const fn millis(self) -> i64 {
+        return match self {
+            Self::Second => 1_000,
+            Self::Minute => 60_000,
+            Self::Hour => 3_600_000,
+            Self::Day => 86_400_000,
+            Self::Unknown => 0,
+        };
+    }
+
+    /// The letter used for the interval in rate limit headers.
+    const fn letter(self) -> char {
+        return match self {
+            Self::Second => 's',
+            Self::Minute => 'm',
+            Self::Hour => 'h',
+            Self::Day => 'd',
+            Self::Unknown => '?',
+        };
+    }
+}
+
+/// The trading status of a symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SymbolStatus {
+    PreTrading,
+    Trading,
+    PostTrading,
+    EndOfDay,
+    Halt,
+    AuctionMatch,
+    Break,
+    /// A status added after this version.
+    #[serde(other)]
+    Unknown,
+}
+
+impl fmt::Display for SymbolStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::PreTrading => "PRE_TRADING",
+            Self::Trading => "TRADING",
+            Self::PostTrading => "POST_TRADING",
+            Self::EndOfDay => "END_OF_DAY",
+            Self::Halt => "HALT",
+            Self::AuctionMatch => "AUCTION_MATCH",
+            Self::Break => "BREAK",
+            Self::Unknown => "UNKNOWN",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Which symbols to return the exchange information of. The default query
+/// returns every symbol.
+///
+/// `permissions` and `symbol_status` cannot be combined with `symbols`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExchangeInfoQuery {
+    pub symbols: Vec<String>,
+    /// Only symbols with any of these permissions, e.g. `SPOT` or `MARGIN`.
+    pub permissions: Vec<String>,
+    /// Whether `permission_sets` is filled in; Binance defaults to true.
+    pub show_permission_sets: Option<bool>,
+    /// Only symbols with this status.
+    pub symbol_status: Option<SymbolStatus>,
 }
 
 #[allow(clippy::struct_excessive_bools)]
@@ -22,7 +157,7 @@ pub struct RateLimit {
 #[serde(rename_all = "camelCase")]
 pub struct SymbolInfo {
     pub symbol: String,
-    pub status: String,
+    pub status: SymbolStatus,
     pub base_asset: String,
     pub base_asset_precision: u8,
     pub quote_asset: String,
@@ -30,7 +165,7 @@ pub struct SymbolInfo {
     pub quote_asset_precision: u8,
     pub base_commission_precision: u8,
     pub quote_commission_precision: u8,
-    pub order_types: Vec<String>,
+    pub order_types: Vec<OrderType>,
     pub iceberg_allowed: bool,
     pub oco_allowed: bool,
     pub oto_allowed: bool,
@@ -40,13 +175,41 @@ pub struct SymbolInfo {
     pub allow_amend: Option<bool>,
     pub is_spot_trading_allowed: bool,
     pub is_margin_trading_allowed: bool,
-    pub filters: Vec<serde_json::Value>,
+    pub filters: Vec<SymbolFilter>,
     pub permissions: Vec<String>,
     pub permission_sets: Vec<Vec<String>>,
     pub default_self_trade_prevention_mode: String,
     pub allowed_self_trade_prevention_modes: Vec<String>,
 }
 
+impl SymbolInfo {
+    #[must_use]
+    pub fn price_filter(&self) -> Option<&PriceFilter> {
+        return self.filters.iter().find_map(|filter| match filter {
+            SymbolFilter::PriceFilter(filter) => Some(filter),
+            _ => None,
+        });
+    }
+
+    #[must_use]
+    pub fn lot_size(&self) -> Option<&LotSizeFilter> {
+        return self.filters.iter().find_map(|filter| match filter {
+            SymbolFilter::LotSize(filter) => Some(filter),
+            _ => None,
+        });
+    }
+
+    /// The quantity rules of market orders, when they differ from the
+    /// [`lot_size`](Self::lot_size).
+    #[must_use]
+    pub fn market_lot_size(&self) -> Option<&LotSizeFilter> {
+        return self.filters.iter().find_map(|filter| match filter {
+            SymbolFilter::MarketLotSize(filter) if !filter.step_size.is_zero() => Some(filter),
+            _ => None,
+        });
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Sor {
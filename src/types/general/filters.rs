@@ -0,0 +1,155 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+/// A trading rule of a symbol, identified by its `filterType`.
+///
+/// Filter types added by Binance after this version are deserialized as
+/// [`SymbolFilter::Unknown`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "filterType", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SymbolFilter {
+    PriceFilter(PriceFilter),
+    PercentPrice(PercentPriceFilter),
+    PercentPriceBySide(PercentPriceBySideFilter),
+    LotSize(LotSizeFilter),
+    MinNotional(MinNotionalFilter),
+    Notional(NotionalFilter),
+    IcebergParts(IcebergPartsFilter),
+    MarketLotSize(LotSizeFilter),
+    MaxNumOrders(MaxNumOrdersFilter),
+    MaxNumOrderLists(MaxNumOrderListsFilter),
+    MaxNumAlgoOrders(MaxNumAlgoOrdersFilter),
+    MaxNumIcebergOrders(MaxNumIcebergOrdersFilter),
+    MaxNumOrderAmends(MaxNumOrderAmendsFilter),
+    MaxPosition(MaxPositionFilter),
+    TrailingDelta(TrailingDeltaFilter),
+    #[serde(other)]
+    Unknown,
+}
+
+/// A trading rule of the exchange, identified by its `filterType`.
+///
+/// Filter types added by Binance after this version are deserialized as
+/// [`ExchangeFilter::Unknown`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "filterType", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExchangeFilter {
+    ExchangeMaxNumOrders(MaxNumOrdersFilter),
+    ExchangeMaxNumOrderLists(MaxNumOrderListsFilter),
+    ExchangeMaxNumAlgoOrders(MaxNumAlgoOrdersFilter),
+    ExchangeMaxNumIcebergOrders(MaxNumIcebergOrdersFilter),
+    #[serde(other)]
+    Unknown,
+}
+
+/// The price range and tick size of orders. A zero bound is not enforced.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceFilter {
+    pub min_price: Decimal,
+    pub max_price: Decimal,
+    pub tick_size: Decimal,
+}
+
+/// The price range of orders relative to the average price of the last
+/// `avg_price_mins` minutes.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PercentPriceFilter {
+    pub multiplier_up: Decimal,
+    pub multiplier_down: Decimal,
+    pub avg_price_mins: u32,
+}
+
+/// The price range of orders relative to the average price of the last
+/// `avg_price_mins` minutes, by order side.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PercentPriceBySideFilter {
+    pub bid_multiplier_up: Decimal,
+    pub bid_multiplier_down: Decimal,
+    pub ask_multiplier_up: Decimal,
+    pub ask_multiplier_down: Decimal,
+    pub avg_price_mins: u32,
+}
+
+/// The quantity range and step size of orders, or of market orders for
+/// [`SymbolFilter::MarketLotSize`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LotSizeFilter {
+    pub min_qty: Decimal,
+    pub max_qty: Decimal,
+    pub step_size: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinNotionalFilter {
+    pub min_notional: Decimal,
+    pub apply_to_market: bool,
+    pub avg_price_mins: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotionalFilter {
+    pub min_notional: Decimal,
+    pub apply_min_to_market: bool,
+    pub max_notional: Decimal,
+    pub apply_max_to_market: bool,
+    pub avg_price_mins: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct IcebergPartsFilter {
+    pub limit: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaxNumOrdersFilter {
+    pub max_num_orders: u32,
+}
+
+/// The maximum number of open order lists.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaxNumOrderListsFilter {
+    pub max_num_order_lists: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaxNumAlgoOrdersFilter {
+    pub max_num_algo_orders: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaxNumIcebergOrdersFilter {
+    pub max_num_iceberg_orders: u32,
+}
+
+/// The maximum number of times an order can be amended.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaxNumOrderAmendsFilter {
+    pub max_num_order_amends: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaxPositionFilter {
+    pub max_position: Decimal,
+}
+
+/// The range of trailing deltas, in basis points.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrailingDeltaFilter {
+    pub min_trailing_above_delta: u32,
+    pub max_trailing_above_delta: u32,
+    pub min_trailing_below_delta: u32,
+    pub max_trailing_below_delta: u32,
+}
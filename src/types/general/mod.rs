@@ -1,7 +1,17 @@
 mod error;
 mod excahnge_info;
+mod filters;
 mod timestamp;
 
-pub use error::Error;
-pub use excahnge_info::{ExchangeInfo, RateLimit, Sor, SymbolInfo};
+pub use error::{BinanceErrorCode, Error};
+pub use excahnge_info::{
+    ExchangeInfo, ExchangeInfoQuery, RateLimit, RateLimitInterval, RateLimitType, Sor, SymbolInfo,
+    SymbolStatus,
+};
+pub use filters::{
+    ExchangeFilter, IcebergPartsFilter, LotSizeFilter, MaxNumAlgoOrdersFilter,
+    MaxNumIcebergOrdersFilter, MaxNumOrderAmendsFilter, MaxNumOrderListsFilter, MaxNumOrdersFilter,
+    MaxPositionFilter, MinNotionalFilter, NotionalFilter, PercentPriceBySideFilter,
+    PercentPriceFilter, PriceFilter, SymbolFilter, TrailingDeltaFilter,
+};
 pub use timestamp::Timestamp;
@@ -10,7 +10,7 @@ pub struct OrderBook {
 }
 
 // Define a single struct for both bids and asks
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Order {
     pub price: Decimal,
     pub quantity: Decimal,
@@ -0,0 +1,194 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::order::{
+    Order, OrderCreationData, OrderSide, OrderStatus, OrderType, SelfTradePreventionMode,
+    TimeInForce,
+};
+use super::order_list::{ContingencyType, ListOrderStatus, OrderListOrder};
+use crate::errors::BinanceError;
+use crate::types::general::{BinanceErrorCode, Error};
+
+/// What a cancel-replace does when the cancel fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CancelReplaceMode {
+    /// The new order is not placed if the cancel fails.
+    StopOnFailure,
+    /// The new order is placed regardless of the cancel result.
+    AllowFailure,
+}
+
+/// Only cancel the order if it has the given status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CancelRestrictions {
+    OnlyNew,
+    OnlyPartiallyFilled,
+}
+
+/// What a cancel-replace does when the unfilled order count limit is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OrderRateLimitExceededMode {
+    /// Neither cancel nor place an order.
+    DoNothing,
+    /// Cancel the order anyway.
+    CancelOnly,
+}
+
+/// Cancels an existing order and places a new order on the same symbol in
+/// one request. The order to cancel is identified by `cancel_order_id` or
+/// `cancel_orig_client_order_id`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelReplaceOrderData {
+    pub cancel_replace_mode: CancelReplaceMode,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancel_order_id: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancel_orig_client_order_id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancel_new_client_order_id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancel_restrictions: Option<CancelRestrictions>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_rate_limit_exceeded_mode: Option<OrderRateLimitExceededMode>,
+
+    #[serde(flatten)]
+    pub order: OrderCreationData,
+}
+
+impl CancelReplaceOrderData {
+    /// Replaces the order `cancel_order_id` with `order`.
+    #[must_use]
+    pub const fn new(
+        cancel_replace_mode: CancelReplaceMode,
+        cancel_order_id: u64,
+        order: OrderCreationData,
+    ) -> Self {
+        return Self {
+            cancel_replace_mode,
+            cancel_order_id: Some(cancel_order_id),
+            cancel_orig_client_order_id: None,
+            cancel_new_client_order_id: None,
+            cancel_restrictions: None,
+            order_rate_limit_exceeded_mode: None,
+            order,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CancelReplaceStatus {
+    Success,
+    Failure,
+    NotAttempted,
+}
+
+/// The response to one half of a cancel-replace: the canceled or new order,
+/// or the error that prevented it.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum CancelReplaceResponse {
+    Order(Box<Order>),
+    Error(Error),
+}
+
+/// The outcome of a cancel-replace. When only one of the two operations
+/// succeeded, the failed one is reported with its error instead of failing
+/// the whole request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelReplaceResult {
+    pub cancel_result: CancelReplaceStatus,
+    pub new_order_result: CancelReplaceStatus,
+    pub cancel_response: Option<CancelReplaceResponse>,
+    pub new_order_response: Option<CancelReplaceResponse>,
+}
+
+impl CancelReplaceResult {
+    /// Turns the -2021 and -2022 errors of a cancel-replace request into the
+    /// cancel and new order results they carry.
+    pub(crate) fn from_result(result: Result<Self, BinanceError>) -> Result<Self, BinanceError> {
+        if let Err(BinanceError::Api(_, error)) = &result
+            && matches!(
+                error.error_code(),
+                BinanceErrorCode::CancelReplacePartiallyFailed
+                    | BinanceErrorCode::CancelReplaceFailed
+            )
+            && let Some(data) = &error.data
+        {
+            return Ok(serde_json::from_value(data.clone())?);
+        }
+        return result;
+    }
+
+    /// Whether the old order was canceled and the new one placed.
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        return self.cancel_result == CancelReplaceStatus::Success
+            && self.new_order_result == CancelReplaceStatus::Success;
+    }
+
+    /// The new order, if it was placed.
+    #[must_use]
+    pub fn new_order(&self) -> Option<&Order> {
+        return match &self.new_order_response {
+            Some(CancelReplaceResponse::Order(order)) => Some(order),
+            _ => None,
+        };
+    }
+}
+
+/// The response of `PUT /api/v3/order/amend/keepPriority`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendedOrderResult {
+    pub transact_time: i64,
+    pub execution_id: u64,
+    pub amended_order: AmendedOrder,
+    /// Set when the order belongs to an order list.
+    pub list_status: Option<AmendedOrderList>,
+}
+
+/// An order after its quantity was reduced.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendedOrder {
+    pub symbol: String,
+    pub order_id: u64,
+    pub order_list_id: i64,
+    pub orig_client_order_id: String,
+    pub client_order_id: String,
+    pub price: Decimal,
+    pub qty: Decimal,
+    pub executed_qty: Decimal,
+    pub prevented_qty: Decimal,
+    pub quote_order_qty: Decimal,
+    pub cumulative_quote_qty: Decimal,
+    pub status: OrderStatus,
+    pub time_in_force: TimeInForce,
+    #[serde(rename = "type")]
+    pub type_: OrderType,
+    pub side: OrderSide,
+    pub working_time: Option<i64>,
+    pub self_trade_prevention_mode: SelfTradePreventionMode,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendedOrderList {
+    pub order_list_id: i64,
+    pub contingency_type: ContingencyType,
+    pub list_order_status: ListOrderStatus,
+    pub list_client_order_id: String,
+    pub symbol: String,
+    pub orders: Vec<OrderListOrder>,
+}
@@ -0,0 +1,29 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+/// The commission rates that would apply to an order, as returned by
+/// `POST /api/v3/order/test` with `computeCommissionRates=true`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderCommissionRates {
+    pub standard_commission_for_order: CommissionRate,
+    pub special_commission_for_order: CommissionRate,
+    pub tax_commission_for_order: CommissionRate,
+    pub discount: CommissionDiscount,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct CommissionRate {
+    pub maker: Decimal,
+    pub taker: Decimal,
+}
+
+/// The discount on standard commissions when they are paid in `discount_asset` (BNB).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommissionDiscount {
+    pub enabled_for_account: bool,
+    pub enabled_for_symbol: bool,
+    pub discount_asset: String,
+    pub discount: Decimal,
+}
@@ -1,6 +1,19 @@
+mod cancel_replace;
+mod commission;
 mod order;
+mod order_list;
 
+pub use cancel_replace::{
+    AmendedOrder, AmendedOrderList, AmendedOrderResult, CancelReplaceMode, CancelReplaceOrderData,
+    CancelReplaceResponse, CancelReplaceResult, CancelReplaceStatus, CancelRestrictions,
+    OrderRateLimitExceededMode,
+};
+pub use commission::{CommissionDiscount, CommissionRate, OrderCommissionRates};
 pub use order::{
-    CommonOrderCreateData, Order, OrderCreationData, OrderSide, OrderStatus,
+    CommonOrderCreateData, Order, OrderCreationData, OrderSide, OrderStatus, OrderType,
     SelfTradePreventionMode, TimeInForce,
 };
+pub use order_list::{
+    ContingencyType, ListOrderStatus, ListStatusType, OcoLeg, OcoOrderCreateData, OrderList,
+    OrderListLeg, OrderListOrder, OtoOrderCreateData, OtocoOrderCreateData,
+};
@@ -12,7 +12,6 @@ fn get_order_result_type() -> String {
 pub struct CommonOrderCreateData {
     pub symbol: String,
     pub side: OrderSide,
-    pub timestamp: u64,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub new_client_order_id: Option<String>,
@@ -38,7 +37,6 @@ impl Default for CommonOrderCreateData {
         Self {
             symbol: String::new(),
             side: OrderSide::default(),
-            timestamp: chrono::Utc::now().timestamp_millis() as u64,
             new_client_order_id: None,
             strategy_id: None,
             strategy_type: None,
@@ -131,7 +129,35 @@ pub enum OrderCreationData {
     },
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+impl OrderCreationData {
+    /// The parameters shared by every order type.
+    #[must_use]
+    pub const fn common(&self) -> &CommonOrderCreateData {
+        return match self {
+            Self::Limit { common, .. }
+            | Self::Market { common, .. }
+            | Self::StopLoss { common, .. }
+            | Self::StopLossLimit { common, .. }
+            | Self::TakeProfit { common, .. }
+            | Self::TakeProfitLimit { common, .. }
+            | Self::LimitMaker { common, .. } => common,
+        };
+    }
+
+    pub const fn common_mut(&mut self) -> &mut CommonOrderCreateData {
+        return match self {
+            Self::Limit { common, .. }
+            | Self::Market { common, .. }
+            | Self::StopLoss { common, .. }
+            | Self::StopLossLimit { common, .. }
+            | Self::TakeProfit { common, .. }
+            | Self::TakeProfitLimit { common, .. }
+            | Self::LimitMaker { common, .. } => common,
+        };
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum OrderSide {
     #[default]
@@ -139,6 +165,22 @@ pub enum OrderSide {
     Sell,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OrderType {
+    Limit,
+    Market,
+    StopLoss,
+    StopLossLimit,
+    TakeProfit,
+    TakeProfitLimit,
+    LimitMaker,
+    /// An order type added after this version, e.g. in the `orderTypes` of
+    /// exchange information. It cannot be used to place orders.
+    #[serde(other)]
+    Unknown,
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum TimeInForce {
@@ -190,7 +232,7 @@ pub struct Order {
     pub status: OrderStatus,
     pub time_in_force: TimeInForce,
     #[serde(rename = "type")]
-    pub type_: String,
+    pub type_: OrderType,
     pub side: OrderSide,
     pub working_time: Option<i64>,
     pub self_trade_prevention_mode: SelfTradePreventionMode,
@@ -0,0 +1,361 @@
+use rust_decimal::Decimal;
+use serde::ser::Error as _;
+use serde::{Deserialize, Serialize, Serializer};
+
+use super::order::{Order, OrderSide, OrderType, SelfTradePreventionMode, TimeInForce};
+
+/// Serializes the fields of `value` with `prefix` prepended to their names,
+/// e.g. `type` as `aboveType`.
+fn serialize_prefixed<S: Serializer>(
+    prefix: &str,
+    value: &impl Serialize,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let serde_json::Value::Object(fields) =
+        serde_json::to_value(value).map_err(S::Error::custom)?
+    else {
+        return Err(S::Error::custom("expected a struct"));
+    };
+    return serializer.collect_map(fields.iter().map(|(name, value)| {
+        let mut chars = name.chars();
+        let first = chars.next().map(|c| c.to_ascii_uppercase());
+        let name = format!("{prefix}{}{}", first.unwrap_or_default(), chars.as_str());
+        return (name, value);
+    }));
+}
+
+macro_rules! prefixed_serializers {
+    ($($name:ident: $type:ty => $prefix:literal),* $(,)?) => {
+        $(
+            fn $name<S: Serializer>(leg: &$type, serializer: S) -> Result<S::Ok, S::Error> {
+                return serialize_prefixed($prefix, leg, serializer);
+            }
+        )*
+    };
+}
+
+prefixed_serializers! {
+    serialize_above: OcoLeg => "above",
+    serialize_below: OcoLeg => "below",
+    serialize_working: OrderListLeg => "working",
+    serialize_pending: OrderListLeg => "pending",
+    serialize_pending_above: OcoLeg => "pendingAbove",
+    serialize_pending_below: OcoLeg => "pendingBelow",
+}
+
+/// A one-cancels-the-other order list: two orders on the same side, one
+/// priced above and one below the market. When one of them is filled or
+/// canceled, the other one is canceled.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcoOrderCreateData {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: Decimal,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub list_client_order_id: Option<String>,
+
+    #[serde(flatten, serialize_with = "serialize_above")]
+    pub above: OcoLeg,
+
+    #[serde(flatten, serialize_with = "serialize_below")]
+    pub below: OcoLeg,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub self_trade_prevention_mode: Option<SelfTradePreventionMode>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recv_window: Option<i64>,
+
+    pub new_order_resp_type: String,
+}
+
+impl OcoOrderCreateData {
+    #[must_use]
+    pub fn new(
+        symbol: String,
+        side: OrderSide,
+        quantity: Decimal,
+        above: OcoLeg,
+        below: OcoLeg,
+    ) -> Self {
+        return Self {
+            symbol,
+            side,
+            quantity,
+            list_client_order_id: None,
+            above,
+            below,
+            self_trade_prevention_mode: None,
+            recv_window: None,
+            new_order_resp_type: "RESULT".to_string(),
+        };
+    }
+}
+
+/// One of the two orders of an OCO order list. The fields are sent with an
+/// `above` or `below` prefix, e.g. `aboveType` and `belowStopPrice`.
+///
+/// The above leg must be a `LIMIT_MAKER`, `STOP_LOSS(_LIMIT)` or
+/// `TAKE_PROFIT(_LIMIT)` order, the below leg a `STOP_LOSS(_LIMIT)` or
+/// `TAKE_PROFIT(_LIMIT)` order (or `LIMIT_MAKER` for buy lists).
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcoLeg {
+    #[serde(rename = "type")]
+    pub type_: OrderType,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<Decimal>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_price: Option<Decimal>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trailing_delta: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_in_force: Option<TimeInForce>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iceberg_qty: Option<Decimal>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy_id: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy_type: Option<i32>,
+}
+
+impl OcoLeg {
+    /// A leg of the given type; prices and other parameters are set on the
+    /// returned value.
+    #[must_use]
+    pub const fn new(type_: OrderType) -> Self {
+        return Self {
+            type_,
+            client_order_id: None,
+            price: None,
+            stop_price: None,
+            trailing_delta: None,
+            time_in_force: None,
+            iceberg_qty: None,
+            strategy_id: None,
+            strategy_type: None,
+        };
+    }
+}
+
+/// A one-triggers-the-other order list: a working order that places a
+/// pending order once it is filled.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtoOrderCreateData {
+    pub symbol: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub list_client_order_id: Option<String>,
+
+    #[serde(flatten, serialize_with = "serialize_working")]
+    pub working: OrderListLeg,
+
+    #[serde(flatten, serialize_with = "serialize_pending")]
+    pub pending: OrderListLeg,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub self_trade_prevention_mode: Option<SelfTradePreventionMode>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recv_window: Option<i64>,
+
+    pub new_order_resp_type: String,
+}
+
+impl OtoOrderCreateData {
+    #[must_use]
+    pub fn new(symbol: String, working: OrderListLeg, pending: OrderListLeg) -> Self {
+        return Self {
+            symbol,
+            list_client_order_id: None,
+            working,
+            pending,
+            self_trade_prevention_mode: None,
+            recv_window: None,
+            new_order_resp_type: "RESULT".to_string(),
+        };
+    }
+}
+
+/// A one-triggers-a-one-cancels-the-other order list: a working order that
+/// places a pending OCO pair once it is filled.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtocoOrderCreateData {
+    pub symbol: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub list_client_order_id: Option<String>,
+
+    #[serde(flatten, serialize_with = "serialize_working")]
+    pub working: OrderListLeg,
+
+    pub pending_side: OrderSide,
+    pub pending_quantity: Decimal,
+
+    #[serde(flatten, serialize_with = "serialize_pending_above")]
+    pub pending_above: OcoLeg,
+
+    #[serde(flatten, serialize_with = "serialize_pending_below")]
+    pub pending_below: OcoLeg,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub self_trade_prevention_mode: Option<SelfTradePreventionMode>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recv_window: Option<i64>,
+
+    pub new_order_resp_type: String,
+}
+
+impl OtocoOrderCreateData {
+    #[must_use]
+    pub fn new(
+        symbol: String,
+        working: OrderListLeg,
+        pending_side: OrderSide,
+        pending_quantity: Decimal,
+        pending_above: OcoLeg,
+        pending_below: OcoLeg,
+    ) -> Self {
+        return Self {
+            symbol,
+            list_client_order_id: None,
+            working,
+            pending_side,
+            pending_quantity,
+            pending_above,
+            pending_below,
+            self_trade_prevention_mode: None,
+            recv_window: None,
+            new_order_resp_type: "RESULT".to_string(),
+        };
+    }
+}
+
+/// The working or pending order of an OTO or OTOCO order list. The fields are
+/// sent with a `working` or `pending` prefix, e.g. `workingType`.
+///
+/// The working order must be a `LIMIT` or `LIMIT_MAKER` order; the pending
+/// order of an OTO list can be of any type.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderListLeg {
+    #[serde(rename = "type")]
+    pub type_: OrderType,
+
+    pub side: OrderSide,
+    pub quantity: Decimal,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<Decimal>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_price: Option<Decimal>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trailing_delta: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_in_force: Option<TimeInForce>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iceberg_qty: Option<Decimal>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy_id: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy_type: Option<i32>,
+}
+
+impl OrderListLeg {
+    /// An order of the given type; prices and other parameters are set on the
+    /// returned value.
+    #[must_use]
+    pub const fn new(type_: OrderType, side: OrderSide, quantity: Decimal) -> Self {
+        return Self {
+            type_,
+            side,
+            quantity,
+            client_order_id: None,
+            price: None,
+            stop_price: None,
+            trailing_delta: None,
+            time_in_force: None,
+            iceberg_qty: None,
+            strategy_id: None,
+            strategy_type: None,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContingencyType {
+    Oco,
+    /// OTO and OTOCO lists.
+    Oto,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ListStatusType {
+    Response,
+    ExecStarted,
+    Updated,
+    AllDone,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ListOrderStatus {
+    Executing,
+    AllDone,
+    Reject,
+}
+
+/// An order list. `order_reports` is only returned when the list is created
+/// or canceled.
+///
+/// The working order of OTO and OTOCO lists comes first; the pending orders
+/// have the `PENDING_NEW` status until the working order is filled.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderList {
+    pub order_list_id: i64,
+    pub contingency_type: ContingencyType,
+    pub list_status_type: ListStatusType,
+    pub list_order_status: ListOrderStatus,
+    pub list_client_order_id: String,
+    pub transaction_time: i64,
+    pub symbol: String,
+    pub orders: Vec<OrderListOrder>,
+    #[serde(default)]
+    pub order_reports: Vec<Order>,
+}
+
+/// Identifies one order of an [`OrderList`].
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderListOrder {
+    pub symbol: String,
+    pub order_id: u64,
+    pub client_order_id: String,
+}
@@ -0,0 +1,295 @@
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::sync::mpsc;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use url::Url;
+
+use crate::client::BinanceAccountClient;
+use crate::errors::BinanceError;
+use crate::types::events::UserDataEvent;
+use crate::types::general::BinanceErrorCode;
+
+/// Listen keys expire after 60 minutes; Binance recommends a keepalive every 30.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_mins(30);
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Streams the account's order, balance and order list updates.
+///
+/// The stream creates a listen key, keeps it alive while connected and
+/// creates a new one when the key expires or the connection is lost.
+#[derive(Debug, Clone)]
+pub struct UserDataStream {
+    account: BinanceAccountClient,
+    stream_url: Url,
+    keepalive_interval: Duration,
+    reconnect_delay: Duration,
+}
+
+/// Why a connection of the user data stream ended.
+enum StreamEnd {
+    Disconnected,
+    Expired,
+    ReceiverDropped,
+}
+
+impl UserDataStream {
+    /// Creates a stream that manages listen keys with `account` and connects
+    /// to the streams served under `stream_url` (e.g. `wss://stream.binance.com:9443/`).
+    #[must_use]
+    pub const fn new(account: BinanceAccountClient, stream_url: Url) -> Self {
+        return Self {
+            account,
+            stream_url,
+            keepalive_interval: KEEPALIVE_INTERVAL,
+            reconnect_delay: RECONNECT_DELAY,
+        };
+    }
+
+    /// Sets how often the listen key is kept alive. Defaults to 30 minutes.
+    #[must_use]
+    pub const fn with_keepalive_interval(mut self, interval: Duration) -> Self {
+        self.keepalive_interval = interval;
+        return self;
+    }
+
+    /// Sets how long to wait before reconnecting after a connection failure.
+    /// Defaults to 5 seconds.
+    #[must_use]
+    pub const fn with_reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        return self;
+    }
+
+    /// Streams user data events into `tx`, reconnecting on failure.
+    ///
+    /// [`UserDataEvent::ListenKeyExpired`] is passed on before a new listen key
+    /// is created. Returns once the receiver is dropped, after closing the
+    /// listen key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub async fn run(&self, tx: mpsc::Sender<UserDataEvent>) -> Result<(), BinanceError> {
+        loop {
+            let listen_key = match self.account.create_listen_key().await {
+                Ok(listen_key) => listen_key,
+                Err(e) => {
+                    log::error!("Failed to create listen key: {e}");
+                    if !self.wait_reconnect(&tx).await {
+                        return Ok(());
+                    }
+                    continue;
+                }
+            };
+            let ws_url = self.stream_url.join("ws/")?.join(&listen_key)?;
+
+            match self.stream(&ws_url, &listen_key, &tx).await {
+                StreamEnd::ReceiverDropped => {
+                    log::info!("Receiver dropped. Closing user data stream.");
+                    if let Err(e) = self.account.close_listen_key(&listen_key).await {
+                        log::warn!("Failed to close listen key: {e}");
+                    }
+                    return Ok(());
+                }
+                StreamEnd::Expired => {
+                    log::info!("Listen key expired, creating a new one");
+                }
+                StreamEnd::Disconnected => {
+                    log::info!("Reconnecting in {:?}...", self.reconnect_delay);
+                    if !self.wait_reconnect(&tx).await {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Waits for the reconnect delay. Returns false if the receiver is
+    /// dropped in the meantime.
+    async fn wait_reconnect(&self, tx: &mpsc::Sender<UserDataEvent>) -> bool {
+        tokio::select! {
+            () = tokio::time::sleep(self.reconnect_delay) => return true,
+            () = tx.closed() => {
+                log::info!("Receiver dropped. Stopping user data stream.");
+                return false;
+            }
+        }
+    }
+
+    async fn stream(
+        &self,
+        ws_url: &Url,
+        listen_key: &str,
+        tx: &mpsc::Sender<UserDataEvent>,
+    ) -> StreamEnd {
+        let (mut ws_stream, _) = match connect_async(ws_url.as_str()).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::error!("WebSocket connection failed: {e}");
+                return StreamEnd::Disconnected;
+            }
+        };
+
+        let mut keepalive = tokio::time::interval(self.keepalive_interval);
+        keepalive.tick().await;
+        loop {
+            tokio::select! {
+                () = tx.closed() => return StreamEnd::ReceiverDropped,
+                _ = keepalive.tick() => {
+                    let Err(e) = self.account.keepalive_listen_key(listen_key).await else {
+                        continue;
+                    };
+                    log::warn!("Failed to keep listen key alive: {e}");
+                    if e.error_code() == Some(BinanceErrorCode::InvalidListenKey) {
+                        return StreamEnd::Expired;
+                    }
+                }
+                message = ws_stream.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        let event: UserDataEvent = match serde_json::from_str(&text) {
+                            Ok(event) => event,
+                            Err(e) => {
+                                log::error!("Failed to parse user data event: {e}");
+                                continue;
+                            }
+                        };
+                        let expired = matches!(
+                            event,
+                            UserDataEvent::ListenKeyExpired(_)
+                                | UserDataEvent::EventStreamTerminated(_)
+                        );
+                        if tx.send(event).await.is_err() {
+                            return StreamEnd::ReceiverDropped;
+                        }
+                        if expired {
+                            return StreamEnd::Expired;
+                        }
+                    }
+                    Some(Ok(Message::Ping(ping))) => {
+                        if ws_stream.send(Message::Pong(ping)).await.is_err() {
+                            log::error!("Failed to send pong response");
+                            return StreamEnd::Disconnected;
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => {
+                        log::info!("WebSocket connection closed by server");
+                        return StreamEnd::Disconnected;
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        log::error!("WebSocket message error: {e}");
+                        return StreamEnd::Disconnected;
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Method;
+    use rust_decimal::Decimal;
+    use serde_json::json;
+
+    use crate::mock::{MockResponse, MockServer};
+    use crate::types::events::ExecutionType;
+    use crate::types::trading::{CommonOrderCreateData, OrderCreationData, OrderSide};
+
+    use super::*;
+
+    async fn wait_for_connection(server: &MockServer) {
+        while server.user_stream_connections() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_user_data_stream() {
+        let server = MockServer::start().await;
+        let client = server.client();
+        let stream = client
+            .user_data_stream()
+            .with_keepalive_interval(Duration::from_millis(50));
+        let (tx, mut rx) = mpsc::channel(8);
+        let task = tokio::spawn(async move { stream.run(tx).await });
+        wait_for_connection(&server).await;
+
+        let order_data = OrderCreationData::Market {
+            common: CommonOrderCreateData {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Buy,
+                ..Default::default()
+            },
+            quantity: Some(Decimal::from_str_exact("0.01").unwrap()),
+            quote_order_qty: None,
+        };
+        let order = client.trading.create_order(order_data).await.unwrap();
+        let Some(UserDataEvent::ExecutionReport(report)) = rx.recv().await else {
+            panic!("expected an execution report");
+        };
+        assert_eq!(report.order_id, order.order_id);
+        assert_eq!(report.execution_type, ExecutionType::Trade);
+        assert_eq!(report.cumulative_filled_quantity, order.executed_qty);
+
+        server.push_user_event(&json!({
+            "e": "balanceUpdate", "E": 1, "a": "BTC", "d": "0.5", "T": 1
+        }));
+        let Some(UserDataEvent::BalanceUpdate(update)) = rx.recv().await else {
+            panic!("expected a balance update");
+        };
+        assert_eq!(update.delta, Decimal::from_str_exact("0.5").unwrap());
+
+        // An expired listen key is reported, then replaced by a new one.
+        server.expire_listen_keys();
+        assert!(matches!(
+            rx.recv().await,
+            Some(UserDataEvent::ListenKeyExpired(_))
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        wait_for_connection(&server).await;
+
+        let requests = server.requests();
+        let count = |method: Method| {
+            requests
+                .iter()
+                .filter(|r| r.path == "userDataStream" && r.method == method)
+                .count()
+        };
+        assert_eq!(count(Method::POST), 2);
+        assert!(count(Method::PUT) >= 1);
+
+        // Dropping the receiver closes the listen key.
+        drop(rx);
+        task.await.unwrap().unwrap();
+        let request = server.requests().pop().unwrap();
+        assert_eq!(request.method, Method::DELETE);
+        assert_eq!(request.path, "userDataStream");
+    }
+
+    #[tokio::test]
+    async fn test_user_data_stream_stops_while_retrying() {
+        let server = MockServer::start().await;
+        for _ in 0..100 {
+            let response = MockResponse::error(500, -1001, "Internal error.");
+            server.push_response(Method::POST, "userDataStream", response);
+        }
+        let stream = server
+            .client()
+            .user_data_stream()
+            .with_reconnect_delay(Duration::from_millis(100));
+        let (tx, rx) = mpsc::channel(8);
+        let task = tokio::spawn(async move { stream.run(tx).await });
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        // Dropping the receiver stops the retries.
+        drop(rx);
+        let result = tokio::time::timeout(Duration::from_secs(1), task).await;
+        assert!(result.unwrap().unwrap().is_ok());
+        let requests = server.requests();
+        assert!(requests.len() <= 3);
+        assert!(requests.iter().all(|r| r.method == Method::POST));
+    }
+}
@@ -0,0 +1,655 @@
+use rust_decimal::Decimal;
+
+use crate::types::general::{LotSizeFilter, PriceFilter, SymbolFilter, SymbolInfo, SymbolStatus};
+use crate::types::trading::{OrderCreationData, OrderSide, OrderType};
+
+/// Why an order would be rejected by the trading rules of its symbol, mostly
+/// with a -1013 filter failure.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FilterViolation {
+    /// The symbol is not listed, which Binance rejects with -1121.
+    #[error("unknown symbol {0}")]
+    UnknownSymbol(String),
+
+    #[error("{symbol} is not trading ({status:?})")]
+    SymbolNotTrading {
+        symbol: String,
+        status: SymbolStatus,
+    },
+
+    #[error("{0:?} orders are not allowed")]
+    OrderTypeNotAllowed(OrderType),
+
+    #[error("iceberg orders are not allowed")]
+    IcebergNotAllowed,
+
+    #[error("trailing stops are not allowed")]
+    TrailingStopNotAllowed,
+
+    #[error("price {price} is outside [{min}, {max}]")]
+    PriceOutOfRange {
+        price: Decimal,
+        min: Decimal,
+        max: Decimal,
+    },
+
+    #[error("price {price} is not a multiple of the tick size {tick_size}")]
+    PriceNotOnTick { price: Decimal, tick_size: Decimal },
+
+    #[error("quantity {quantity} is outside [{min}, {max}]")]
+    QuantityOutOfRange {
+        quantity: Decimal,
+        min: Decimal,
+        max: Decimal,
+    },
+
+    #[error("quantity {quantity} is not a multiple of the step size {step_size}")]
+    QuantityNotOnStep {
+        quantity: Decimal,
+        step_size: Decimal,
+    },
+
+    #[error("notional {notional} is below the minimum {min}")]
+    NotionalTooLow { notional: Decimal, min: Decimal },
+
+    #[error("notional {notional} is above the maximum {max}")]
+    NotionalTooHigh { notional: Decimal, max: Decimal },
+
+    /// The price is too far from the average price.
+    #[error("price {price} is outside the band [{min}, {max}] around the average price")]
+    PriceOutsideBand {
+        price: Decimal,
+        min: Decimal,
+        max: Decimal,
+    },
+
+    #[error("the order would be split into {parts} iceberg parts, more than {limit}")]
+    TooManyIcebergParts { parts: Decimal, limit: u32 },
+
+    #[error("trailing delta {delta} is outside [{min}, {max}]")]
+    TrailingDeltaOutOfRange { delta: i64, min: u32, max: u32 },
+}
+
+/// The parameters of an order that its symbol's filters apply to.
+struct OrderParams {
+    order_type: OrderType,
+    side: OrderSide,
+    price: Option<Decimal>,
+    stop_price: Option<Decimal>,
+    quantity: Option<Decimal>,
+    quote_order_qty: Option<Decimal>,
+    iceberg_qty: Option<Decimal>,
+    trailing_delta: Option<i64>,
+}
+
+impl OrderParams {
+    const fn new(order: &OrderCreationData) -> Self {
+        let mut params = Self {
+            order_type: OrderType::Limit,
+            side: order.common().side,
+            price: None,
+            stop_price: None,
+            quantity: None,
+            quote_order_qty: None,
+            iceberg_qty: None,
+            trailing_delta: None,
+        };
+        match *order {
+            OrderCreationData::Limit {
+                quantity,
+                price,
+                iceberg_qty,
+                ..
+            } => {
+                params.quantity = Some(quantity);
+                params.price = Some(price);
+                params.iceberg_qty = iceberg_qty;
+            }
+            OrderCreationData::Market {
+                quantity,
+                quote_order_qty,
+                ..
+            } => {
+                params.order_type = OrderType::Market;
+                params.quantity = quantity;
+                params.quote_order_qty = quote_order_qty;
+            }
+            OrderCreationData::StopLoss {
+                quantity,
+                stop_price,
+                trailing_delta,
+                ..
+            }
+            | OrderCreationData::TakeProfit {
+                quantity,
+                stop_price,
+                trailing_delta,
+                ..
+            } => {
+                params.order_type = if matches!(order, OrderCreationData::StopLoss { .. }) {
+                    OrderType::StopLoss
+                } else {
+                    OrderType::TakeProfit
+                };
+                params.quantity = Some(quantity);
+                params.stop_price = stop_price;
+                params.trailing_delta = trailing_delta;
+            }
+            OrderCreationData::StopLossLimit {
+                quantity,
+                price,
+                stop_price,
+                trailing_delta,
+                iceberg_qty,
+                ..
+            }
+            | OrderCreationData::TakeProfitLimit {
+                quantity,
+                price,
+                stop_price,
+                trailing_delta,
+                iceberg_qty,
+                ..
+            } => {
+                params.order_type = if matches!(order, OrderCreationData::StopLossLimit { .. }) {
+                    OrderType::StopLossLimit
+                } else {
+                    OrderType::TakeProfitLimit
+                };
+                params.quantity = Some(quantity);
+                params.price = Some(price);
+                params.stop_price = stop_price;
+                params.trailing_delta = trailing_delta;
+                params.iceberg_qty = iceberg_qty;
+            }
+            OrderCreationData::LimitMaker {
+                quantity, price, ..
+            } => {
+                params.order_type = OrderType::LimitMaker;
+                params.quantity = Some(quantity);
+                params.price = Some(price);
+            }
+        }
+        return params;
+    }
+
+    const fn is_market(&self) -> bool {
+        return matches!(
+            self.order_type,
+            OrderType::Market | OrderType::StopLoss | OrderType::TakeProfit
+        );
+    }
+
+    /// The price used for notional and percent price checks: the limit
+    /// price, or the average price for market orders.
+    const fn reference_price(&self, avg_price: Option<Decimal>) -> Option<Decimal> {
+        return if self.is_market() {
+            avg_price
+        } else {
+            self.price
+        };
+    }
+
+    fn notional(&self, avg_price: Option<Decimal>) -> Option<Decimal> {
+        if let Some(quote_order_qty) = self.quote_order_qty {
+            return Some(quote_order_qty);
+        }
+        return Some(self.quantity? * self.reference_price(avg_price)?);
+    }
+
+    /// Whether a trailing stop triggers above the market price rather than
+    /// below it.
+    const fn trails_above(&self) -> bool {
+        return matches!(
+            (self.order_type, &self.side),
+            (
+                OrderType::StopLoss | OrderType::StopLossLimit,
+                OrderSide::Buy
+            ) | (
+                OrderType::TakeProfit | OrderType::TakeProfitLimit,
+                OrderSide::Sell
+            )
+        );
+    }
+}
+
+/// Checks orders against the filters of their symbol before they are sent.
+///
+/// Filters relative to the average price (`PERCENT_PRICE`,
+/// `PERCENT_PRICE_BY_SIDE` and the notional of market orders) are only
+/// checked when the average price is given, e.g. from
+/// [`get_average_price`](crate::client::BinanceMarketClient::get_average_price).
+/// Filters depending on the open orders of the account (`MAX_NUM_*`,
+/// `MAX_POSITION`) are not checked.
+#[derive(Debug, Clone, Copy)]
+pub struct OrderValidator<'a> {
+    symbol: &'a SymbolInfo,
+    avg_price: Option<Decimal>,
+}
+
+impl<'a> OrderValidator<'a> {
+    #[must_use]
+    pub const fn new(symbol: &'a SymbolInfo) -> Self {
+        return Self {
+            symbol,
+            avg_price: None,
+        };
+    }
+
+    /// Also checks the filters relative to the average price of the symbol.
+    #[must_use]
+    pub const fn with_avg_price(mut self, avg_price: Decimal) -> Self {
+        self.avg_price = Some(avg_price);
+        return self;
+    }
+
+    /// Rounds `price` down to the tick size of the symbol.
+    #[must_use]
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        return self
+            .symbol
+            .price_filter()
+            .map_or(price, |filter| filter.round_price(price));
+    }
+
+    /// Rounds `quantity` down to the step size of the symbol, or to the step
+    /// size of market orders with `market`.
+    #[must_use]
+    pub fn round_quantity(&self, quantity: Decimal, market: bool) -> Decimal {
+        let lot_size = if market {
+            self.symbol
+                .market_lot_size()
+                .or_else(|| self.symbol.lot_size())
+        } else {
+            self.symbol.lot_size()
+        };
+        return lot_size.map_or(quantity, |filter| filter.round_quantity(quantity));
+    }
+
+    /// Checks `order` against the status, allowed order types and filters of
+    /// the symbol.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first rule the order breaks.
+    pub fn validate(&self, order: &OrderCreationData) -> Result<(), FilterViolation> {
+        let symbol = self.symbol;
+        if symbol.status != SymbolStatus::Trading {
+            return Err(FilterViolation::SymbolNotTrading {
+                symbol: symbol.symbol.clone(),
+                status: symbol.status,
+            });
+        }
+        let params = OrderParams::new(order);
+        if !symbol.order_types.contains(&params.order_type) {
+            return Err(FilterViolation::OrderTypeNotAllowed(params.order_type));
+        }
+        if params.iceberg_qty.is_some() && !symbol.iceberg_allowed {
+            return Err(FilterViolation::IcebergNotAllowed);
+        }
+        if params.trailing_delta.is_some() && !symbol.allow_trailing_stop {
+            return Err(FilterViolation::TrailingStopNotAllowed);
+        }
+
+        for filter in &symbol.filters {
+            self.check(filter, &params)?;
+        }
+        return Ok(());
+    }
+
+    fn check(&self, filter: &SymbolFilter, params: &OrderParams) -> Result<(), FilterViolation> {
+        match filter {
+            SymbolFilter::PriceFilter(filter) => {
+                for price in [params.price, params.stop_price].into_iter().flatten() {
+                    filter.check(price)?;
+                }
+            }
+            SymbolFilter::LotSize(filter) => {
+                for quantity in [params.quantity, params.iceberg_qty].into_iter().flatten() {
+                    filter.check(quantity)?;
+                }
+            }
+            SymbolFilter::MarketLotSize(filter) if params.is_market() => {
+                if let Some(quantity) = params.quantity {
+                    filter.check(quantity)?;
+                }
+            }
+            SymbolFilter::MinNotional(filter) if !params.is_market() || filter.apply_to_market => {
+                if let Some(notional) = params.notional(self.avg_price)
+                    && notional < filter.min_notional
+                {
+                    return Err(FilterViolation::NotionalTooLow {
+                        notional,
+                        min: filter.min_notional,
+                    });
+                }
+            }
+            SymbolFilter::Notional(filter) => {
+                let Some(notional) = params.notional(self.avg_price) else {
+                    return Ok(());
+                };
+                let market = params.is_market();
+                if (!market || filter.apply_min_to_market) && notional < filter.min_notional {
+                    return Err(FilterViolation::NotionalTooLow {
+                        notional,
+                        min: filter.min_notional,
+                    });
+                }
+                if (!market || filter.apply_max_to_market) && notional > filter.max_notional {
+                    return Err(FilterViolation::NotionalTooHigh {
+                        notional,
+                        max: filter.max_notional,
+                    });
+                }
+            }
+            SymbolFilter::PercentPrice(filter) => {
+                check_band(
+                    params.reference_price(self.avg_price),
+                    self.avg_price,
+                    filter.multiplier_down,
+                    filter.multiplier_up,
+                )?;
+            }
+            SymbolFilter::PercentPriceBySide(filter) => {
+                let (down, up) = match params.side {
+                    OrderSide::Buy => (filter.bid_multiplier_down, filter.bid_multiplier_up),
+                    OrderSide::Sell => (filter.ask_multiplier_down, filter.ask_multiplier_up),
+                };
+                check_band(
+                    params.reference_price(self.avg_price),
+                    self.avg_price,
+                    down,
+                    up,
+                )?;
+            }
+            SymbolFilter::IcebergParts(filter) => {
+                if let (Some(quantity), Some(iceberg_qty)) = (params.quantity, params.iceberg_qty)
+                    && !iceberg_qty.is_zero()
+                {
+                    let parts = (quantity / iceberg_qty).ceil();
+                    if parts > Decimal::from(filter.limit) {
+                        return Err(FilterViolation::TooManyIcebergParts {
+                            parts,
+                            limit: filter.limit,
+                        });
+                    }
+                }
+            }
+            SymbolFilter::TrailingDelta(filter) => {
+                if let Some(delta) = params.trailing_delta {
+                    let (min, max) = if params.trails_above() {
+                        (
+                            filter.min_trailing_above_delta,
+                            filter.max_trailing_above_delta,
+                        )
+                    } else {
+                        (
+                            filter.min_trailing_below_delta,
+                            filter.max_trailing_below_delta,
+                        )
+                    };
+                    if delta < i64::from(min) || delta > i64::from(max) {
+                        return Err(FilterViolation::TrailingDeltaOutOfRange { delta, min, max });
+                    }
+                }
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+}
+
+fn check_band(
+    price: Option<Decimal>,
+    avg_price: Option<Decimal>,
+    multiplier_down: Decimal,
+    multiplier_up: Decimal,
+) -> Result<(), FilterViolation> {
+    let (Some(price), Some(avg_price)) = (price, avg_price) else {
+        return Ok(());
+    };
+    let (min, max) = (avg_price * multiplier_down, avg_price * multiplier_up);
+    if price < min || price > max {
+        return Err(FilterViolation::PriceOutsideBand { price, min, max });
+    }
+    return Ok(());
+}
+
+/// Rounds `value` down to `origin` plus a multiple of `step`.
+fn round_down(value: Decimal, origin: Decimal, step: Decimal) -> Decimal {
+    if step.is_zero() {
+        return value;
+    }
+    let rounded = origin + ((value - origin) / step).floor() * step;
+    let scale = step.normalize().scale().max(origin.normalize().scale());
+    return rounded.round_dp(scale);
+}
+
+impl PriceFilter {
+    /// Rounds `price` down to a multiple of the tick size. Unlike quantities,
+    /// prices are not stepped from the minimum.
+    #[must_use]
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        return round_down(price, Decimal::ZERO, self.tick_size);
+    }
+
+    /// Checks `price` against the bounds and tick size. Zero bounds and tick
+    /// sizes are not enforced.
+    ///
+    /// # Errors
+    ///
+    /// Returns the rule the price breaks.
+    pub fn check(&self, price: Decimal) -> Result<(), FilterViolation> {
+        let below = !self.min_price.is_zero() && price < self.min_price;
+        let above = !self.max_price.is_zero() && price > self.max_price;
+        if below || above {
+            return Err(FilterViolation::PriceOutOfRange {
+                price,
+                min: self.min_price,
+                max: self.max_price,
+            });
+        }
+        if self.round_price(price) != price {
+            return Err(FilterViolation::PriceNotOnTick {
+                price,
+                tick_size: self.tick_size,
+            });
+        }
+        return Ok(());
+    }
+}
+
+impl LotSizeFilter {
+    /// Rounds `quantity` down to the minimum plus a multiple of the step size.
+    #[must_use]
+    pub fn round_quantity(&self, quantity: Decimal) -> Decimal {
+        return round_down(quantity, self.min_qty, self.step_size);
+    }
+
+    /// Checks `quantity` against the bounds and step size. A zero step size
+    /// is not enforced.
+    ///
+    /// # Errors
+    ///
+    /// Returns the rule the quantity breaks.
+    pub fn check(&self, quantity: Decimal) -> Result<(), FilterViolation> {
+        let above = !self.max_qty.is_zero() && quantity > self.max_qty;
+        if quantity < self.min_qty || above {
+            return Err(FilterViolation::QuantityOutOfRange {
+                quantity,
+                min: self.min_qty,
+                max: self.max_qty,
+            });
+        }
+        if self.round_quantity(quantity) != quantity {
+            return Err(FilterViolation::QuantityNotOnStep {
+                quantity,
+                step_size: self.step_size,
+            });
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockServer;
+    use crate::types::trading::{CommonOrderCreateData, TimeInForce};
+
+    async fn btcusdt() -> SymbolInfo {
+        let server = MockServer::start().await;
+        let client = server.client();
+        let info = client.general.get_exchange_info(&["BTCUSDT"]).await;
+        return info.unwrap().symbols.remove(0);
+    }
+
+    fn common(side: OrderSide) -> CommonOrderCreateData {
+        return CommonOrderCreateData {
+            symbol: "BTCUSDT".to_string(),
+            side,
+            ..Default::default()
+        };
+    }
+
+    fn limit(quantity: &str, price: &str, iceberg_qty: Option<&str>) -> OrderCreationData {
+        return OrderCreationData::Limit {
+            common: common(OrderSide::Buy),
+            time_in_force: TimeInForce::Gtc,
+            quantity: Decimal::from_str_exact(quantity).unwrap(),
+            price: Decimal::from_str_exact(price).unwrap(),
+            iceberg_qty: iceberg_qty.map(|qty| Decimal::from_str_exact(qty).unwrap()),
+        };
+    }
+
+    #[tokio::test]
+    async fn test_validate_order() {
+        let mut symbol = btcusdt().await;
+        let validator = OrderValidator::new(&symbol);
+
+        assert_eq!(validator.validate(&limit("0.001", "80002", None)), Ok(()));
+        assert!(matches!(
+            validator.validate(&limit("0.001", "80000", None)),
+            Err(FilterViolation::PriceNotOnTick { .. })
+        ));
+        assert!(matches!(
+            validator.validate(&limit("0.001", "4", None)),
+            Err(FilterViolation::PriceOutOfRange { .. })
+        ));
+        assert!(matches!(
+            validator.validate(&limit("0.000015", "80002", None)),
+            Err(FilterViolation::QuantityNotOnStep { .. })
+        ));
+        assert!(matches!(
+            validator.validate(&limit("10000", "80002", None)),
+            Err(FilterViolation::QuantityOutOfRange { .. })
+        ));
+        assert!(matches!(
+            validator.validate(&limit("0.00005", "80002", None)),
+            Err(FilterViolation::NotionalTooLow { .. })
+        ));
+        assert!(matches!(
+            validator.validate(&limit("1", "80002", Some("0.05"))),
+            Err(FilterViolation::TooManyIcebergParts { limit: 10, .. })
+        ));
+        assert_eq!(
+            validator.validate(&limit("1", "80002", Some("0.1"))),
+            Ok(())
+        );
+
+        // Market orders are checked against the average price when given.
+        let market = OrderCreationData::Market {
+            common: common(OrderSide::Sell),
+            quantity: None,
+            quote_order_qty: Some(Decimal::ONE),
+        };
+        assert!(matches!(
+            validator.validate(&market),
+            Err(FilterViolation::NotionalTooLow { .. })
+        ));
+        let far = limit("0.001", "501500", None);
+        assert_eq!(validator.validate(&far), Ok(()));
+        assert!(matches!(
+            validator
+                .with_avg_price(Decimal::from(85_000))
+                .validate(&far),
+            Err(FilterViolation::PriceOutsideBand { .. })
+        ));
+
+        let trailing = OrderCreationData::StopLoss {
+            common: common(OrderSide::Sell),
+            quantity: Decimal::ONE,
+            stop_price: None,
+            trailing_delta: Some(5),
+        };
+        assert_eq!(
+            validator.validate(&trailing),
+            Err(FilterViolation::TrailingDeltaOutOfRange {
+                delta: 5,
+                min: 10,
+                max: 2000
+            })
+        );
+
+        symbol.status = SymbolStatus::Halt;
+        assert!(matches!(
+            OrderValidator::new(&symbol).validate(&limit("0.001", "80002", None)),
+            Err(FilterViolation::SymbolNotTrading { .. })
+        ));
+    }
+
+    #[test]
+    fn test_price_grid() {
+        // The price grid starts at zero, not at the minimum price.
+        let filter = PriceFilter {
+            min_price: Decimal::new(15, 3),
+            max_price: Decimal::ZERO,
+            tick_size: Decimal::new(1, 2),
+        };
+        assert_eq!(filter.check(Decimal::new(2, 2)), Ok(()));
+        assert!(matches!(
+            filter.check(Decimal::new(25, 3)),
+            Err(FilterViolation::PriceNotOnTick { .. })
+        ));
+        assert!(matches!(
+            filter.check(Decimal::new(1, 2)),
+            Err(FilterViolation::PriceOutOfRange { .. })
+        ));
+        assert_eq!(filter.round_price(Decimal::new(29, 3)), Decimal::new(2, 2));
+
+        // Quantities are stepped from the minimum quantity.
+        let filter = LotSizeFilter {
+            min_qty: Decimal::new(15, 3),
+            max_qty: Decimal::ZERO,
+            step_size: Decimal::new(1, 2),
+        };
+        assert_eq!(filter.check(Decimal::new(25, 3)), Ok(()));
+        assert_eq!(
+            filter.round_quantity(Decimal::new(3, 2)),
+            Decimal::new(25, 3)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_round() {
+        let symbol = btcusdt().await;
+        let validator = OrderValidator::new(&symbol);
+        assert_eq!(
+            validator.round_price(Decimal::from(80_000)),
+            Decimal::new(799_935, 1)
+        );
+        assert_eq!(
+            validator.round_price(Decimal::from(80_002)),
+            Decimal::from(80_002)
+        );
+        assert_eq!(
+            validator.round_quantity(Decimal::from_str_exact("0.123456789").unwrap(), false),
+            Decimal::from_str_exact("0.12345").unwrap()
+        );
+        // The market lot size of the symbol has no step size.
+        assert_eq!(
+            validator.round_quantity(Decimal::from_str_exact("0.123456789").unwrap(), true),
+            Decimal::from_str_exact("0.12345").unwrap()
+        );
+    }
+}
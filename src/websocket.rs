@@ -1,313 +1,656 @@
-use futures_util::{SinkExt, StreamExt};
-use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_util::{SinkExt, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message};
 use url::Url;
 
 use crate::{
+    client::{MAINNET_STREAM_URL, TESTNET_STREAM_URL},
     errors::BinanceError,
+    stream_manager::StreamManager,
     types::{
-        events::{AggTradeEvent, KlineEvent, MiniTickerEvent, TickerEvent, TradeEvent},
-        market::KlineInterval,
+        events::{
+            AggTradeEvent, AvgPriceEvent, BookTickerEvent, DepthLevels, DepthUpdateEvent,
+            KlineEvent, MiniTickerEvent, PartialDepthEvent, TickerEvent, TradeEvent, UpdateSpeed,
+            WindowTickerEvent,
+        },
+        market::{Kline, KlineInterval, WindowSize},
     },
 };
 
+/// How long to wait between reconnection attempts of a stream.
+///
+/// The delay starts at `initial` and is multiplied by `multiplier` after
+/// every failed attempt, up to `max`. A random part of up to `jitter` times
+/// the delay is subtracted from it, so that clients disconnected together do
+/// not reconnect together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub multiplier: f64,
+    /// The fraction of the delay that is randomized, between 0 and 1.
+    pub jitter: f64,
+    /// Gives up after this many consecutive failed attempts. Unlimited by default.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        return Self {
+            initial: Duration::from_secs(1),
+            max: Duration::from_mins(1),
+            multiplier: 2.0,
+            jitter: 0.25,
+            max_attempts: None,
+        };
+    }
+}
+
+impl Backoff {
+    /// Waits `delay` between every attempt, without jitter.
+    #[must_use]
+    pub const fn fixed(delay: Duration) -> Self {
+        return Self {
+            initial: delay,
+            max: delay,
+            multiplier: 1.0,
+            jitter: 0.0,
+            max_attempts: None,
+        };
+    }
+
+    /// The delay before the reconnection attempt following `failures`
+    /// consecutive failed ones.
+    #[must_use]
+    pub fn delay(&self, failures: u32) -> Duration {
+        let exponent = i32::try_from(failures).unwrap_or(i32::MAX);
+        let delay = self
+            .initial
+            .mul_f64(self.multiplier.max(1.0).powi(exponent).min(1e9))
+            .min(self.max);
+        let random = RandomState::new().build_hasher().finish();
+        #[allow(clippy::cast_precision_loss)]
+        let fraction = random as f64 / u64::MAX as f64;
+        return delay.mul_f64(self.jitter.clamp(0.0, 1.0).mul_add(-fraction, 1.0));
+    }
+}
+
+/// The state of the connection behind an [`EventStream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    /// The connection was lost or could not be opened, and will be retried
+    /// after `delay`.
+    Reconnecting {
+        attempt: u32,
+        delay: Duration,
+        reason: String,
+    },
+    /// The stream gave up reconnecting after the maximum number of attempts.
+    Disconnected {
+        reason: String,
+    },
+}
+
+/// The events of a market data stream, as a [`Stream`].
+///
+/// Events that cannot be decoded are yielded as
+/// [`BinanceError::InvalidEvent`] instead of being dropped. The connection is
+/// reopened when lost, following the [`Backoff`] of the client; the stream
+/// only ends if the backoff gives up. Dropping the stream closes the
+/// connection.
+#[derive(Debug)]
+pub struct EventStream<T> {
+    name: String,
+    events: mpsc::Receiver<Result<T, BinanceError>>,
+    state: watch::Receiver<ConnectionState>,
+    task: JoinHandle<()>,
+}
+
+impl<T> EventStream<T> {
+    /// The name of the stream, e.g. `btcusdt@aggTrade`.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    /// Returns a receiver of the connection state changes.
+    #[must_use]
+    pub fn state(&self) -> watch::Receiver<ConnectionState> {
+        return self.state.clone();
+    }
+}
+
+impl<T> Stream for EventStream<T> {
+    type Item = Result<T, BinanceError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        return self.events.poll_recv(cx);
+    }
+}
+
+impl<T> Drop for EventStream<T> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Why a stream connection ended.
+enum StreamEnd {
+    Lost(String),
+    ReceiverDropped,
+}
+
+fn kline_stream(symbol: &str, interval: &KlineInterval, timezone: Option<&str>) -> String {
+    let timezone = timezone
+        .map(|timezone| format!("@{timezone}"))
+        .unwrap_or_default();
+    return format!("{}@kline_{interval}{timezone}", symbol.to_lowercase());
+}
+
+/// Rolling window ticker streams only exist for 1h, 4h and 1d windows.
+fn stream_window(window: &WindowSize) -> Result<String, BinanceError> {
+    return match window {
+        WindowSize::Hours(1 | 4) | WindowSize::Days(1) => Ok(window.to_string()),
+        _ => Err(BinanceError::NotAllowed(format!(
+            "ticker streams only support 1h, 4h and 1d windows, not {window}"
+        ))),
+    };
+}
+
+/// Connects to `url` and forwards its events until the receiver is dropped,
+/// reconnecting after the delays of `backoff`.
+///
+/// A connection only counts as successful once it delivers an event, so that
+/// a server accepting connections and closing them right away, e.g. for an
+/// unknown stream, is retried with growing delays.
+async fn run_stream<T: DeserializeOwned, U>(
+    url: Url,
+    backoff: Backoff,
+    filter: fn(T) -> Option<U>,
+    events: mpsc::Sender<Result<U, BinanceError>>,
+    state: watch::Sender<ConnectionState>,
+) {
+    let mut failures = 0;
+    loop {
+        let reason = match connect_async(url.as_str()).await {
+            Ok((ws_stream, _)) => {
+                state.send_replace(ConnectionState::Connected);
+                match forward_events(ws_stream, filter, &events, &mut failures).await {
+                    StreamEnd::Lost(reason) => reason,
+                    StreamEnd::ReceiverDropped => {
+                        log::info!("Receiver dropped. Stopping processing.");
+                        return;
+                    }
+                }
+            }
+            Err(e) => format!("connection failed: {e}"),
+        };
+
+        if backoff.max_attempts.is_some_and(|max| failures >= max) {
+            log::error!("Giving up on {url}: {reason}");
+            state.send_replace(ConnectionState::Disconnected { reason });
+            return;
+        }
+        let delay = backoff.delay(failures);
+        failures += 1;
+        log::info!("Reconnecting to {url} in {delay:?} ({reason})");
+        state.send_replace(ConnectionState::Reconnecting {
+            attempt: failures,
+            delay,
+            reason,
+        });
+        tokio::select! {
+            () = tokio::time::sleep(delay) => {}
+            () = events.closed() => return,
+        }
+        state.send_replace(ConnectionState::Connecting);
+    }
+}
+
+/// Forwards the events of `ws_stream`, resetting `failures` once one is
+/// delivered.
+async fn forward_events<T: DeserializeOwned, U>(
+    mut ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    filter: fn(T) -> Option<U>,
+    events: &mpsc::Sender<Result<U, BinanceError>>,
+    failures: &mut u32,
+) -> StreamEnd {
+    loop {
+        let message = tokio::select! {
+            message = ws_stream.next() => message,
+            () = events.closed() => return StreamEnd::ReceiverDropped,
+        };
+        match message {
+            Some(Ok(Message::Text(text))) => {
+                let event = match serde_json::from_str(&text) {
+                    Ok(event) => match filter(event) {
+                        Some(event) => Ok(event),
+                        None => continue,
+                    },
+                    Err(e) => Err(BinanceError::InvalidEvent(e, text.to_string())),
+                };
+                if events.send(event).await.is_err() {
+                    return StreamEnd::ReceiverDropped;
+                }
+                *failures = 0;
+            }
+            Some(Ok(Message::Ping(ping))) => {
+                if ws_stream.send(Message::Pong(ping)).await.is_err() {
+                    return StreamEnd::Lost("failed to send pong response".to_string());
+                }
+            }
+            Some(Ok(Message::Close(frame))) => {
+                let reason = frame.map_or_else(String::new, |frame| frame.reason.to_string());
+                return StreamEnd::Lost(format!("closed by server {reason}").trim().to_string());
+            }
+            Some(Ok(_)) => {}
+            Some(Err(e)) => return StreamEnd::Lost(e.to_string()),
+            None => return StreamEnd::Lost("connection closed".to_string()),
+        }
+    }
+}
+
+/// Sends the events of `stream` into `tx` until the receiver is dropped,
+/// logging the events that cannot be decoded.
+async fn forward<T>(mut stream: EventStream<T>, tx: mpsc::Sender<T>) {
+    loop {
+        let event = tokio::select! {
+            event = stream.next() => event,
+            () = tx.closed() => break,
+        };
+        match event {
+            Some(Ok(event)) => {
+                if tx.send(event).await.is_err() {
+                    break;
+                }
+            }
+            Some(Err(e)) => log::error!("Failed to parse {} event: {e}", stream.name()),
+            None => return,
+        }
+    }
+    log::info!("Receiver dropped. Stopping processing.");
+}
+
 #[derive(Debug, Clone)]
 pub struct BinanceWebSocket {
     base_url: Url,
+    backoff: Backoff,
 }
 
 impl BinanceWebSocket {
+    /// Creates a client for the Binance mainnet or testnet market data streams.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the built-in stream URL cannot be parsed.
     #[must_use]
     pub fn new(testnet: bool) -> Self {
-        Self {
-            base_url: Url::parse(if testnet {
-                "wss://stream.testnet.binance.vision/ws/"
-            } else {
-                "wss://stream.binance.com/ws/"
-            })
-            .unwrap(),
-        }
+        let base_url = if testnet {
+            TESTNET_STREAM_URL
+        } else {
+            MAINNET_STREAM_URL
+        };
+        return Self::with_base_url(Url::parse(base_url).unwrap());
+    }
+
+    /// Creates a client for the streams served under `base_url`
+    /// (e.g. `wss://stream.binance.com:9443/`).
+    #[must_use]
+    pub fn with_base_url(base_url: Url) -> Self {
+        return Self {
+            base_url,
+            backoff: Backoff::default(),
+        };
+    }
+
+    /// Sets how streams wait between reconnection attempts.
+    #[must_use]
+    pub const fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        return self;
+    }
+
+    #[must_use]
+    pub const fn base_url(&self) -> &Url {
+        return &self.base_url;
+    }
+
+    /// Returns a manager multiplexing streams over combined stream connections
+    /// to the same host.
+    #[must_use]
+    pub fn stream_manager(&self) -> StreamManager {
+        return StreamManager::new(self.base_url.clone());
+    }
+
+    /// Returns the URL of the raw stream named `stream` (`<base_url>/ws/<stream>`).
+    fn stream_url(&self, stream: &str) -> Result<Url, BinanceError> {
+        return Ok(self.base_url.join("ws/")?.join(stream)?);
+    }
+
+    /// Opens the raw stream `name` (e.g. `btcusdt@depth`), decoding its
+    /// events as `T`.
+    ///
+    /// The connection is opened in the background; its progress can be
+    /// followed with [`EventStream::state`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn stream<T: DeserializeOwned + Send + 'static>(
+        &self,
+        name: &str,
+    ) -> Result<EventStream<T>, BinanceError> {
+        return self.stream_filtered(name, Some);
+    }
+
+    /// Opens the raw stream `name`, decoding its events as `T` and passing
+    /// on the ones `filter` converts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn stream_filtered<T, U>(
+        &self,
+        name: &str,
+        filter: fn(T) -> Option<U>,
+    ) -> Result<EventStream<U>, BinanceError>
+    where
+        T: DeserializeOwned + Send + 'static,
+        U: Send + 'static,
+    {
+        let url = self.stream_url(name)?;
+        let (events_tx, events) = mpsc::channel(64);
+        let (state_tx, state) = watch::channel(ConnectionState::Connecting);
+        let backoff = self.backoff.clone();
+        let task = tokio::spawn(run_stream(url, backoff, filter, events_tx, state_tx));
+        return Ok(EventStream {
+            name: name.to_string(),
+            events,
+            state,
+            task,
+        });
+    }
+
+    /// Opens the aggregate trade stream of `symbol`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn agg_trades(&self, symbol: &str) -> Result<EventStream<AggTradeEvent>, BinanceError> {
+        return self.stream(&format!("{}@aggTrade", symbol.to_lowercase()));
+    }
+
+    /// Opens the raw trade stream of `symbol`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn trades(&self, symbol: &str) -> Result<EventStream<TradeEvent>, BinanceError> {
+        return self.stream(&format!("{}@trade", symbol.to_lowercase()));
+    }
+
+    /// Opens the kline stream of `symbol` and `interval`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn klines(
+        &self,
+        symbol: &str,
+        interval: &KlineInterval,
+    ) -> Result<EventStream<KlineEvent>, BinanceError> {
+        return self.stream(&kline_stream(symbol, interval, None));
+    }
+
+    /// Opens the kline stream of `symbol` and `interval`, with candles opening
+    /// in `timezone` (e.g. `+08:00`) instead of UTC.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn klines_with_timezone(
+        &self,
+        symbol: &str,
+        interval: &KlineInterval,
+        timezone: &str,
+    ) -> Result<EventStream<KlineEvent>, BinanceError> {
+        return self.stream(&kline_stream(symbol, interval, Some(timezone)));
     }
 
+    /// Opens the kline stream of `symbol` and `interval`, passing on only the
+    /// closed candles as [`Kline`]s, like the ones of
+    /// [`get_klines`](crate::client::BinanceMarketClient::get_klines).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn closed_klines(
+        &self,
+        symbol: &str,
+        interval: &KlineInterval,
+        timezone: Option<&str>,
+    ) -> Result<EventStream<Kline>, BinanceError> {
+        return self.stream_filtered(
+            &kline_stream(symbol, interval, timezone),
+            |event: KlineEvent| event.kline.is_closed.then(|| Kline::from(event.kline)),
+        );
+    }
+
+    /// Opens the 24hr mini ticker stream of `symbol`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn mini_tickers(&self, symbol: &str) -> Result<EventStream<MiniTickerEvent>, BinanceError> {
+        return self.stream(&format!("{}@miniTicker", symbol.to_lowercase()));
+    }
+
+    /// Opens the 24hr ticker stream of `symbol`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn tickers(&self, symbol: &str) -> Result<EventStream<TickerEvent>, BinanceError> {
+        return self.stream(&format!("{}@ticker", symbol.to_lowercase()));
+    }
+
+    /// Opens the 24hr mini ticker stream of all symbols, pushing the tickers
+    /// that changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn all_mini_tickers(&self) -> Result<EventStream<Vec<MiniTickerEvent>>, BinanceError> {
+        return self.stream("!miniTicker@arr");
+    }
+
+    /// Opens the 24hr ticker stream of all symbols, pushing the tickers that
+    /// changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn all_tickers(&self) -> Result<EventStream<Vec<TickerEvent>>, BinanceError> {
+        return self.stream("!ticker@arr");
+    }
+
+    /// Opens the rolling window ticker stream of `symbol`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `window` is not 1h, 4h or 1d, or if the stream URL
+    /// cannot be built.
+    pub fn window_tickers(
+        &self,
+        symbol: &str,
+        window: &WindowSize,
+    ) -> Result<EventStream<WindowTickerEvent>, BinanceError> {
+        let window = stream_window(window)?;
+        return self.stream(&format!("{}@ticker_{window}", symbol.to_lowercase()));
+    }
+
+    /// Opens the rolling window ticker stream of all symbols, pushing the
+    /// tickers that changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `window` is not 1h, 4h or 1d, or if the stream URL
+    /// cannot be built.
+    pub fn all_window_tickers(
+        &self,
+        window: &WindowSize,
+    ) -> Result<EventStream<Vec<WindowTickerEvent>>, BinanceError> {
+        let window = stream_window(window)?;
+        return self.stream(&format!("!ticker_{window}@arr"));
+    }
+
+    /// Opens the average price stream of `symbol`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn avg_prices(&self, symbol: &str) -> Result<EventStream<AvgPriceEvent>, BinanceError> {
+        return self.stream(&format!("{}@avgPrice", symbol.to_lowercase()));
+    }
+
+    /// Opens the diff depth stream of `symbol`, pushing the changes of its
+    /// order book at `speed`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn diff_depth(
+        &self,
+        symbol: &str,
+        speed: UpdateSpeed,
+    ) -> Result<EventStream<DepthUpdateEvent>, BinanceError> {
+        return self.stream(&format!(
+            "{}@depth{}",
+            symbol.to_lowercase(),
+            speed.suffix()
+        ));
+    }
+
+    /// Opens the partial depth stream of `symbol`, pushing its top `levels`
+    /// bids and asks at `speed`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn partial_depth(
+        &self,
+        symbol: &str,
+        levels: DepthLevels,
+        speed: UpdateSpeed,
+    ) -> Result<EventStream<PartialDepthEvent>, BinanceError> {
+        return self.stream(&format!(
+            "{}@depth{}{}",
+            symbol.to_lowercase(),
+            levels.count(),
+            speed.suffix()
+        ));
+    }
+
+    /// Opens the book ticker stream of `symbol`, pushing its best bid and ask
+    /// on every change.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
+    pub fn book_tickers(&self, symbol: &str) -> Result<EventStream<BookTickerEvent>, BinanceError> {
+        return self.stream(&format!("{}@bookTicker", symbol.to_lowercase()));
+    }
+
+    /// Streams aggregate trades for `symbol` into `tx`, reconnecting on failure.
+    ///
+    /// Returns once the receiver is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
     pub async fn stream_agg_trade(
         &self,
         symbol: &str,
         tx: mpsc::Sender<AggTradeEvent>,
     ) -> Result<(), BinanceError> {
-        let suffix = format!("{}@aggTrade", symbol.to_lowercase());
-        let ws_url = self.base_url.join(&suffix)?;
-
-        loop {
-            let (mut ws_stream, _) = match connect_async(&ws_url).await {
-                Ok(stream) => stream,
-                Err(e) => {
-                    log::error!("WebSocket connection failed: {e}");
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                    continue;
-                }
-            };
-
-            while let Some(message) = ws_stream.next().await {
-                match message {
-                    Ok(Message::Text(text)) => {
-                        let event: AggTradeEvent = match serde_json::from_str(&text) {
-                            Ok(event) => event,
-                            Err(e) => {
-                                log::error!("Failed to parse agg trade event: {e}");
-                                continue;
-                            }
-                        };
-                        if tx.send(event).await.is_err() {
-                            log::info!("Receiver dropped. Stopping processing.");
-                            return Ok(());
-                        }
-                    }
-                    Ok(Message::Ping(ping)) => {
-                        if ws_stream.send(Message::Pong(ping)).await.is_err() {
-                            log::error!("Failed to send pong response");
-                            break;
-                        }
-                    }
-                    Ok(Message::Close(_)) => {
-                        log::info!("WebSocket connection closed by server");
-                        break;
-                    }
-                    Ok(_) => {}
-                    Err(e) => {
-                        log::error!("WebSocket message error: {e}");
-                        break;
-                    }
-                }
-            }
-
-            log::info!("Reconnecting in 5 seconds...");
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-        }
+        forward(self.agg_trades(symbol)?, tx).await;
+        return Ok(());
     }
 
+    /// Streams raw trades for `symbol` into `tx`, reconnecting on failure.
+    ///
+    /// Returns once the receiver is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
     pub async fn stream_trades(
         &self,
         symbol: &str,
         tx: mpsc::Sender<TradeEvent>,
     ) -> Result<(), BinanceError> {
-        let suffix = format!("{}@trade", symbol.to_lowercase());
-        let ws_url = self.base_url.join(&suffix)?;
-
-        loop {
-            let (mut ws_stream, _) = match connect_async(&ws_url).await {
-                Ok(stream) => stream,
-                Err(e) => {
-                    log::error!("WebSocket connection failed: {e}");
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                    continue;
-                }
-            };
-
-            while let Some(message) = ws_stream.next().await {
-                match message {
-                    Ok(Message::Text(text)) => {
-                        let event: TradeEvent = match serde_json::from_str(&text) {
-                            Ok(event) => event,
-                            Err(e) => {
-                                log::error!("Failed to parse trade event: {e}");
-                                continue;
-                            }
-                        };
-                        if tx.send(event).await.is_err() {
-                            log::info!("Receiver dropped. Stopping processing.");
-                            return Ok(());
-                        }
-                    }
-                    Ok(Message::Ping(ping)) => {
-                        if ws_stream.send(Message::Pong(ping)).await.is_err() {
-                            log::error!("Failed to send pong response");
-                            break;
-                        }
-                    }
-                    Ok(Message::Close(_)) => {
-                        log::info!("WebSocket connection closed by server");
-                        break;
-                    }
-                    Ok(_) => {}
-                    Err(e) => {
-                        log::error!("WebSocket message error: {e}");
-                        break;
-                    }
-                }
-            }
-
-            log::info!("Reconnecting in 5 seconds...");
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-        }
+        forward(self.trades(symbol)?, tx).await;
+        return Ok(());
     }
 
+    /// Streams kline updates for `symbol` and `interval` into `tx`, reconnecting on failure.
+    ///
+    /// Returns once the receiver is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
     pub async fn stream_kline(
         &self,
         symbol: &str,
         interval: &KlineInterval,
         tx: mpsc::Sender<KlineEvent>,
     ) -> Result<(), BinanceError> {
-        let suffix = format!("{}@kline_{}", symbol.to_lowercase(), interval);
-        let ws_url = self.base_url.join(&suffix)?;
-
-        loop {
-            let (mut ws_stream, _) = match connect_async(&ws_url).await {
-                Ok(stream) => stream,
-                Err(e) => {
-                    log::error!("WebSocket connection failed: {e}");
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                    continue;
-                }
-            };
-
-            while let Some(message) = ws_stream.next().await {
-                match message {
-                    Ok(Message::Text(text)) => {
-                        let event: KlineEvent = match serde_json::from_str(&text) {
-                            Ok(event) => event,
-                            Err(e) => {
-                                log::error!("Failed to parse kline event: {e}");
-                                continue;
-                            }
-                        };
-                        if tx.send(event).await.is_err() {
-                            log::info!("Receiver dropped. Stopping processing.");
-                            return Ok(());
-                        }
-                    }
-                    Ok(Message::Ping(ping)) => {
-                        if ws_stream.send(Message::Pong(ping)).await.is_err() {
-                            log::error!("Failed to send pong response");
-                            break;
-                        }
-                    }
-                    Ok(Message::Close(_)) => {
-                        log::info!("WebSocket connection closed by server");
-                        break;
-                    }
-                    Ok(_) => {}
-                    Err(e) => {
-                        log::error!("WebSocket message error: {e}");
-                        break;
-                    }
-                }
-            }
-
-            log::info!("Reconnecting in 5 seconds...");
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-        }
+        forward(self.klines(symbol, interval)?, tx).await;
+        return Ok(());
     }
 
+    /// Streams the 24hr mini ticker for `symbol` into `tx`, reconnecting on failure.
+    ///
+    /// Returns once the receiver is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
     pub async fn stream_mini_ticker(
         &self,
         symbol: &str,
         tx: mpsc::Sender<MiniTickerEvent>,
     ) -> Result<(), BinanceError> {
-        let suffix = format!("{}@miniTicker", symbol.to_lowercase());
-        let ws_url = self.base_url.join(&suffix)?;
-
-        loop {
-            let (mut ws_stream, _) = match connect_async(&ws_url).await {
-                Ok(stream) => stream,
-                Err(e) => {
-                    log::error!("WebSocket connection failed: {e}");
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                    continue;
-                }
-            };
-
-            while let Some(message) = ws_stream.next().await {
-                match message {
-                    Ok(Message::Text(text)) => {
-                        let event: MiniTickerEvent = match serde_json::from_str(&text) {
-                            Ok(event) => event,
-                            Err(e) => {
-                                log::error!("Failed to parse mini ticker event: {e}");
-                                continue;
-                            }
-                        };
-                        if tx.send(event).await.is_err() {
-                            log::info!("Receiver dropped. Stopping processing.");
-                            return Ok(());
-                        }
-                    }
-                    Ok(Message::Ping(ping)) => {
-                        if ws_stream.send(Message::Pong(ping)).await.is_err() {
-                            log::error!("Failed to send pong response");
-                            break;
-                        }
-                    }
-                    Ok(Message::Close(_)) => {
-                        log::info!("WebSocket connection closed by server");
-                        break;
-                    }
-                    Ok(_) => {}
-                    Err(e) => {
-                        log::error!("WebSocket message error: {e}");
-                        break;
-                    }
-                }
-            }
-
-            log::info!("Reconnecting in 5 seconds...");
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-        }
+        forward(self.mini_tickers(symbol)?, tx).await;
+        return Ok(());
     }
 
+    /// Streams the 24hr ticker for `symbol` into `tx`, reconnecting on failure.
+    ///
+    /// Returns once the receiver is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream URL cannot be built.
     pub async fn stream_ticker(
         &self,
         symbol: &str,
         tx: mpsc::Sender<TickerEvent>,
     ) -> Result<(), BinanceError> {
-        let suffix = format!("{}@ticker", symbol.to_lowercase());
-        let ws_url = self.base_url.join(&suffix)?;
-
-        loop {
-            let (mut ws_stream, _) = match connect_async(&ws_url).await {
-                Ok(stream) => stream,
-                Err(e) => {
-                    log::error!("WebSocket connection failed: {e}");
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                    continue;
-                }
-            };
-
-            while let Some(message) = ws_stream.next().await {
-                match message {
-                    Ok(Message::Text(text)) => {
-                        let event: TickerEvent = match serde_json::from_str(&text) {
-                            Ok(event) => event,
-                            Err(e) => {
-                                log::error!("Failed to parse ticker event: {e}");
-                                continue;
-                            }
-                        };
-                        if tx.send(event).await.is_err() {
-                            log::info!("Receiver dropped. Stopping processing.");
-                            return Ok(());
-                        }
-                    }
-                    Ok(Message::Ping(ping)) => {
-                        if ws_stream.send(Message::Pong(ping)).await.is_err() {
-                            log::error!("Failed to send pong response");
-                            break;
-                        }
-                    }
-                    Ok(Message::Close(_)) => {
-                        log::info!("WebSocket connection closed by server");
-                        break;
-                    }
-                    Ok(_) => {}
-                    Err(e) => {
-                        log::error!("WebSocket message error: {e}");
-                        break;
-                    }
-                }
-            }
-
-            log::info!("Reconnecting in 5 seconds...");
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-        }
+        forward(self.tickers(symbol)?, tx).await;
+        return Ok(());
     }
 }
 
@@ -317,10 +660,13 @@ mod tests {
 
     use rust_decimal::Decimal;
 
+    use crate::mock::MockServer;
+
     #[tokio::test]
     async fn test_stream_agg_trade() {
         let (tx, mut rx) = mpsc::channel(1);
-        let ws = BinanceWebSocket::new(true);
+        let server = MockServer::start().await;
+        let ws = BinanceWebSocket::with_base_url(server.stream_url());
 
         tokio::spawn(async move {
             ws.stream_agg_trade("btcusdt", tx).await.unwrap();
@@ -336,7 +682,8 @@ mod tests {
     #[tokio::test]
     async fn test_stream_trades() {
         let (tx, mut rx) = mpsc::channel(1);
-        let ws = BinanceWebSocket::new(true);
+        let server = MockServer::start().await;
+        let ws = BinanceWebSocket::with_base_url(server.stream_url());
 
         tokio::spawn(async move {
             ws.stream_trades("btcusdt", tx).await.unwrap();
@@ -352,7 +699,8 @@ mod tests {
     #[tokio::test]
     async fn test_stream_kline() {
         let (tx, mut rx) = mpsc::channel(1);
-        let ws = BinanceWebSocket::new(true);
+        let server = MockServer::start().await;
+        let ws = BinanceWebSocket::with_base_url(server.stream_url());
 
         tokio::spawn(async move {
             ws.stream_kline("btcusdt", &KlineInterval::OneMinute, tx)
@@ -370,7 +718,8 @@ mod tests {
     #[tokio::test]
     async fn test_stream_mini_ticker() {
         let (tx, mut rx) = mpsc::channel(1);
-        let ws = BinanceWebSocket::new(true);
+        let server = MockServer::start().await;
+        let ws = BinanceWebSocket::with_base_url(server.stream_url());
 
         tokio::spawn(async move {
             ws.stream_mini_ticker("btcusdt", tx).await.unwrap();
@@ -384,7 +733,8 @@ mod tests {
     #[tokio::test]
     async fn test_stream_ticker() {
         let (tx, mut rx) = mpsc::channel(1);
-        let ws = BinanceWebSocket::new(true);
+        let server = MockServer::start().await;
+        let ws = BinanceWebSocket::with_base_url(server.stream_url());
 
         tokio::spawn(async move {
             ws.stream_ticker("btcusdt", tx).await.unwrap();
@@ -394,4 +744,174 @@ mod tests {
             assert_eq!(event.symbol, "BTCUSDT");
         }
     }
+
+    #[tokio::test]
+    async fn test_event_stream() {
+        let server = MockServer::start().await;
+        let ws = BinanceWebSocket::with_base_url(server.stream_url());
+
+        let mut trades = ws.trades("BTCUSDT").unwrap();
+        assert_eq!(trades.name(), "btcusdt@trade");
+        let event = trades.next().await.unwrap().unwrap();
+        assert_eq!(event.symbol, "BTCUSDT");
+        assert_eq!(*trades.state().borrow(), ConnectionState::Connected);
+
+        // Events of the wrong type are reported instead of dropped.
+        let mut klines = ws.stream::<KlineEvent>("btcusdt@trade").unwrap();
+        let error = klines.next().await.unwrap().unwrap_err();
+        assert!(matches!(error, BinanceError::InvalidEvent(_, text) if text.contains("\"trade\"")));
+    }
+
+    #[tokio::test]
+    async fn test_book_streams() {
+        let server = MockServer::start().await;
+        let ws = BinanceWebSocket::with_base_url(server.stream_url());
+
+        let mut depth = ws
+            .partial_depth("BTCUSDT", DepthLevels::Ten, UpdateSpeed::Fast)
+            .unwrap();
+        assert_eq!(depth.name(), "btcusdt@depth10@100ms");
+        let book = depth.next().await.unwrap().unwrap();
+        assert_eq!(book.bids.len(), 10);
+        assert_eq!(book.asks.len(), 10);
+        assert!(book.bids[0].price < book.asks[0].price);
+
+        let mut tickers = ws.book_tickers("BTCUSDT").unwrap();
+        let ticker = tickers.next().await.unwrap().unwrap();
+        assert_eq!(ticker.symbol, "BTCUSDT");
+        assert!(ticker.best_bid_price < ticker.best_ask_price);
+        let next = tickers.next().await.unwrap().unwrap();
+        assert!(next.update_id > ticker.update_id);
+    }
+
+    #[tokio::test]
+    async fn test_ticker_streams() {
+        let server = MockServer::start().await;
+        let ws = BinanceWebSocket::with_base_url(server.stream_url());
+
+        let mut tickers = ws.all_tickers().unwrap();
+        let tickers = tickers.next().await.unwrap().unwrap();
+        assert_eq!(tickers.len(), 4);
+        assert!(tickers.iter().any(|t| t.symbol == "ETHBTC"));
+
+        let mut mini_tickers = ws.all_mini_tickers().unwrap();
+        assert_eq!(mini_tickers.next().await.unwrap().unwrap().len(), 4);
+
+        let mut window_tickers = ws.window_tickers("ETHUSDT", &WindowSize::Hours(4)).unwrap();
+        assert_eq!(window_tickers.name(), "ethusdt@ticker_4h");
+        let ticker = window_tickers.next().await.unwrap().unwrap();
+        assert_eq!(ticker.event_type, "4hTicker");
+        assert_eq!(ticker.close_time - ticker.open_time, 4 * 3_600_000);
+
+        let mut window_tickers = ws.all_window_tickers(&WindowSize::Days(1)).unwrap();
+        assert_eq!(window_tickers.name(), "!ticker_1d@arr");
+        let tickers = window_tickers.next().await.unwrap().unwrap();
+        assert!(tickers.iter().all(|t| t.event_type == "1dTicker"));
+
+        let error = ws.all_window_tickers(&WindowSize::Minutes(30)).unwrap_err();
+        assert!(matches!(error, BinanceError::NotAllowed(_)));
+
+        let mut avg_prices = ws.avg_prices("BTCUSDT").unwrap();
+        let avg_price = avg_prices.next().await.unwrap().unwrap();
+        assert_eq!(avg_price.price, Decimal::from(85_000));
+        assert_eq!(avg_price.interval, "5m");
+    }
+
+    #[tokio::test]
+    async fn test_kline_streams() {
+        let server = MockServer::start().await;
+        let ws = BinanceWebSocket::with_base_url(server.stream_url());
+
+        let mut klines = ws
+            .klines_with_timezone("BTCUSDT", &KlineInterval::OneDay, "+08:00")
+            .unwrap();
+        assert_eq!(klines.name(), "btcusdt@kline_1d@+08:00");
+        let event = klines.next().await.unwrap().unwrap();
+        let open_time = event.kline.start_time.timestamp_millis();
+        assert_eq!((open_time + 8 * 3_600_000) % 86_400_000, 0);
+
+        let mut closed = ws
+            .closed_klines("BTCUSDT", &KlineInterval::OneMinute, None)
+            .unwrap();
+        let kline = closed.next().await.unwrap().unwrap();
+        assert_eq!(kline.close_price, Decimal::from(85_000));
+        assert_eq!(kline.number_of_trades, 10);
+        assert_eq!(
+            (kline.close_time - kline.open_time).num_milliseconds(),
+            59_999
+        );
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_reconnects() {
+        let server = MockServer::start().await;
+        let ws = BinanceWebSocket::with_base_url(server.stream_url())
+            .with_backoff(Backoff::fixed(Duration::from_millis(100)));
+        let mut trades = ws.trades("btcusdt").unwrap();
+        let mut state = trades.state();
+        trades.next().await.unwrap().unwrap();
+
+        server.disconnect_streams();
+        state
+            .wait_for(|s| matches!(s, ConnectionState::Reconnecting { attempt: 1, .. }))
+            .await
+            .unwrap();
+        state
+            .wait_for(|s| *s == ConnectionState::Connected)
+            .await
+            .unwrap();
+        assert!(trades.next().await.unwrap().is_ok());
+
+        // Without a server to reconnect to, the stream ends once the backoff gives up.
+        let ws = BinanceWebSocket::with_base_url(Url::parse("ws://127.0.0.1:1/ws").unwrap())
+            .with_backoff(Backoff {
+                max_attempts: Some(2),
+                ..Backoff::fixed(Duration::from_millis(10))
+            });
+        let mut trades = ws.trades("btcusdt").unwrap();
+        assert!(trades.next().await.is_none());
+        assert!(matches!(
+            *trades.state().borrow(),
+            ConnectionState::Disconnected { .. }
+        ));
+
+        // Neither does a server closing every connection before any event.
+        let ws = BinanceWebSocket::with_base_url(server.stream_url()).with_backoff(Backoff {
+            max_attempts: Some(2),
+            ..Backoff::fixed(Duration::from_millis(10))
+        });
+        let mut trades = ws.trades("xyzusdt").unwrap();
+        let end = tokio::time::timeout(Duration::from_secs(5), trades.next()).await;
+        assert!(matches!(end, Ok(None)));
+        assert!(matches!(
+            *trades.state().borrow(),
+            ConnectionState::Disconnected { .. }
+        ));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let backoff = Backoff {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+            multiplier: 2.0,
+            jitter: 0.5,
+            max_attempts: None,
+        };
+        for (failures, base) in [(0, 100), (1, 200), (3, 800), (4, 1000), (40, 1000)] {
+            let delay = backoff.delay(failures);
+            assert!(
+                delay <= Duration::from_millis(base),
+                "{failures}: {delay:?}"
+            );
+            assert!(
+                delay >= Duration::from_millis(base / 2),
+                "{failures}: {delay:?}"
+            );
+        }
+        assert_eq!(
+            Backoff::fixed(Duration::from_secs(5)).delay(7),
+            Duration::from_secs(5)
+        );
+    }
 }
@@ -0,0 +1,692 @@
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{Instant, sleep_until};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message};
+use url::Url;
+
+use crate::clock::ServerClock;
+use crate::errors::BinanceError;
+use crate::signer::Signer;
+use crate::types::account::Account;
+use crate::types::general::Error;
+use crate::types::trading::{
+    CancelReplaceOrderData, CancelReplaceResult, Order, OrderCreationData,
+};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+type PendingResponse = oneshot::Sender<Result<Value, BinanceError>>;
+
+/// A request waiting to be sent by the connection task.
+struct Command {
+    method: &'static str,
+    params: Map<String, Value>,
+    response: PendingResponse,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    id: Option<u64>,
+    status: u16,
+    #[serde(default)]
+    result: Value,
+    error: Option<Error>,
+}
+
+/// Places and queries orders through the Binance WebSocket API, which avoids
+/// the connection overhead of a REST request per order.
+///
+/// The connection is opened on the first request. With an Ed25519 key it is
+/// authenticated with `session.logon`, so later requests only carry a
+/// timestamp; Binance does not accept other keys for `session.logon`, so with
+/// HMAC and RSA keys every request is signed instead. When the connection is
+/// lost it is opened and authenticated again; requests still waiting for a
+/// response fail with [`BinanceError::ConnectionLost`], and requests without
+/// a response within the request timeout with [`BinanceError::Timeout`].
+/// Clones share the connection,
+/// which is closed once every clone is dropped.
+#[derive(Debug, Clone)]
+pub struct BinanceWsApiClient {
+    url: Url,
+    api_key: String,
+    signer: Arc<dyn Signer>,
+    clock: Option<ServerClock>,
+    reconnect_delay: Duration,
+    request_timeout: Duration,
+    commands: Arc<OnceLock<mpsc::UnboundedSender<Command>>>,
+}
+
+impl BinanceWsApiClient {
+    /// Creates a client for the WebSocket API at `url`
+    /// (e.g. `wss://ws-api.binance.com/ws-api/v3`).
+    #[must_use]
+    pub fn new(url: Url, api_key: String, signer: Arc<dyn Signer>) -> Self {
+        return Self {
+            url,
+            api_key,
+            signer,
+            clock: None,
+            reconnect_delay: RECONNECT_DELAY,
+            request_timeout: REQUEST_TIMEOUT,
+            commands: Arc::default(),
+        };
+    }
+
+    /// Stamps requests with `clock` instead of the local time.
+    #[must_use]
+    pub fn with_clock(mut self, clock: ServerClock) -> Self {
+        self.clock = Some(clock);
+        return self;
+    }
+
+    /// Sets how long to wait before reconnecting after a connection failure.
+    /// Defaults to 5 seconds.
+    #[must_use]
+    pub const fn with_reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        return self;
+    }
+
+    /// Sets how long to wait for the connection to be authenticated and for
+    /// the response of each request. Defaults to 10 seconds.
+    #[must_use]
+    pub const fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        return self;
+    }
+
+    #[must_use]
+    pub const fn url(&self) -> &Url {
+        return &self.url;
+    }
+
+    /// Places a new order (`order.place`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection fails or Binance rejects the order.
+    pub async fn create_order(&self, order_data: OrderCreationData) -> Result<Order, BinanceError> {
+        return self.request("order.place", &order_data).await;
+    }
+
+    /// Checks the status of an order (`order.status`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither `order_id` nor `orig_client_order_id` is
+    /// given, the connection fails or the order does not exist.
+    pub async fn get_order(
+        &self,
+        symbol: &str,
+        order_id: Option<u64>,
+        orig_client_order_id: Option<&str>,
+        recv_window: Option<u32>,
+    ) -> Result<Order, BinanceError> {
+        if order_id.is_none() && orig_client_order_id.is_none() {
+            return Err(BinanceError::MissingParameter(
+                "Either orderId or origClientOrderId must be provided".to_string(),
+            ));
+        }
+
+        let params = json!({
+            "symbol": symbol,
+            "orderId": order_id,
+            "origClientOrderId": orig_client_order_id,
+            "recvWindow": recv_window,
+        });
+        return self.request("order.status", &params).await;
+    }
+
+    /// Lists the open orders of `symbol`, or of every symbol
+    /// (`openOrders.status`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection fails or Binance rejects the request.
+    pub async fn get_open_orders(
+        &self,
+        symbol: Option<&str>,
+        recv_window: Option<u32>,
+    ) -> Result<Vec<Order>, BinanceError> {
+        let params = json!({ "symbol": symbol, "recvWindow": recv_window });
+        return self.request("openOrders.status", &params).await;
+    }
+
+    /// Cancels an open order (`order.cancel`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither `order_id` nor `orig_client_order_id` is
+    /// given, the connection fails or the order cannot be canceled.
+    pub async fn cancel_order(
+        &self,
+        symbol: &str,
+        order_id: Option<u64>,
+        orig_client_order_id: Option<&str>,
+        new_client_order_id: Option<&str>,
+        cancel_restriction: Option<&str>,
+        recv_window: Option<u32>,
+    ) -> Result<Order, BinanceError> {
+        if order_id.is_none() && orig_client_order_id.is_none() {
+            return Err(BinanceError::MissingParameter(
+                "Either orderId or origClientOrderId must be provided".to_string(),
+            ));
+        }
+
+        let params = json!({
+            "symbol": symbol,
+            "orderId": order_id,
+            "origClientOrderId": orig_client_order_id,
+            "newClientOrderId": new_client_order_id,
+            "cancelRestrictions": cancel_restriction,
+            "recvWindow": recv_window,
+        });
+        return self.request("order.cancel", &params).await;
+    }
+
+    /// Cancels an order and places a new one on the same symbol
+    /// (`order.cancelReplace`).
+    ///
+    /// Like the REST `cancel_replace_order`, the results reported by -2021
+    /// and -2022 errors are returned as a [`CancelReplaceResult`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the order to cancel is not identified, the
+    /// connection fails or Binance rejects the request.
+    pub async fn cancel_replace_order(
+        &self,
+        order_data: CancelReplaceOrderData,
+    ) -> Result<CancelReplaceResult, BinanceError> {
+        if order_data.cancel_order_id.is_none() && order_data.cancel_orig_client_order_id.is_none()
+        {
+            return Err(BinanceError::MissingParameter(
+                "Either cancelOrderId or cancelOrigClientOrderId must be provided".to_string(),
+            ));
+        }
+
+        let result = self.request("order.cancelReplace", &order_data).await;
+        return CancelReplaceResult::from_result(result);
+    }
+
+    /// Returns the account information (`account.status`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection fails or Binance rejects the request.
+    pub async fn get_account(
+        &self,
+        omit_zero_balances: Option<bool>,
+        recv_window: Option<u32>,
+    ) -> Result<Account, BinanceError> {
+        let params = json!({
+            "omitZeroBalances": omit_zero_balances,
+            "recvWindow": recv_window,
+        });
+        return self.request("account.status", &params).await;
+    }
+
+    /// Sends a signed request and decodes its result as `T`.
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: &'static str,
+        params: &(impl Serialize + Sync),
+    ) -> Result<T, BinanceError> {
+        let Value::Object(mut params) = serde_json::to_value(params)? else {
+            return Err(BinanceError::MissingParameter(format!(
+                "{method} parameters must be an object"
+            )));
+        };
+        params.retain(|_, value| !value.is_null());
+        params.insert("timestamp".to_string(), json!(self.timestamp().await));
+        if !self.signer.supports_session_logon() {
+            params.insert("apiKey".to_string(), json!(self.api_key));
+            let signature = self.signer.sign(&signature_payload(&params));
+            params.insert("signature".to_string(), json!(signature));
+        }
+
+        let (response, receiver) = oneshot::channel();
+        self.commands()
+            .send(Command {
+                method,
+                params,
+                response,
+            })
+            .map_err(|_| BinanceError::ConnectionLost)?;
+        let result = receiver.await.map_err(|_| BinanceError::ConnectionLost)??;
+        return Ok(serde_json::from_value(result)?);
+    }
+
+    async fn timestamp(&self) -> i64 {
+        return match &self.clock {
+            Some(clock) => clock.timestamp().await,
+            None => chrono::Utc::now().timestamp_millis(),
+        };
+    }
+
+    /// Returns the channel to the connection task, starting it on first use.
+    fn commands(&self) -> &mpsc::UnboundedSender<Command> {
+        return self.commands.get_or_init(|| {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            let connection = Connection {
+                url: self.url.clone(),
+                api_key: self.api_key.clone(),
+                signer: self.signer.clone(),
+                clock: self.clock.clone(),
+                reconnect_delay: self.reconnect_delay,
+                request_timeout: self.request_timeout,
+                next_id: 1,
+            };
+            tokio::spawn(connection.run(receiver));
+            sender
+        });
+    }
+}
+
+/// Joins `params` sorted by name as `key=value` pairs, the payload Binance
+/// expects WebSocket API signatures to cover.
+fn signature_payload(params: &Map<String, Value>) -> String {
+    let mut pairs = params.iter().collect::<Vec<_>>();
+    pairs.sort_by_key(|(key, _)| *key);
+    return pairs
+        .iter()
+        .map(|(key, value)| {
+            let value = value
+                .as_str()
+                .map_or_else(|| value.to_string(), str::to_string);
+            format!("{key}={value}")
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+}
+
+/// The state of the task owning the WebSocket connection.
+struct Connection {
+    url: Url,
+    api_key: String,
+    signer: Arc<dyn Signer>,
+    clock: Option<ServerClock>,
+    reconnect_delay: Duration,
+    request_timeout: Duration,
+    next_id: u64,
+}
+
+impl Connection {
+    /// Forwards commands to the WebSocket API until every client is dropped,
+    /// reconnecting and logging on again whenever the connection is lost.
+    async fn run(mut self, mut commands: mpsc::UnboundedReceiver<Command>) {
+        loop {
+            let ws_stream = match self.connect().await {
+                Ok(ws_stream) => ws_stream,
+                Err(e) => {
+                    log::error!("WebSocket API connection failed: {e}");
+                    if !self.reject_until_reconnect(&mut commands, &e).await {
+                        return;
+                    }
+                    continue;
+                }
+            };
+            if !self.serve(ws_stream, &mut commands).await {
+                return;
+            }
+            log::info!("WebSocket API connection lost, reconnecting");
+        }
+    }
+
+    /// Connects and authenticates the session, giving up after the request
+    /// timeout.
+    async fn connect(&mut self) -> Result<WsStream, BinanceError> {
+        let timeout = self.request_timeout;
+        return tokio::time::timeout(timeout, self.open())
+            .await
+            .map_err(|_| BinanceError::Timeout(timeout))?;
+    }
+
+    /// Connects and authenticates the session with `session.logon` when the
+    /// signer supports it.
+    async fn open(&mut self) -> Result<WsStream, BinanceError> {
+        let (mut ws_stream, _) = connect_async(self.url.as_str()).await?;
+        if !self.signer.supports_session_logon() {
+            return Ok(ws_stream);
+        }
+
+        let timestamp = match &self.clock {
+            Some(clock) => clock.timestamp().await,
+            None => chrono::Utc::now().timestamp_millis(),
+        };
+        // The signature covers the parameters sorted by name.
+        let payload = format!("apiKey={}&timestamp={timestamp}", self.api_key);
+        let id = self.next_id();
+        let request = json!({
+            "id": id,
+            "method": "session.logon",
+            "params": {
+                "apiKey": self.api_key,
+                "timestamp": timestamp,
+                "signature": self.signer.sign(&payload),
+            },
+        });
+        ws_stream.send(Message::text(request.to_string())).await?;
+
+        while let Some(message) = ws_stream.next().await {
+            let Message::Text(text) = message? else {
+                continue;
+            };
+            let response: Response = serde_json::from_str(&text)?;
+            if response.id == Some(id) {
+                return match response.error {
+                    Some(error) => Err(BinanceError::Api(response.status, error)),
+                    None => Ok(ws_stream),
+                };
+            }
+        }
+        return Err(BinanceError::NotConnected(
+            "connection closed during session.logon".to_string(),
+        ));
+    }
+
+    /// Fails the commands received while waiting to reconnect. Returns false
+    /// once every client is dropped.
+    async fn reject_until_reconnect(
+        &self,
+        commands: &mut mpsc::UnboundedReceiver<Command>,
+        error: &BinanceError,
+    ) -> bool {
+        let reconnect = tokio::time::sleep(self.reconnect_delay);
+        tokio::pin!(reconnect);
+        loop {
+            tokio::select! {
+                () = &mut reconnect => return true,
+                command = commands.recv() => {
+                    let Some(command) = command else {
+                        return false;
+                    };
+                    let error = BinanceError::NotConnected(error.to_string());
+                    let _ = command.response.send(Err(error));
+                }
+            }
+        }
+    }
+
+    /// Sends commands and routes responses by id until the connection is
+    /// lost. Returns false once every client is dropped.
+    ///
+    /// Requests without a response within the request timeout are failed
+    /// with [`BinanceError::Timeout`]; dropping the pending requests on
+    /// return fails them with [`BinanceError::ConnectionLost`].
+    async fn serve(
+        &mut self,
+        mut ws_stream: WsStream,
+        commands: &mut mpsc::UnboundedReceiver<Command>,
+    ) -> bool {
+        let mut pending = HashMap::new();
+        loop {
+            let next_deadline = pending.values().map(|(deadline, _)| *deadline).min();
+            tokio::select! {
+                () = sleep_until(next_deadline.unwrap_or_else(Instant::now)), if next_deadline.is_some() => {
+                    self.expire(&mut pending);
+                }
+                command = commands.recv() => {
+                    let Some(command) = command else {
+                        let _ = ws_stream.close(None).await;
+                        return false;
+                    };
+                    let id = self.next_id();
+                    let request = json!({
+                        "id": id,
+                        "method": command.method,
+                        "params": command.params,
+                    });
+                    log::debug!("WebSocket API {}", command.method);
+                    if let Err(e) = ws_stream.send(Message::text(request.to_string())).await {
+                        log::error!("Failed to send WebSocket API request: {e}");
+                        let _ = command.response.send(Err(e.into()));
+                        return true;
+                    }
+                    let deadline = Instant::now() + self.request_timeout;
+                    pending.insert(id, (deadline, command.response));
+                }
+                message = ws_stream.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        let response: Response = match serde_json::from_str(&text) {
+                            Ok(response) => response,
+                            Err(e) => {
+                                log::error!("Failed to parse WebSocket API response: {e}");
+                                continue;
+                            }
+                        };
+                        let Some((_, sender)) = response.id.and_then(|id| pending.remove(&id)) else {
+                            log::warn!("Unexpected WebSocket API response: {text}");
+                            continue;
+                        };
+                        let result = match response.error {
+                            Some(error) => Err(BinanceError::Api(response.status, error)),
+                            None => Ok(response.result),
+                        };
+                        let _ = sender.send(result);
+                    }
+                    Some(Ok(Message::Ping(ping))) => {
+                        if ws_stream.send(Message::Pong(ping)).await.is_err() {
+                            log::error!("Failed to send pong response");
+                            return true;
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => {
+                        log::info!("WebSocket API connection closed by server");
+                        return true;
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        log::error!("WebSocket API message error: {e}");
+                        return true;
+                    }
+                },
+            }
+        }
+    }
+
+    /// Fails the pending requests whose deadline has passed.
+    fn expire(&self, pending: &mut HashMap<u64, (Instant, PendingResponse)>) {
+        let now = Instant::now();
+        let expired = pending
+            .iter()
+            .filter(|(_, (deadline, _))| *deadline <= now)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in expired {
+            log::warn!("No response to WebSocket API request {id}");
+            if let Some((_, response)) = pending.remove(&id) {
+                let _ = response.send(Err(BinanceError::Timeout(self.request_timeout)));
+            }
+        }
+    }
+
+    const fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        return id;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use crate::BinanceClient;
+    use crate::mock::{MOCK_API_KEY, MockServer};
+    use crate::signer::Ed25519Signer;
+    use crate::types::trading::{
+        CancelReplaceMode, CommonOrderCreateData, OrderSide, OrderStatus, TimeInForce,
+    };
+
+    use super::*;
+
+    async fn ed25519_server() -> MockServer {
+        return MockServer::with_signer(MOCK_API_KEY, Ed25519Signer::from_bytes(&[7; 32])).await;
+    }
+
+    fn limit_order(price: u32) -> OrderCreationData {
+        return OrderCreationData::Limit {
+            common: CommonOrderCreateData {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Buy,
+                ..Default::default()
+            },
+            time_in_force: TimeInForce::Gtc,
+            quantity: Decimal::from_str_exact("0.01").unwrap(),
+            price: Decimal::from(price),
+            iceberg_qty: None,
+        };
+    }
+
+    fn logons(server: &MockServer) -> usize {
+        return server
+            .requests()
+            .iter()
+            .filter(|r| r.path == "session.logon")
+            .count();
+    }
+
+    #[tokio::test]
+    async fn test_ws_api_orders() {
+        let server = ed25519_server().await;
+        let ws_api = server.client().ws_api;
+
+        let order = ws_api.create_order(limit_order(80_000)).await.unwrap();
+        assert_eq!(order.status, OrderStatus::New);
+
+        let queried = ws_api
+            .get_order("BTCUSDT", Some(order.order_id), None, None)
+            .await
+            .unwrap();
+        assert_eq!(queried.order_id, order.order_id);
+        let open = ws_api.get_open_orders(Some("BTCUSDT"), None).await.unwrap();
+        assert_eq!(open.len(), 1);
+
+        let data = CancelReplaceOrderData::new(
+            CancelReplaceMode::StopOnFailure,
+            order.order_id,
+            limit_order(81_000),
+        );
+        let result = ws_api.cancel_replace_order(data).await.unwrap();
+        assert!(result.is_success());
+        let new_order_id = result.new_order().unwrap().order_id;
+
+        let canceled = ws_api
+            .cancel_order("BTCUSDT", Some(new_order_id), None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(canceled.status, OrderStatus::Canceled);
+
+        let account = ws_api.get_account(Some(true), None).await.unwrap();
+        assert!(!account.balances.is_empty());
+
+        // The session is authenticated once; requests are not signed.
+        assert_eq!(logons(&server), 1);
+        let request = server.requests().pop().unwrap();
+        assert_eq!(request.path, "account.status");
+        assert!(request.param("timestamp").is_some());
+        assert!(request.param("signature").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ws_api_signed_requests() {
+        // HMAC keys cannot log on; every request is signed instead.
+        let server = MockServer::start().await;
+        let ws_api = server.client().ws_api;
+
+        let order = ws_api.create_order(limit_order(80_000)).await.unwrap();
+        assert_eq!(order.status, OrderStatus::New);
+        let open = ws_api.get_open_orders(Some("BTCUSDT"), None).await.unwrap();
+        assert_eq!(open.len(), 1);
+
+        assert_eq!(logons(&server), 0);
+        let request = server.requests().pop().unwrap();
+        assert_eq!(request.path, "openOrders.status");
+        assert_eq!(request.api_key.as_deref(), Some(MOCK_API_KEY));
+        assert!(request.param("signature").is_some());
+
+        let ws_api = BinanceClient::builder(MOCK_API_KEY.to_string(), "wrong".to_string())
+            .base_url(server.url().as_str())
+            .ws_api_url(server.stream_url().as_str())
+            .build()
+            .unwrap()
+            .ws_api;
+        let result = ws_api.get_open_orders(None, None).await;
+        assert!(matches!(result, Err(BinanceError::Api(400, e)) if e.code == -1022));
+    }
+
+    #[tokio::test]
+    async fn test_ws_api_errors() {
+        let server = ed25519_server().await;
+        let ws_api = server.client().ws_api;
+
+        let result = ws_api.get_order("BTCUSDT", Some(42), None, None).await;
+        assert!(matches!(result, Err(BinanceError::Api(400, e)) if e.code == -2013));
+
+        // Requests fail without being sent while the session cannot log on.
+        let ws_api = BinanceClient::builder(MOCK_API_KEY.to_string(), String::new())
+            .signer(Ed25519Signer::from_bytes(&[8; 32]))
+            .base_url(server.url().as_str())
+            .ws_api_url(server.stream_url().as_str())
+            .build()
+            .unwrap()
+            .ws_api;
+        let result = ws_api.get_open_orders(None, None).await;
+        assert!(matches!(result, Err(BinanceError::NotConnected(msg)) if msg.contains("-1022")));
+    }
+
+    #[tokio::test]
+    async fn test_ws_api_timeout() {
+        let server = ed25519_server().await;
+        let ws_api = server
+            .client()
+            .ws_api
+            .with_request_timeout(Duration::from_millis(200));
+        ws_api.get_open_orders(None, None).await.unwrap();
+
+        // A request without a response fails without blocking the others.
+        server.ignore_ws_api_method("order.status");
+        let result = ws_api.get_order("BTCUSDT", Some(42), None, None).await;
+        assert!(matches!(result, Err(BinanceError::Timeout(_))));
+        assert!(ws_api.get_open_orders(None, None).await.is_ok());
+
+        // So does a session that cannot log on.
+        server.ignore_ws_api_method("session.logon");
+        let ws_api = server
+            .client()
+            .ws_api
+            .with_request_timeout(Duration::from_millis(200));
+        let result = ws_api.get_open_orders(None, None).await;
+        assert!(
+            matches!(result, Err(BinanceError::NotConnected(msg)) if msg.contains("No response"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ws_api_reconnect() {
+        let server = ed25519_server().await;
+        let ws_api = server.client().ws_api;
+        ws_api.get_open_orders(None, None).await.unwrap();
+
+        server.disconnect_ws_api();
+        while logons(&server) < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let order = ws_api.create_order(limit_order(80_000)).await.unwrap();
+        assert_eq!(server.orders().len(), 1);
+        assert_eq!(server.orders()[0]["orderId"], order.order_id);
+    }
+}